@@ -1,21 +1,46 @@
 mod error;
 mod model;
 mod pipeline;
-pub(crate) use model::{command, config, event, snapshot, state};
-pub(crate) use pipeline::{backend, decoder, runtime, service, source};
+pub(crate) use model::{analysis, command, config, equalizer, event, metadata, snapshot, state};
+#[cfg(feature = "test-support")]
+pub use pipeline::fixtures;
+#[cfg(feature = "codec-opus")]
+pub(crate) use pipeline::opus;
+pub(crate) use pipeline::{
+    analyzer, backend, cache, channels, codecs, decoder, devices, dsp, loudness, ncm, offline,
+    prefetch, runtime, service, source, stretch, tags,
+};
 
+pub use analysis::{AudioAnalysis, ChannelLevel};
+pub use analyzer::AnalysisProducer;
 pub use backend::{AudioDevice, OutputBackend, OutputSession, backend_for_kind};
+pub use cache::CachingSourceFactory;
 pub use command::AudioCommand;
 pub use config::{
-    AudioConfig, NetworkConfig, OutputBackendKind, ResampleQualityPreset, RuntimeConfigPatch,
+    AudioConfig, ChannelMixMode, DEFAULT_STREAM_CACHE_BYTES, DevicePolicy, MAX_ANALYSIS_HZ,
+    MAX_CROSSFADE_MS, MAX_MANUAL_FADE_MS, MAX_PLAYBACK_RATE, MAX_REBUFFER_MS, MAX_SLEEP_FADE_MS,
+    MAX_SPECTRUM_BINS, MAX_TARGET_LUFS, MIN_PLAYBACK_RATE, MIN_TARGET_LUFS, NetworkConfig,
+    NormalizationMode, OfflineClock, OfflineOutputConfig, OutputBackendKind, OutputMode,
+    ResampleQualityPreset, RuntimeConfigPatch, StreamCacheConfig,
+};
+pub use dsp::DspStage;
+pub use equalizer::{
+    EQ_BAND_COUNT, EQ_BAND_FREQUENCIES, EqBand, EqualizerPreset, EqualizerSettings, MAX_EQ_GAIN_DB,
 };
 pub use error::{AudioError, Result};
 pub use event::AudioEvent;
-pub use service::{AudioRuntimeHandle, AudioService};
-pub use snapshot::AudioSnapshot;
+pub use metadata::{Artwork, TrackMetadata};
+pub use ncm::{NcmFile, NcmMetadata};
+pub use offline::OfflineBackend;
+#[cfg(feature = "test-support")]
+pub use offline::OfflineFaults;
+pub use service::{
+    AudioRuntimeHandle, AudioService, LatestReceiver, SubscriptionHandle, SubscriptionStream,
+};
+pub use snapshot::{AudioSnapshot, OutputFormat, TrackFormat};
 pub use source::{
     DefaultSourceFactory, FileSource, NetworkSource, OpenedSource, SeekTarget, Source,
-    SourceFactory, SourceSpec,
+    SourceFactory, SourceSpec, StreamCacheKey,
 };
 pub use state::EngineState;
 
@@ -0,0 +1,13 @@
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ChannelLevel {
+    pub peak: f32,
+    pub rms: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AudioAnalysis {
+    pub sample_rate: u32,
+    pub levels: Vec<ChannelLevel>,
+    // Log-spaced bands from 20 Hz to Nyquist; a full-scale sine reads 1.0.
+    pub spectrum: Vec<f32>,
+}
@@ -1,4 +1,4 @@
-use crate::{RuntimeConfigPatch, SeekTarget, SourceSpec};
+use crate::{EqualizerSettings, RuntimeConfigPatch, SeekTarget, SourceSpec};
 
 #[derive(Debug, Clone)]
 pub enum AudioCommand {
@@ -7,13 +7,29 @@ pub enum AudioCommand {
         start_ms: u64,
         autoplay: bool,
     },
+    SetNext(Option<SourceSpec>),
     Play,
     Pause,
     Stop,
     Seek(SeekTarget),
+    // Repeats `start_ms..end_ms` of the current track until cleared or another
+    // source is opened.
+    SetLoop {
+        start_ms: u64,
+        end_ms: u64,
+    },
+    ClearLoop,
     SetVolume(f32),
+    SetPlaybackRate(f32),
+    // Pauses playback `after_ms` from now, fading out over the last `fade_ms`.
+    StartSleepTimer {
+        after_ms: u64,
+        fade_ms: u64,
+    },
+    CancelSleepTimer,
     SwitchBackend(crate::OutputBackendKind),
     SwitchDevice(Option<String>),
+    SetEqualizer(EqualizerSettings),
     UpdateConfig(RuntimeConfigPatch),
     Shutdown,
 }
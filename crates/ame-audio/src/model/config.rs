@@ -1,3 +1,17 @@
+use std::path::PathBuf;
+
+pub const MAX_CROSSFADE_MS: u64 = 12_000;
+pub const MAX_MANUAL_FADE_MS: u64 = 2_000;
+pub const MAX_REBUFFER_MS: u64 = 1_500;
+pub const MAX_SLEEP_FADE_MS: u64 = 60_000;
+pub const MIN_PLAYBACK_RATE: f32 = 0.5;
+pub const MAX_PLAYBACK_RATE: f32 = 3.0;
+pub const MAX_ANALYSIS_HZ: u32 = 120;
+pub const MAX_SPECTRUM_BINS: usize = 256;
+pub const MIN_TARGET_LUFS: f32 = -30.0;
+pub const MAX_TARGET_LUFS: f32 = -5.0;
+pub const DEFAULT_STREAM_CACHE_BYTES: u64 = 1024 * 1024 * 1024;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ResampleQualityPreset {
     LowLatency,
@@ -6,11 +20,78 @@ pub enum ResampleQualityPreset {
     Auto,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormalizationMode {
+    Off,
+    Track,
+    Album,
+}
+
+// How decoded channels are fitted to the output. `Downmix` folds layouts the
+// device cannot play into the speakers it has; `Upmix` additionally spreads
+// mono and stereo over multichannel outputs; `Discrete` maps by index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelMixMode {
+    Downmix,
+    Upmix,
+    Discrete,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OutputBackendKind {
     PlatformDefault,
     Wasapi,
     Asio,
+    Null,
+    WavFile,
+}
+
+// How the output stream's format is chosen. `Resampled` opens the device in
+// its default format and converts every track to it; `BitPerfect` opens it at
+// the track's own rate and channel count when the device supports that, and
+// leaves the volume to the system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputMode {
+    Resampled,
+    BitPerfect,
+}
+
+// What the runtime plays on after the output device changes. `StickToDevice`
+// falls back to the default while the preferred device is gone and switches
+// back once it returns; `FollowSystemDefault` forgets the preferred device when
+// it disappears and moves along whenever the system default changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DevicePolicy {
+    StickToDevice,
+    FollowSystemDefault,
+}
+
+// How the offline backends pace the output callback: like a sound card, or as
+// fast as the decoder can keep up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OfflineClock {
+    Realtime,
+    Freewheel,
+}
+
+// Output format for the `Null` and `WavFile` backends.
+#[derive(Debug, Clone)]
+pub struct OfflineOutputConfig {
+    pub clock: OfflineClock,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub wav_path: Option<PathBuf>,
+}
+
+impl Default for OfflineOutputConfig {
+    fn default() -> Self {
+        Self {
+            clock: OfflineClock::Realtime,
+            sample_rate: 48_000,
+            channels: 2,
+            wav_path: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -32,14 +113,41 @@ impl Default for NetworkConfig {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamCacheConfig {
+    pub dir: PathBuf,
+    pub max_bytes: u64,
+}
+
+impl StreamCacheConfig {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_bytes: DEFAULT_STREAM_CACHE_BYTES,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AudioConfig {
     pub backend: OutputBackendKind,
     pub preferred_device: Option<String>,
+    pub device_policy: DevicePolicy,
+    pub output_mode: OutputMode,
+    pub offline_output: OfflineOutputConfig,
     pub snapshot_hz: u32,
+    pub analysis_hz: u32,
+    pub spectrum_bins: usize,
     pub volume: f32,
     pub resample_quality: ResampleQualityPreset,
+    pub channel_mix: ChannelMixMode,
     pub network: NetworkConfig,
+    pub stream_cache: Option<StreamCacheConfig>,
+    pub crossfade_ms: u64,
+    pub manual_fade_ms: u64,
+    pub rebuffer_ms: u64,
+    pub normalization: NormalizationMode,
+    pub target_lufs: f32,
 }
 
 impl Default for AudioConfig {
@@ -47,10 +155,22 @@ impl Default for AudioConfig {
         Self {
             backend: OutputBackendKind::PlatformDefault,
             preferred_device: None,
+            device_policy: DevicePolicy::StickToDevice,
+            output_mode: OutputMode::Resampled,
+            offline_output: OfflineOutputConfig::default(),
             snapshot_hz: 30,
+            analysis_hz: 30,
+            spectrum_bins: 64,
             volume: 0.7,
             resample_quality: ResampleQualityPreset::Balanced,
+            channel_mix: ChannelMixMode::Downmix,
             network: NetworkConfig::default(),
+            stream_cache: None,
+            crossfade_ms: 0,
+            manual_fade_ms: 0,
+            rebuffer_ms: 1_000,
+            normalization: NormalizationMode::Off,
+            target_lufs: -14.0,
         }
     }
 }
@@ -59,10 +179,22 @@ impl Default for AudioConfig {
 pub struct RuntimeConfigPatch {
     pub backend: Option<OutputBackendKind>,
     pub preferred_device: Option<Option<String>>,
+    pub device_policy: Option<DevicePolicy>,
+    pub output_mode: Option<OutputMode>,
+    pub offline_output: Option<OfflineOutputConfig>,
     pub snapshot_hz: Option<u32>,
+    pub analysis_hz: Option<u32>,
+    pub spectrum_bins: Option<usize>,
     pub volume: Option<f32>,
     pub resample_quality: Option<ResampleQualityPreset>,
+    pub channel_mix: Option<ChannelMixMode>,
     pub network: Option<NetworkConfig>,
+    pub stream_cache: Option<Option<StreamCacheConfig>>,
+    pub crossfade_ms: Option<u64>,
+    pub manual_fade_ms: Option<u64>,
+    pub rebuffer_ms: Option<u64>,
+    pub normalization: Option<NormalizationMode>,
+    pub target_lufs: Option<f32>,
 }
 
 impl AudioConfig {
@@ -73,17 +205,53 @@ impl AudioConfig {
         if let Some(device) = patch.preferred_device {
             self.preferred_device = device;
         }
+        if let Some(policy) = patch.device_policy {
+            self.device_policy = policy;
+        }
+        if let Some(output_mode) = patch.output_mode {
+            self.output_mode = output_mode;
+        }
+        if let Some(offline_output) = patch.offline_output {
+            self.offline_output = offline_output;
+        }
         if let Some(snapshot_hz) = patch.snapshot_hz {
             self.snapshot_hz = snapshot_hz.max(1);
         }
+        if let Some(analysis_hz) = patch.analysis_hz {
+            self.analysis_hz = analysis_hz.min(MAX_ANALYSIS_HZ);
+        }
+        if let Some(spectrum_bins) = patch.spectrum_bins {
+            self.spectrum_bins = spectrum_bins.min(MAX_SPECTRUM_BINS);
+        }
         if let Some(volume) = patch.volume {
             self.volume = volume.clamp(0.0, 1.0);
         }
         if let Some(quality) = patch.resample_quality {
             self.resample_quality = quality;
         }
+        if let Some(channel_mix) = patch.channel_mix {
+            self.channel_mix = channel_mix;
+        }
         if let Some(network) = patch.network {
             self.network = network;
         }
+        if let Some(stream_cache) = patch.stream_cache {
+            self.stream_cache = stream_cache;
+        }
+        if let Some(crossfade_ms) = patch.crossfade_ms {
+            self.crossfade_ms = crossfade_ms.min(MAX_CROSSFADE_MS);
+        }
+        if let Some(manual_fade_ms) = patch.manual_fade_ms {
+            self.manual_fade_ms = manual_fade_ms.min(MAX_MANUAL_FADE_MS);
+        }
+        if let Some(rebuffer_ms) = patch.rebuffer_ms {
+            self.rebuffer_ms = rebuffer_ms.min(MAX_REBUFFER_MS);
+        }
+        if let Some(normalization) = patch.normalization {
+            self.normalization = normalization;
+        }
+        if let Some(target_lufs) = patch.target_lufs {
+            self.target_lufs = target_lufs.clamp(MIN_TARGET_LUFS, MAX_TARGET_LUFS);
+        }
     }
 }
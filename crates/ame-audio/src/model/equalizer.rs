@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+pub const EQ_BAND_COUNT: usize = 10;
+pub const MAX_EQ_GAIN_DB: f32 = 12.0;
+pub const EQ_BAND_FREQUENCIES: [f32; EQ_BAND_COUNT] = [
+    31.0, 62.0, 125.0, 250.0, 500.0, 1_000.0, 2_000.0, 4_000.0, 8_000.0, 16_000.0,
+];
+const DEFAULT_BAND_Q: f32 = 1.41;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum EqualizerPreset {
+    #[default]
+    Flat,
+    BassBoost,
+    TrebleBoost,
+    Vocal,
+    Rock,
+    Pop,
+    Jazz,
+    Classical,
+    Electronic,
+}
+
+impl EqualizerPreset {
+    pub const fn variants() -> [Self; 9] {
+        [
+            Self::Flat,
+            Self::BassBoost,
+            Self::TrebleBoost,
+            Self::Vocal,
+            Self::Rock,
+            Self::Pop,
+            Self::Jazz,
+            Self::Classical,
+            Self::Electronic,
+        ]
+    }
+
+    pub const fn gains_db(self) -> [f32; EQ_BAND_COUNT] {
+        match self {
+            Self::Flat => [0.0; EQ_BAND_COUNT],
+            Self::BassBoost => [6.0, 5.0, 4.0, 2.5, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+            Self::TrebleBoost => [0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 2.5, 4.0, 5.0, 6.0],
+            Self::Vocal => [-2.0, -2.0, -1.0, 1.0, 3.0, 3.5, 3.0, 1.5, 0.0, -1.0],
+            Self::Rock => [4.5, 3.5, 2.0, 0.5, -1.0, -0.5, 1.0, 2.5, 3.5, 4.0],
+            Self::Pop => [-1.0, 0.5, 2.0, 3.0, 3.5, 2.5, 1.0, 0.0, -0.5, -1.0],
+            Self::Jazz => [3.0, 2.0, 1.0, 1.5, -1.0, -1.0, 0.0, 1.0, 2.0, 3.0],
+            Self::Classical => [4.0, 3.0, 2.0, 1.0, -1.0, -1.0, 0.0, 2.0, 3.0, 3.5],
+            Self::Electronic => [5.0, 4.0, 1.5, 0.0, -1.5, 1.0, 0.5, 1.0, 4.0, 5.0],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EqBand {
+    pub frequency_hz: f32,
+    pub gain_db: f32,
+    pub q: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EqualizerSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub preset: Option<EqualizerPreset>,
+    #[serde(default)]
+    pub preamp_db: f32,
+    pub bands: [EqBand; EQ_BAND_COUNT],
+}
+
+impl Default for EqualizerSettings {
+    fn default() -> Self {
+        Self::from_preset(EqualizerPreset::Flat)
+    }
+}
+
+impl EqualizerSettings {
+    pub fn from_preset(preset: EqualizerPreset) -> Self {
+        let gains = preset.gains_db();
+        Self {
+            enabled: preset != EqualizerPreset::Flat,
+            preset: Some(preset),
+            preamp_db: 0.0,
+            bands: std::array::from_fn(|index| EqBand {
+                frequency_hz: EQ_BAND_FREQUENCIES[index],
+                gain_db: gains[index],
+                q: DEFAULT_BAND_Q,
+            }),
+        }
+    }
+
+    pub fn set_band_gain(&mut self, index: usize, gain_db: f32) {
+        if let Some(band) = self.bands.get_mut(index) {
+            band.gain_db = gain_db;
+            self.preset = None;
+        }
+    }
+
+    pub fn clamped(mut self) -> Self {
+        self.preamp_db = clamp_gain(self.preamp_db);
+        for band in &mut self.bands {
+            band.frequency_hz = band.frequency_hz.clamp(20.0, 20_000.0);
+            band.gain_db = clamp_gain(band.gain_db);
+            band.q = if band.q.is_finite() {
+                band.q.clamp(0.1, 10.0)
+            } else {
+                DEFAULT_BAND_Q
+            };
+        }
+        self
+    }
+}
+
+fn clamp_gain(gain_db: f32) -> f32 {
+    if gain_db.is_finite() {
+        gain_db.clamp(-MAX_EQ_GAIN_DB, MAX_EQ_GAIN_DB)
+    } else {
+        0.0
+    }
+}
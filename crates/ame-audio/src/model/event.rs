@@ -1,4 +1,6 @@
-use crate::{AudioError, EngineState, OutputBackendKind};
+use std::sync::Arc;
+
+use crate::{AudioDevice, AudioError, EngineState, OutputBackendKind, SourceSpec, TrackMetadata};
 
 #[derive(Debug, Clone)]
 pub enum AudioEvent {
@@ -6,7 +8,9 @@ pub enum AudioEvent {
         from: EngineState,
         to: EngineState,
     },
-    TrackEnded,
+    TrackEnded {
+        next: Option<SourceSpec>,
+    },
     DeviceChanged {
         backend: OutputBackendKind,
         device: Option<String>,
@@ -15,5 +19,22 @@ pub enum AudioEvent {
         backend: OutputBackendKind,
         device: Option<String>,
     },
+    // Output devices of the current backend, whenever the list or the system
+    // default changes.
+    DevicesChanged(Vec<AudioDevice>),
+    // Periodic report for network sources; `total_bytes` is `None` when the
+    // server did not send a length.
+    BufferHealth {
+        buffered_ms: u64,
+        downloaded_bytes: u64,
+        total_bytes: Option<u64>,
+    },
+    SleepTimerFired,
+    // Tags and embedded artwork of a track, once its decoder has opened it.
+    // Sent only for files that carry any.
+    MetadataReady {
+        source: SourceSpec,
+        metadata: Arc<TrackMetadata>,
+    },
     Error(AudioError),
 }
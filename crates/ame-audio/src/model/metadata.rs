@@ -0,0 +1,24 @@
+// Embedded picture, usually the front cover.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Artwork {
+    pub media_type: String,
+    pub data: Vec<u8>,
+}
+
+// What the file itself says about the track. Every field is optional; plenty
+// of files carry no tags at all.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrackMetadata {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub track_number: Option<u32>,
+    pub lyrics: Option<String>,
+    pub artwork: Option<Artwork>,
+}
+
+impl TrackMetadata {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
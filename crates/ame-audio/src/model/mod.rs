@@ -1,5 +1,8 @@
+pub mod analysis;
 pub mod command;
 pub mod config;
+pub mod equalizer;
 pub mod event;
+pub mod metadata;
 pub mod snapshot;
 pub mod state;
@@ -1,5 +1,43 @@
 use crate::{EngineState, OutputBackendKind, SourceSpec};
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackFormat {
+    pub codec: String,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub bit_depth: Option<u32>,
+    pub bitrate_kbps: Option<u32>,
+}
+
+impl TrackFormat {
+    // Short label for quality badges, e.g. "FLAC 24/96" or "MP3 320k".
+    pub fn badge(&self) -> String {
+        match (self.bit_depth, self.bitrate_kbps) {
+            (Some(bits), _) => format!("{} {bits}/{}", self.codec, khz(self.sample_rate)),
+            (None, Some(kbps)) => format!("{} {kbps}k", self.codec),
+            (None, None) => self.codec.clone(),
+        }
+    }
+}
+
+fn khz(sample_rate: u32) -> String {
+    if sample_rate.is_multiple_of(1000) {
+        (sample_rate / 1000).to_string()
+    } else {
+        format!("{:.1}", sample_rate as f32 / 1000.0)
+    }
+}
+
+// What the output stream was actually opened with. `bit_perfect` is set when
+// the current track reaches it without resampling or software volume.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub bit_depth: u32,
+    pub bit_perfect: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct AudioSnapshot {
     pub state: EngineState,
@@ -7,9 +45,15 @@ pub struct AudioSnapshot {
     pub position_ms: u64,
     pub duration_ms: u64,
     pub volume: f32,
+    pub playback_rate: f32,
     pub backend: OutputBackendKind,
     pub device: Option<String>,
     pub source: Option<SourceSpec>,
+    pub normalization_gain_db: f32,
+    pub format: Option<TrackFormat>,
+    pub output_format: Option<OutputFormat>,
+    pub sleep_timer_remaining_ms: Option<u64>,
+    pub loop_region: Option<(u64, u64)>,
 }
 
 impl Default for AudioSnapshot {
@@ -20,9 +64,15 @@ impl Default for AudioSnapshot {
             position_ms: 0,
             duration_ms: 0,
             volume: 0.7,
+            playback_rate: 1.0,
             backend: OutputBackendKind::PlatformDefault,
             device: None,
             source: None,
+            normalization_gain_db: 0.0,
+            format: None,
+            output_format: None,
+            sleep_timer_remaining_ms: None,
+            loop_region: None,
         }
     }
 }
@@ -4,6 +4,7 @@ pub enum EngineState {
     Loading,
     Ready,
     Playing,
+    Buffering,
     Paused,
     Recovering,
     Stopped,
@@ -17,7 +18,8 @@ impl EngineState {
             (Idle, Loading | Stopped) => true,
             (Loading, Ready | Playing | Stopped | Error) => true,
             (Ready, Loading | Playing | Stopped | Recovering | Error | Paused) => true,
-            (Playing, Loading | Ready | Paused | Stopped | Recovering | Error) => true,
+            (Playing, Loading | Ready | Paused | Buffering | Stopped | Recovering | Error) => true,
+            (Buffering, Loading | Ready | Playing | Paused | Stopped | Recovering | Error) => true,
             (Paused, Loading | Playing | Stopped | Recovering | Error | Ready) => true,
             (Recovering, Loading | Playing | Paused | Stopped | Error | Ready) => true,
             (Stopped, Idle | Loading) => true,
@@ -37,5 +39,8 @@ mod tests {
         assert!(EngineState::Playing.can_transition_to(EngineState::Paused));
         assert!(EngineState::Paused.can_transition_to(EngineState::Playing));
         assert!(!EngineState::Idle.can_transition_to(EngineState::Playing));
+        assert!(EngineState::Playing.can_transition_to(EngineState::Buffering));
+        assert!(EngineState::Buffering.can_transition_to(EngineState::Playing));
+        assert!(!EngineState::Paused.can_transition_to(EngineState::Buffering));
     }
 }
@@ -0,0 +1,323 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ringbuf::traits::{Consumer, Observer, Split};
+
+use crate::Sample;
+use crate::analysis::{AudioAnalysis, ChannelLevel};
+use crate::backend::SampleRing;
+use crate::service::SubscriptionHub;
+
+pub type AnalysisProducer = <SampleRing as Split>::Prod;
+pub(crate) type AnalysisConsumer = <SampleRing as Split>::Cons;
+
+const FFT_SIZE: usize = 2048;
+const TAP_CAPACITY: usize = 64 * 1024;
+const DRAIN_INTERVAL: Duration = Duration::from_millis(10);
+const MIN_BAND_HZ: f32 = 20.0;
+
+pub(crate) fn analysis_ring() -> (AnalysisProducer, AnalysisConsumer) {
+    SampleRing::new(TAP_CAPACITY).split()
+}
+
+// Rate and band count are shared with the runtime so they can change without
+// rebuilding the output stream.
+pub(crate) struct AnalysisSettings {
+    hz: AtomicU32,
+    bins: AtomicU32,
+}
+
+impl AnalysisSettings {
+    pub fn new(hz: u32, bins: usize) -> Self {
+        let settings = Self {
+            hz: AtomicU32::new(0),
+            bins: AtomicU32::new(0),
+        };
+        settings.update(hz, bins);
+        settings
+    }
+
+    pub fn update(&self, hz: u32, bins: usize) {
+        self.hz.store(hz, Ordering::Relaxed);
+        self.bins.store(bins as u32, Ordering::Relaxed);
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        let hz = self.hz.load(Ordering::Relaxed);
+        (hz > 0).then(|| Duration::from_secs_f32(1.0 / hz as f32))
+    }
+
+    fn bins(&self) -> usize {
+        self.bins.load(Ordering::Relaxed) as usize
+    }
+}
+
+pub(crate) struct AnalyzerSpawnRequest {
+    pub consumer: AnalysisConsumer,
+    pub sample_rate: u32,
+    pub channels: usize,
+    pub settings: Arc<AnalysisSettings>,
+    pub hub: SubscriptionHub<AudioAnalysis>,
+    pub cancel: Arc<AtomicBool>,
+}
+
+pub(crate) fn spawn_analyzer(request: AnalyzerSpawnRequest) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let AnalyzerSpawnRequest {
+            mut consumer,
+            sample_rate,
+            channels,
+            settings,
+            hub,
+            cancel,
+        } = request;
+        let mut analyzer = Analyzer::new(sample_rate, channels);
+        let mut scratch = vec![0.0; TAP_CAPACITY];
+        let mut last_publish = Instant::now();
+
+        while !cancel.load(Ordering::Relaxed) {
+            let frames = consumer.occupied_len() / analyzer.channels;
+            let popped = consumer.pop_slice(&mut scratch[..frames * analyzer.channels]);
+            analyzer.push(&scratch[..popped]);
+
+            if let Some(interval) = settings.interval()
+                && last_publish.elapsed() >= interval
+            {
+                last_publish = Instant::now();
+                if hub.has_subscribers() {
+                    hub.publish(analyzer.take(settings.bins()));
+                }
+            }
+            thread::sleep(DRAIN_INTERVAL);
+        }
+    })
+}
+
+pub(crate) struct Analyzer {
+    sample_rate: u32,
+    channels: usize,
+    peaks: Vec<f32>,
+    sums: Vec<f64>,
+    frames: usize,
+    history: Vec<Sample>,
+    history_pos: usize,
+    window: Vec<f32>,
+    window_gain: f32,
+    fft: Fft,
+    spectrum: Vec<f32>,
+}
+
+impl Analyzer {
+    pub fn new(sample_rate: u32, channels: usize) -> Self {
+        let channels = channels.max(1);
+        let window = (0..FFT_SIZE)
+            .map(|index| 0.5 - 0.5 * (2.0 * PI * index as f32 / FFT_SIZE as f32).cos())
+            .collect::<Vec<_>>();
+        let window_gain = window.iter().sum::<f32>();
+        Self {
+            sample_rate: sample_rate.max(1),
+            channels,
+            peaks: vec![0.0; channels],
+            sums: vec![0.0; channels],
+            frames: 0,
+            history: vec![0.0; FFT_SIZE],
+            history_pos: 0,
+            window,
+            window_gain,
+            fft: Fft::new(FFT_SIZE),
+            spectrum: vec![0.0; FFT_SIZE / 2],
+        }
+    }
+
+    pub fn push(&mut self, samples: &[Sample]) {
+        for frame in samples.chunks_exact(self.channels) {
+            let mut mono = 0.0;
+            for (channel, sample) in frame.iter().enumerate() {
+                self.peaks[channel] = self.peaks[channel].max(sample.abs());
+                self.sums[channel] += (*sample as f64) * (*sample as f64);
+                mono += *sample;
+            }
+            self.history[self.history_pos] = mono / self.channels as f32;
+            self.history_pos = (self.history_pos + 1) % FFT_SIZE;
+            self.frames += 1;
+        }
+    }
+
+    // Returns the levels since the previous call and the spectrum of the most
+    // recent FFT window, then starts a new level period.
+    pub fn take(&mut self, bins: usize) -> AudioAnalysis {
+        let levels = self
+            .peaks
+            .iter()
+            .zip(&self.sums)
+            .map(|(peak, sum)| ChannelLevel {
+                peak: *peak,
+                rms: if self.frames == 0 {
+                    0.0
+                } else {
+                    (sum / self.frames as f64).sqrt() as f32
+                },
+            })
+            .collect();
+        let silent = self.frames == 0;
+        self.peaks.fill(0.0);
+        self.sums.fill(0.0);
+        self.frames = 0;
+
+        if silent {
+            self.history.fill(0.0);
+        }
+        self.compute_spectrum();
+
+        AudioAnalysis {
+            sample_rate: self.sample_rate,
+            levels,
+            spectrum: self.bands(bins),
+        }
+    }
+
+    fn compute_spectrum(&mut self) {
+        for index in 0..FFT_SIZE {
+            let sample = self.history[(self.history_pos + index) % FFT_SIZE];
+            self.fft.re[index] = sample * self.window[index];
+            self.fft.im[index] = 0.0;
+        }
+        self.fft.run();
+
+        let scale = 2.0 / self.window_gain;
+        for (bin, magnitude) in self.spectrum.iter_mut().enumerate() {
+            *magnitude = self.fft.re[bin].hypot(self.fft.im[bin]) * scale;
+        }
+    }
+
+    fn bands(&self, bins: usize) -> Vec<f32> {
+        if bins == 0 {
+            return Vec::new();
+        }
+
+        let nyquist = self.sample_rate as f32 / 2.0;
+        let bin_hz = self.sample_rate as f32 / FFT_SIZE as f32;
+        let ratio = (nyquist / MIN_BAND_HZ).max(1.0);
+        let edge = |band: usize| MIN_BAND_HZ * ratio.powf(band as f32 / bins as f32);
+
+        (0..bins)
+            .map(|band| {
+                let low = ((edge(band) / bin_hz) as usize).min(self.spectrum.len() - 1);
+                let high = ((edge(band + 1) / bin_hz) as usize).clamp(low + 1, self.spectrum.len());
+                self.spectrum[low..high]
+                    .iter()
+                    .fold(0.0f32, |peak, magnitude| peak.max(*magnitude))
+            })
+            .collect()
+    }
+}
+
+// Radix-2 in-place FFT with precomputed twiddles; only needs to be fast enough
+// for a few dozen frames per second.
+struct Fft {
+    re: Vec<f32>,
+    im: Vec<f32>,
+    cos: Vec<f32>,
+    sin: Vec<f32>,
+}
+
+impl Fft {
+    fn new(size: usize) -> Self {
+        debug_assert!(size.is_power_of_two());
+        let angle = |index: usize| -2.0 * PI * index as f32 / size as f32;
+        Self {
+            re: vec![0.0; size],
+            im: vec![0.0; size],
+            cos: (0..size / 2).map(|index| angle(index).cos()).collect(),
+            sin: (0..size / 2).map(|index| angle(index).sin()).collect(),
+        }
+    }
+
+    fn run(&mut self) {
+        let size = self.re.len();
+        let bits = size.trailing_zeros();
+        for index in 0..size {
+            let reversed = index.reverse_bits() >> (usize::BITS - bits);
+            if reversed > index {
+                self.re.swap(index, reversed);
+                self.im.swap(index, reversed);
+            }
+        }
+
+        let mut len = 2;
+        while len <= size {
+            let half = len / 2;
+            let stride = size / len;
+            for start in (0..size).step_by(len) {
+                for offset in 0..half {
+                    let (cos, sin) = (self.cos[offset * stride], self.sin[offset * stride]);
+                    let (a, b) = (start + offset, start + offset + half);
+                    let re = self.re[b] * cos - self.im[b] * sin;
+                    let im = self.re[b] * sin + self.im[b] * cos;
+                    self.re[b] = self.re[a] - re;
+                    self.im[b] = self.im[a] - im;
+                    self.re[a] += re;
+                    self.im[a] += im;
+                }
+            }
+            len *= 2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Analyzer, FFT_SIZE};
+
+    #[test]
+    fn levels_report_peak_and_rms_per_channel() {
+        let mut analyzer = Analyzer::new(48_000, 2);
+        let samples = (0..4800).flat_map(|_| [0.5, -0.25]).collect::<Vec<_>>();
+        analyzer.push(&samples);
+        let analysis = analyzer.take(0);
+        assert_eq!(analysis.levels.len(), 2);
+        assert!((analysis.levels[0].peak - 0.5).abs() < 1e-6);
+        assert!((analysis.levels[0].rms - 0.5).abs() < 1e-6);
+        assert!((analysis.levels[1].rms - 0.25).abs() < 1e-6);
+
+        let idle = analyzer.take(0);
+        assert_eq!(idle.levels[0].peak, 0.0);
+    }
+
+    #[test]
+    fn spectrum_peaks_in_the_sine_band() {
+        let sample_rate = 48_000;
+        let mut analyzer = Analyzer::new(sample_rate, 1);
+        // Pick a frequency centred on an FFT bin so leakage stays small.
+        let frequency = 64.0 * sample_rate as f32 / FFT_SIZE as f32;
+        let samples = (0..FFT_SIZE * 2)
+            .map(|index| {
+                (2.0 * std::f32::consts::PI * frequency * index as f32 / sample_rate as f32).sin()
+            })
+            .collect::<Vec<_>>();
+        analyzer.push(&samples);
+
+        let analysis = analyzer.take(32);
+        assert_eq!(analysis.spectrum.len(), 32);
+        let (loudest, magnitude) =
+            analysis
+                .spectrum
+                .iter()
+                .enumerate()
+                .fold((0, 0.0f32), |best, (band, value)| {
+                    if *value > best.1 {
+                        (band, *value)
+                    } else {
+                        best
+                    }
+                });
+        assert!((magnitude - 1.0).abs() < 0.05, "magnitude = {magnitude}");
+
+        let nyquist = sample_rate as f32 / 2.0;
+        let position = (frequency / 20.0).ln() / (nyquist / 20.0).ln();
+        assert_eq!(loudest, (position * 32.0) as usize);
+    }
+}
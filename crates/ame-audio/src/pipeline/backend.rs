@@ -1,20 +1,26 @@
+use std::f32::consts::FRAC_PI_2;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::sync::mpsc::Sender;
 
 #[cfg(target_os = "windows")]
 use cpal::HostId;
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{Device, SampleFormat, Stream, StreamConfig};
-use ringbuf::traits::{Consumer, Split};
+use cpal::{Device, SampleFormat, Stream, StreamConfig, SupportedStreamConfig};
+use ringbuf::traits::{Consumer, Observer, Producer, Split};
+use tracing::debug;
 
+use crate::analyzer::AnalysisProducer;
+use crate::config::OfflineOutputConfig;
 use crate::error::{AudioError, Result};
+use crate::offline::OfflineBackend;
+use crate::snapshot::TrackFormat;
 use crate::{OutputBackendKind, Sample};
 
 pub type SampleRing = ringbuf::HeapRb<Sample>;
 pub type RingConsumer = <SampleRing as Split>::Cons;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AudioDevice {
     pub id: String,
     pub name: String,
@@ -23,23 +29,64 @@ pub struct AudioDevice {
 pub struct OpenStreamRequest {
     pub stream_id: u64,
     pub preferred_device: Option<String>,
+    // Track a bit-perfect stream should match; the device default format is
+    // used when this is `None` or the device cannot play it.
+    pub native_format: Option<TrackFormat>,
     pub volume: f32,
+    pub fade_in_ms: u64,
     pub consumer: RingConsumer,
+    pub analysis: Option<AnalysisProducer>,
     pub event_tx: Sender<BackendNotification>,
     pub drain_state: Arc<PlaybackDrainState>,
 }
 
 #[derive(Debug, Clone)]
 pub enum BackendNotification {
-    StreamError { stream_id: u64, reason: String },
-    PlaybackDrained { stream_id: u64 },
+    StreamError {
+        stream_id: u64,
+        reason: String,
+    },
+    PlaybackDrained {
+        stream_id: u64,
+    },
+    TrackBoundary {
+        stream_id: u64,
+    },
+    DevicesChanged {
+        backend: OutputBackendKind,
+        devices: Vec<AudioDevice>,
+        default_device: Option<String>,
+    },
 }
 
-#[derive(Debug, Default)]
+const NO_TRACK_BOUNDARY: u64 = u64::MAX;
+
+#[derive(Debug)]
 pub struct PlaybackDrainState {
     pending_samples: AtomicUsize,
     decoder_finished: AtomicBool,
     drained_notified: AtomicBool,
+    queued_total: AtomicU64,
+    played_total: AtomicU64,
+    track_boundary: AtomicU64,
+    underruns: AtomicU64,
+    // Everything queued before this count is dropped unplayed.
+    discard_until: AtomicU64,
+}
+
+impl Default for PlaybackDrainState {
+    fn default() -> Self {
+        Self {
+            pending_samples: AtomicUsize::new(0),
+            decoder_finished: AtomicBool::new(false),
+            drained_notified: AtomicBool::new(false),
+            queued_total: AtomicU64::new(0),
+            played_total: AtomicU64::new(0),
+            track_boundary: AtomicU64::new(NO_TRACK_BOUNDARY),
+            underruns: AtomicU64::new(0),
+            discard_until: AtomicU64::new(0),
+        }
+    }
 }
 
 impl PlaybackDrainState {
@@ -48,6 +95,7 @@ impl PlaybackDrainState {
             return;
         }
 
+        self.queued_total.fetch_add(count as u64, Ordering::AcqRel);
         self.pending_samples.fetch_add(count, Ordering::AcqRel);
         self.drained_notified.store(false, Ordering::Release);
     }
@@ -56,6 +104,74 @@ impl PlaybackDrainState {
         self.decoder_finished.store(true, Ordering::Release);
     }
 
+    pub fn is_decoder_finished(&self) -> bool {
+        self.decoder_finished.load(Ordering::Acquire)
+    }
+
+    pub fn pending_samples(&self) -> usize {
+        self.pending_samples.load(Ordering::Acquire)
+    }
+
+    pub fn queued_total(&self) -> u64 {
+        self.queued_total.load(Ordering::Acquire)
+    }
+
+    pub fn played_total(&self) -> u64 {
+        self.played_total.load(Ordering::Acquire)
+    }
+
+    // Callbacks that ran dry while the decoder still had more to deliver.
+    pub fn underruns(&self) -> u64 {
+        self.underruns.load(Ordering::Acquire)
+    }
+
+    fn on_underrun(&self) {
+        if !self.decoder_finished.load(Ordering::Acquire) {
+            self.underruns.fetch_add(1, Ordering::AcqRel);
+        }
+    }
+
+    // Everything queued so far belongs to the previous track; the next queued
+    // sample is the first one of the following track.
+    pub fn mark_track_boundary(&self) {
+        let queued = self.queued_total.load(Ordering::Acquire);
+        self.track_boundary.store(queued, Ordering::Release);
+    }
+
+    // Has the output skip everything queued so far on its next callback, as if
+    // it had been played, and returns how many samples that is.
+    pub fn discard_queued(&self) -> u64 {
+        let queued = self.queued_total.load(Ordering::Acquire);
+        self.discard_until.store(queued, Ordering::Release);
+        queued.saturating_sub(self.played_total.load(Ordering::Acquire))
+    }
+
+    fn stale_samples(&self) -> usize {
+        self.discard_until
+            .load(Ordering::Acquire)
+            .saturating_sub(self.played_total.load(Ordering::Acquire)) as usize
+    }
+
+    pub fn has_pending_boundary(&self) -> bool {
+        self.track_boundary.load(Ordering::Acquire) != NO_TRACK_BOUNDARY
+    }
+
+    pub fn take_track_boundary(&self) -> bool {
+        let boundary = self.track_boundary.load(Ordering::Acquire);
+        if boundary == NO_TRACK_BOUNDARY || self.played_total.load(Ordering::Acquire) < boundary {
+            return false;
+        }
+
+        self.track_boundary
+            .compare_exchange(
+                boundary,
+                NO_TRACK_BOUNDARY,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .is_ok()
+    }
+
     pub fn on_output_callback(&self, played_samples: usize) -> bool {
         if played_samples > 0 {
             self.consume_samples(played_samples);
@@ -74,6 +190,7 @@ impl PlaybackDrainState {
     }
 
     fn consume_samples(&self, count: usize) {
+        self.played_total.fetch_add(count as u64, Ordering::AcqRel);
         let mut current = self.pending_samples.load(Ordering::Acquire);
         loop {
             let next = current.saturating_sub(count);
@@ -90,12 +207,85 @@ impl PlaybackDrainState {
     }
 }
 
+pub fn equal_power(level: f32) -> f32 {
+    (level.clamp(0.0, 1.0) * FRAC_PI_2).sin()
+}
+
+// Shared between the runtime and the output callback. The callback owns the
+// ramp; the runtime only moves the target or forces the level while paused.
+#[derive(Debug)]
+pub struct FadeControl {
+    level: AtomicU32,
+    target: AtomicU32,
+    step: AtomicU32,
+}
+
+impl FadeControl {
+    pub fn new(level: f32) -> Self {
+        Self {
+            level: AtomicU32::new(level.to_bits()),
+            target: AtomicU32::new(level.to_bits()),
+            step: AtomicU32::new(1.0f32.to_bits()),
+        }
+    }
+
+    pub fn fade_to(&self, target: f32, duration_ms: u64, sample_rate: u32) {
+        let frames = duration_ms * sample_rate as u64 / 1000;
+        let step = if frames == 0 {
+            1.0
+        } else {
+            1.0 / frames as f32
+        };
+        self.step.store(step.to_bits(), Ordering::Relaxed);
+        self.target
+            .store(target.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn force_level(&self, level: f32) {
+        let level = level.clamp(0.0, 1.0).to_bits();
+        self.level.store(level, Ordering::Relaxed);
+        self.target.store(level, Ordering::Relaxed);
+    }
+
+    fn begin(&self) -> FadeRamp {
+        FadeRamp {
+            level: f32::from_bits(self.level.load(Ordering::Relaxed)),
+            target: f32::from_bits(self.target.load(Ordering::Relaxed)),
+            step: f32::from_bits(self.step.load(Ordering::Relaxed)),
+        }
+    }
+
+    fn finish(&self, ramp: FadeRamp) {
+        self.level.store(ramp.level.to_bits(), Ordering::Relaxed);
+    }
+}
+
+struct FadeRamp {
+    level: f32,
+    target: f32,
+    step: f32,
+}
+
+impl FadeRamp {
+    fn next_gain(&mut self) -> f32 {
+        if self.level < self.target {
+            self.level = (self.level + self.step).min(self.target);
+        } else if self.level > self.target {
+            self.level = (self.level - self.step).max(self.target);
+        }
+        equal_power(self.level)
+    }
+}
+
 pub trait OutputSession: Send {
     fn play(&self) -> Result<()>;
     fn pause(&self) -> Result<()>;
     fn set_volume(&self, volume: f32);
+    fn fade_to(&self, level: f32, duration_ms: u64);
+    fn force_fade_level(&self, level: f32);
     fn sample_rate(&self) -> u32;
     fn channels(&self) -> u16;
+    fn bit_depth(&self) -> u32;
     fn device_name(&self) -> String;
 }
 
@@ -195,44 +385,25 @@ impl OutputBackend for CpalBackend {
             reason: err.to_string(),
         })?;
 
-        let supported = device.default_output_config()?;
+        let supported = match request
+            .native_format
+            .as_ref()
+            .and_then(|format| native_config(&device, format))
+        {
+            Some(supported) => supported,
+            None => device.default_output_config()?,
+        };
         let sample_format = supported.sample_format();
         let config: StreamConfig = supported.into();
 
-        let volume = Arc::new(AtomicU32::new(request.volume.clamp(0.0, 1.0).to_bits()));
-        let volume_clone = Arc::clone(&volume);
-        let tx = request.event_tx;
-        let stream_id = request.stream_id;
-        let drain_state = request.drain_state;
+        let callback = OutputCallbackContext::new(request, config.sample_rate);
+        let volume = Arc::clone(&callback.volume);
+        let fade = Arc::clone(&callback.fade);
 
         let stream = match sample_format {
-            SampleFormat::F32 => build_output_stream::<f32>(
-                &device,
-                &config,
-                request.consumer,
-                volume_clone,
-                tx,
-                stream_id,
-                Arc::clone(&drain_state),
-            )?,
-            SampleFormat::I16 => build_output_stream::<i16>(
-                &device,
-                &config,
-                request.consumer,
-                volume_clone,
-                tx,
-                stream_id,
-                Arc::clone(&drain_state),
-            )?,
-            SampleFormat::U16 => build_output_stream::<u16>(
-                &device,
-                &config,
-                request.consumer,
-                volume_clone,
-                tx,
-                stream_id,
-                drain_state,
-            )?,
+            SampleFormat::F32 => build_output_stream::<f32>(&device, &config, callback)?,
+            SampleFormat::I16 => build_output_stream::<i16>(&device, &config, callback)?,
+            SampleFormat::U16 => build_output_stream::<u16>(&device, &config, callback)?,
             _ => {
                 return Err(AudioError::OutputInitFailed {
                     reason: format!("unsupported output sample format: {sample_format:?}"),
@@ -243,44 +414,145 @@ impl OutputBackend for CpalBackend {
         Ok(Box::new(CpalOutputSession {
             stream,
             volume,
+            fade,
             sample_rate: config.sample_rate,
             channels: config.channels,
+            bit_depth: sample_format.bits_per_sample(),
             device_name,
         }))
     }
 }
 
+// A supported config at the track's own rate and channel count. 16-bit tracks
+// prefer an `I16` stream; anything else goes out as `F32`, which holds up to 24
+// bits exactly.
+fn native_config(device: &Device, format: &TrackFormat) -> Option<SupportedStreamConfig> {
+    let rank = |sample_format: SampleFormat| match sample_format {
+        SampleFormat::I16 if format.bit_depth.is_some_and(|bits| bits <= 16) => 2,
+        SampleFormat::F32 => 1,
+        _ => 0,
+    };
+    let supported = device
+        .supported_output_configs()
+        .ok()?
+        .filter(|range| {
+            range.channels() == format.channels
+                && (range.min_sample_rate()..=range.max_sample_rate()).contains(&format.sample_rate)
+                && matches!(
+                    range.sample_format(),
+                    SampleFormat::F32 | SampleFormat::I16 | SampleFormat::U16
+                )
+        })
+        .max_by_key(|range| rank(range.sample_format()))
+        .map(|range| range.with_sample_rate(format.sample_rate));
+    if supported.is_none() {
+        debug!(
+            "no native output config for {} Hz x{}, resampling",
+            format.sample_rate, format.channels
+        );
+    }
+    supported
+}
+
+pub(crate) struct OutputCallbackContext {
+    pub consumer: RingConsumer,
+    pub analysis: Option<AnalysisProducer>,
+    pub volume: Arc<AtomicU32>,
+    pub fade: Arc<FadeControl>,
+    pub event_tx: Sender<BackendNotification>,
+    pub stream_id: u64,
+    pub drain_state: Arc<PlaybackDrainState>,
+}
+
+impl OutputCallbackContext {
+    pub(crate) fn new(request: OpenStreamRequest, sample_rate: u32) -> Self {
+        let fade = FadeControl::new(1.0);
+        if request.fade_in_ms > 0 {
+            fade.force_level(0.0);
+            fade.fade_to(1.0, request.fade_in_ms, sample_rate);
+        }
+        Self {
+            consumer: request.consumer,
+            analysis: request.analysis,
+            volume: Arc::new(AtomicU32::new(request.volume.clamp(0.0, 1.0).to_bits())),
+            fade: Arc::new(fade),
+            event_tx: request.event_tx,
+            stream_id: request.stream_id,
+            drain_state: request.drain_state,
+        }
+    }
+
+    // One output callback's worth of work: pull samples from the ring, apply
+    // volume and fades, feed the analyzer tap and report boundaries.
+    pub(crate) fn render<T>(&mut self, data: &mut [T], channels: usize)
+    where
+        T: cpal::SizedSample + cpal::FromSample<Sample>,
+    {
+        let stale = self.drain_state.stale_samples();
+        if stale > 0 {
+            let skipped = self.consumer.skip(stale);
+            self.drain_state.consume_samples(skipped);
+        }
+
+        let volume = f32::from_bits(self.volume.load(Ordering::Relaxed));
+        let mut ramp = self.fade.begin();
+        let mut played_samples = 0;
+        for frame in data.chunks_mut(channels) {
+            let gain = volume * ramp.next_gain();
+            // Whole frames are skipped when the analyzer falls behind so its
+            // channels stay aligned.
+            let mut tap = self
+                .analysis
+                .as_mut()
+                .filter(|tap| tap.vacant_len() >= frame.len());
+            for output in frame.iter_mut() {
+                let value = match self.consumer.try_pop() {
+                    Some(sample) => {
+                        played_samples += 1;
+                        sample * gain
+                    }
+                    None => 0.0,
+                };
+                *output = T::from_sample(value);
+                if let Some(tap) = tap.as_mut() {
+                    let _ = tap.try_push(value);
+                }
+            }
+        }
+        self.fade.finish(ramp);
+
+        let stream_id = self.stream_id;
+        if played_samples < data.len() {
+            self.drain_state.on_underrun();
+        }
+        let drained = self.drain_state.on_output_callback(played_samples);
+        if self.drain_state.take_track_boundary() {
+            let _ = self
+                .event_tx
+                .send(BackendNotification::TrackBoundary { stream_id });
+        }
+        if drained {
+            let _ = self
+                .event_tx
+                .send(BackendNotification::PlaybackDrained { stream_id });
+        }
+    }
+}
+
 fn build_output_stream<T>(
     device: &Device,
     config: &StreamConfig,
-    mut consumer: RingConsumer,
-    volume: Arc<AtomicU32>,
-    event_tx: Sender<BackendNotification>,
-    stream_id: u64,
-    drain_state: Arc<PlaybackDrainState>,
+    mut context: OutputCallbackContext,
 ) -> Result<Stream>
 where
     T: cpal::SizedSample + cpal::FromSample<Sample>,
 {
-    let drain_event_tx = event_tx.clone();
+    let event_tx = context.event_tx.clone();
+    let stream_id = context.stream_id;
+    let channels = config.channels.max(1) as usize;
     let stream = device.build_output_stream(
         config,
-        move |data: &mut [T], _| {
-            let volume = f32::from_bits(volume.load(Ordering::Relaxed));
-            let mut played_samples = 0;
-            for output in data.iter_mut() {
-                if let Some(sample) = consumer.try_pop() {
-                    played_samples += 1;
-                    *output = T::from_sample(sample * volume);
-                } else {
-                    *output = T::from_sample(0.0);
-                }
-            }
-
-            if drain_state.on_output_callback(played_samples) {
-                let _ = drain_event_tx.send(BackendNotification::PlaybackDrained { stream_id });
-            }
-        },
+        move |data: &mut [T], _| context.render(data, channels),
         move |err| {
             let _ = event_tx.send(BackendNotification::StreamError {
                 stream_id,
@@ -296,8 +568,10 @@ where
 struct CpalOutputSession {
     stream: Stream,
     volume: Arc<AtomicU32>,
+    fade: Arc<FadeControl>,
     sample_rate: u32,
     channels: u16,
+    bit_depth: u32,
     device_name: String,
 }
 
@@ -317,6 +591,14 @@ impl OutputSession for CpalOutputSession {
             .store(volume.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
     }
 
+    fn fade_to(&self, level: f32, duration_ms: u64) {
+        self.fade.fade_to(level, duration_ms, self.sample_rate);
+    }
+
+    fn force_fade_level(&self, level: f32) {
+        self.fade.force_level(level);
+    }
+
     fn sample_rate(&self) -> u32 {
         self.sample_rate
     }
@@ -325,14 +607,24 @@ impl OutputSession for CpalOutputSession {
         self.channels
     }
 
+    fn bit_depth(&self) -> u32 {
+        self.bit_depth
+    }
+
     fn device_name(&self) -> String {
         self.device_name.clone()
     }
 }
 
-pub fn backend_for_kind(kind: OutputBackendKind) -> Result<Box<dyn OutputBackend>> {
+pub fn backend_for_kind(
+    kind: OutputBackendKind,
+    offline: &OfflineOutputConfig,
+) -> Result<Box<dyn OutputBackend>> {
     match kind {
         OutputBackendKind::PlatformDefault => Ok(Box::new(CpalBackend::new(kind))),
+        OutputBackendKind::Null | OutputBackendKind::WavFile => {
+            Ok(Box::new(OfflineBackend::new(kind, offline.clone())))
+        }
         OutputBackendKind::Wasapi => {
             #[cfg(target_os = "windows")]
             {
@@ -359,6 +651,9 @@ pub fn backend_for_kind(kind: OutputBackendKind) -> Result<Box<dyn OutputBackend
 fn host_for_backend(kind: OutputBackendKind) -> Result<cpal::Host> {
     match kind {
         OutputBackendKind::PlatformDefault => Ok(cpal::default_host()),
+        OutputBackendKind::Null | OutputBackendKind::WavFile => {
+            Err(AudioError::BackendUnavailable { backend: kind })
+        }
         OutputBackendKind::Wasapi => {
             #[cfg(target_os = "windows")]
             {
@@ -393,7 +688,7 @@ fn device_name(device: &Device) -> std::result::Result<String, cpal::DeviceNameE
 
 #[cfg(test)]
 mod tests {
-    use super::PlaybackDrainState;
+    use super::{FadeControl, PlaybackDrainState, equal_power};
 
     #[test]
     fn drain_notification_waits_for_empty_callback_after_last_samples() {
@@ -417,4 +712,45 @@ mod tests {
         assert!(state.on_output_callback(0));
         assert!(!state.on_output_callback(0));
     }
+
+    #[test]
+    fn track_boundary_is_reported_after_previous_samples_play() {
+        let state = PlaybackDrainState::default();
+        state.on_samples_queued(8);
+        state.mark_track_boundary();
+        state.on_samples_queued(8);
+
+        assert!(state.has_pending_boundary());
+        assert!(!state.on_output_callback(6));
+        assert!(!state.take_track_boundary());
+
+        assert!(!state.on_output_callback(4));
+        assert!(state.take_track_boundary());
+        assert!(!state.take_track_boundary());
+        assert!(!state.has_pending_boundary());
+    }
+
+    #[test]
+    fn equal_power_crossfade_keeps_constant_power() {
+        for step in 0..=10 {
+            let progress = step as f32 / 10.0;
+            let power = equal_power(1.0 - progress).powi(2) + equal_power(progress).powi(2);
+            assert!((power - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn fade_ramp_reaches_target_after_duration() {
+        let fade = FadeControl::new(1.0);
+        fade.fade_to(0.0, 8, 1000);
+
+        let mut ramp = fade.begin();
+        for _ in 0..7 {
+            assert!(ramp.next_gain() > 0.0);
+        }
+        assert_eq!(ramp.next_gain(), 0.0);
+        fade.finish(ramp);
+
+        assert_eq!(fade.begin().level, 0.0);
+    }
 }
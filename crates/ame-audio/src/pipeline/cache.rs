@@ -0,0 +1,658 @@
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use symphonia::core::io::MediaSource;
+use tracing::warn;
+
+use crate::config::StreamCacheConfig;
+use crate::error::{AudioError, Result};
+use crate::source::{OpenedSource, SourceFactory, SourceSpec, StreamCacheKey};
+
+const COMPLETE_EXTENSION: &str = "audio";
+const PARTIAL_EXTENSION: &str = "part";
+const INDEX_EXTENSION: &str = "ranges";
+
+// Wraps another factory and keeps `SourceSpec::CachedStream` bytes on disk.
+// Partially fetched streams live in a sparse `.part` file next to a `.ranges`
+// index; once every byte has been seen the file is renamed to `.audio` and is
+// served without touching the network.
+pub struct CachingSourceFactory {
+    inner: Arc<dyn SourceFactory>,
+    store: Arc<CacheStore>,
+}
+
+impl CachingSourceFactory {
+    pub fn new(inner: Arc<dyn SourceFactory>, config: StreamCacheConfig) -> Result<Self> {
+        fs::create_dir_all(&config.dir).map_err(|err| AudioError::ConfigInvalid {
+            reason: format!("stream cache dir {} ({err})", config.dir.display()),
+        })?;
+        let store = Arc::new(CacheStore {
+            dir: config.dir,
+            max_bytes: config.max_bytes,
+            active: Mutex::new(HashSet::new()),
+        });
+        store.evict();
+        Ok(Self { inner, store })
+    }
+}
+
+impl SourceFactory for CachingSourceFactory {
+    fn open(&self, spec: &SourceSpec) -> Result<OpenedSource> {
+        let SourceSpec::CachedStream { url, key } = spec else {
+            return self.inner.open(spec);
+        };
+
+        let stem = cache_stem(key);
+        let complete = self.store.path(&stem, COMPLETE_EXTENSION);
+        if let Ok(file) = OpenOptions::new().read(true).write(true).open(&complete) {
+            let _ = file.set_modified(SystemTime::now());
+            return Ok(OpenedSource {
+                media_source: Box::new(file),
+                seekable: true,
+            });
+        }
+
+        let upstream_spec = SourceSpec::NetworkUrl(url.clone());
+        // One writer per entry: a second source for the same stem (a probe
+        // next to the decoder, say) would keep its own ranges and overwrite
+        // the index of the first, so it streams from the network instead.
+        let Some(claim) = CacheStore::claim(&self.store, &stem) else {
+            return self.inner.open(&upstream_spec);
+        };
+        let (len, ranges, upstream) = match self.store.read_index(&stem) {
+            Some((len, ranges)) => (len, ranges, None),
+            None => {
+                let upstream = self.inner.open(&upstream_spec)?;
+                let Some(len) = upstream.media_source.byte_len() else {
+                    // Without a length there is no way to tell when the file is
+                    // complete, so such streams are played uncached.
+                    return Ok(upstream);
+                };
+                (len, RangeSet::default(), Some(upstream))
+            }
+        };
+
+        let seekable = upstream.as_ref().is_none_or(|upstream| upstream.seekable);
+        let file = self.store.open_partial(&stem, len)?;
+        self.store.evict();
+        Ok(OpenedSource {
+            media_source: Box::new(CachedStreamSource {
+                store: Arc::clone(&self.store),
+                inner: Arc::clone(&self.inner),
+                upstream_spec,
+                stem,
+                claim: Some(claim),
+                file: Some(file),
+                len,
+                ranges,
+                position: 0,
+                upstream: upstream.map(|upstream| upstream.media_source),
+                upstream_position: 0,
+                seekable,
+                dirty: false,
+            }),
+            seekable,
+        })
+    }
+}
+
+fn cache_stem(key: &StreamCacheKey) -> String {
+    format!("{}-{}", key.track_id, key.bitrate)
+}
+
+struct CacheStore {
+    dir: PathBuf,
+    max_bytes: u64,
+    // Stems with a writer attached; eviction leaves these alone.
+    active: Mutex<HashSet<String>>,
+}
+
+impl CacheStore {
+    fn path(&self, stem: &str, extension: &str) -> PathBuf {
+        self.dir.join(format!("{stem}.{extension}"))
+    }
+
+    fn open_partial(&self, stem: &str, len: u64) -> Result<File> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(self.path(stem, PARTIAL_EXTENSION))?;
+        if file.metadata()?.len() != len {
+            file.set_len(len)?;
+        }
+        let _ = file.set_modified(SystemTime::now());
+        Ok(file)
+    }
+
+    // `None` while another source is writing `stem`.
+    fn claim(store: &Arc<Self>, stem: &str) -> Option<StemClaim> {
+        let claimed = store
+            .active
+            .lock()
+            .is_ok_and(|mut active| active.insert(stem.to_string()));
+        claimed.then(|| StemClaim {
+            store: Arc::clone(store),
+            stem: stem.to_string(),
+        })
+    }
+
+    fn read_index(&self, stem: &str) -> Option<(u64, RangeSet)> {
+        let index = fs::read_to_string(self.path(stem, INDEX_EXTENSION)).ok();
+        // The index only vouches for bytes in a part file of the length it was
+        // written for; a missing or truncated one would read back as zeros.
+        let parsed = index
+            .as_deref()
+            .and_then(RangeSet::parse_index)
+            .filter(|(len, _)| {
+                fs::metadata(self.path(stem, PARTIAL_EXTENSION))
+                    .is_ok_and(|metadata| metadata.len() == *len)
+            });
+        if parsed.is_none() {
+            // A part file without a usable index cannot be trusted either.
+            let _ = fs::remove_file(self.path(stem, PARTIAL_EXTENSION));
+            let _ = fs::remove_file(self.path(stem, INDEX_EXTENSION));
+        }
+        parsed
+    }
+
+    fn write_index(&self, stem: &str, len: u64, ranges: &RangeSet) {
+        if let Err(err) = fs::write(
+            self.path(stem, INDEX_EXTENSION),
+            ranges.to_index(len).as_bytes(),
+        ) {
+            warn!("failed to write stream cache index for {stem}: {err}");
+        }
+    }
+
+    fn complete(&self, stem: &str) {
+        let _ = fs::remove_file(self.path(stem, INDEX_EXTENSION));
+        if let Err(err) = fs::rename(
+            self.path(stem, PARTIAL_EXTENSION),
+            self.path(stem, COMPLETE_EXTENSION),
+        ) {
+            warn!("failed to finalize stream cache entry {stem}: {err}");
+        }
+    }
+
+    // Least recently used entries go first; modification time doubles as the
+    // access time because opening an entry touches it.
+    fn evict(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        let active = self
+            .active
+            .lock()
+            .map(|active| active.clone())
+            .unwrap_or_default();
+
+        let mut files = Vec::new();
+        let mut total = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(stem) = file_stem(&path) else {
+                continue;
+            };
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            total += metadata.len();
+            if active.contains(&stem) || !is_cache_data(&path) {
+                continue;
+            }
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            files.push((modified, path, metadata.len()));
+        }
+
+        files.sort_by_key(|(modified, _, _)| *modified);
+        for (_, path, len) in files {
+            if total <= self.max_bytes {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total = total.saturating_sub(len);
+                let index = path.with_extension(INDEX_EXTENSION);
+                if let Ok(metadata) = fs::metadata(&index)
+                    && fs::remove_file(&index).is_ok()
+                {
+                    total = total.saturating_sub(metadata.len());
+                }
+            }
+        }
+    }
+}
+
+// Held by the single writer of a cache entry; eviction and other openers leave
+// the entry alone until it is dropped.
+struct StemClaim {
+    store: Arc<CacheStore>,
+    stem: String,
+}
+
+impl Drop for StemClaim {
+    fn drop(&mut self) {
+        if let Ok(mut active) = self.store.active.lock() {
+            active.remove(&self.stem);
+        }
+    }
+}
+
+fn file_stem(path: &Path) -> Option<String> {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .map(ToString::to_string)
+}
+
+fn is_cache_data(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension == COMPLETE_EXTENSION || extension == PARTIAL_EXTENSION)
+}
+
+struct CachedStreamSource {
+    store: Arc<CacheStore>,
+    inner: Arc<dyn SourceFactory>,
+    upstream_spec: SourceSpec,
+    stem: String,
+    claim: Option<StemClaim>,
+    file: Option<File>,
+    len: u64,
+    ranges: RangeSet,
+    position: u64,
+    upstream: Option<Box<dyn MediaSource>>,
+    upstream_position: u64,
+    seekable: bool,
+    dirty: bool,
+}
+
+impl CachedStreamSource {
+    fn upstream_at(&mut self, position: u64) -> std::io::Result<&mut Box<dyn MediaSource>> {
+        if self.upstream.is_none() {
+            let opened = self
+                .inner
+                .open(&self.upstream_spec)
+                .map_err(|err| std::io::Error::other(err.to_string()))?;
+            self.seekable = opened.seekable;
+            self.upstream = Some(opened.media_source);
+            self.upstream_position = 0;
+        }
+
+        let upstream = self.upstream.as_mut().expect("upstream opened above");
+        if self.upstream_position != position {
+            self.upstream_position = upstream.seek(SeekFrom::Start(position))?;
+        }
+        Ok(upstream)
+    }
+
+    fn read_cached(&mut self, buf: &mut [u8], end: u64) -> std::io::Result<usize> {
+        let len = (end - self.position).min(buf.len() as u64) as usize;
+        let file = self.file.as_mut().expect("cache file open while reading");
+        file.seek(SeekFrom::Start(self.position))?;
+        let read = file.read(&mut buf[..len])?;
+        self.position += read as u64;
+        Ok(read)
+    }
+
+    fn store_fetched(&mut self, start: u64, bytes: &[u8]) {
+        let Some(file) = self.file.as_mut() else {
+            return;
+        };
+        let written = file
+            .seek(SeekFrom::Start(start))
+            .and_then(|_| file.write_all(bytes));
+        match written {
+            Ok(()) => {
+                self.ranges.insert(start, start + bytes.len() as u64);
+                self.dirty = true;
+            }
+            // Playback carries on from the network if the cache disk fails.
+            Err(err) => warn!("failed to write stream cache for {}: {err}", self.stem),
+        }
+    }
+}
+
+impl Read for CachedStreamSource {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() || self.position >= self.len {
+            return Ok(0);
+        }
+
+        if let Some(end) = self.ranges.covering(self.position) {
+            return self.read_cached(buf, end);
+        }
+
+        let position = self.position;
+        let limit = self.ranges.next_start(position).unwrap_or(self.len);
+        let len = (limit - position).min(buf.len() as u64) as usize;
+        let read = self.upstream_at(position)?.read(&mut buf[..len])?;
+        self.upstream_position += read as u64;
+        if read > 0 {
+            self.store_fetched(position, &buf[..read]);
+            self.position += read as u64;
+        }
+        Ok(read)
+    }
+}
+
+impl Seek for CachedStreamSource {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => offset as i128,
+            SeekFrom::Current(delta) => self.position as i128 + delta as i128,
+            SeekFrom::End(delta) => self.len as i128 + delta as i128,
+        };
+        if target < 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "seek before start of stream",
+            ));
+        }
+        // Upstream is only repositioned once a read misses the cache.
+        self.position = target as u64;
+        Ok(self.position)
+    }
+}
+
+impl MediaSource for CachedStreamSource {
+    fn is_seekable(&self) -> bool {
+        self.seekable
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        Some(self.len)
+    }
+}
+
+impl Drop for CachedStreamSource {
+    fn drop(&mut self) {
+        // Close the file first so the rename also works on Windows.
+        if let Some(file) = self.file.take() {
+            let _ = file.sync_data();
+        }
+        if self.ranges.is_complete(self.len) {
+            self.store.complete(&self.stem);
+        } else if self.dirty {
+            self.store.write_index(&self.stem, self.len, &self.ranges);
+        }
+        self.claim = None;
+        self.store.evict();
+    }
+}
+
+// Sorted, non-overlapping half-open byte ranges.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct RangeSet {
+    ranges: Vec<(u64, u64)>,
+}
+
+impl RangeSet {
+    fn insert(&mut self, start: u64, end: u64) {
+        if start >= end {
+            return;
+        }
+        self.ranges.push((start, end));
+        self.ranges.sort_unstable();
+
+        let mut merged: Vec<(u64, u64)> = Vec::with_capacity(self.ranges.len());
+        for (start, end) in self.ranges.drain(..) {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        self.ranges = merged;
+    }
+
+    fn covering(&self, position: u64) -> Option<u64> {
+        self.ranges
+            .iter()
+            .find(|(start, end)| *start <= position && position < *end)
+            .map(|(_, end)| *end)
+    }
+
+    fn next_start(&self, position: u64) -> Option<u64> {
+        self.ranges
+            .iter()
+            .map(|(start, _)| *start)
+            .find(|start| *start > position)
+    }
+
+    fn is_complete(&self, len: u64) -> bool {
+        self.ranges == [(0, len)]
+    }
+
+    fn to_index(&self, len: u64) -> String {
+        let mut index = format!("{len}\n");
+        for (start, end) in &self.ranges {
+            index.push_str(&format!("{start} {end}\n"));
+        }
+        index
+    }
+
+    fn parse_index(index: &str) -> Option<(u64, RangeSet)> {
+        let mut lines = index.lines();
+        let len = lines.next()?.trim().parse().ok()?;
+        let mut ranges = RangeSet::default();
+        for line in lines {
+            let (start, end) = line.split_once(' ')?;
+            let (start, end) = (start.parse().ok()?, end.parse().ok()?);
+            if end > len {
+                return None;
+            }
+            ranges.insert(start, end);
+        }
+        Some((len, ranges))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::{CachingSourceFactory, RangeSet};
+    use crate::config::StreamCacheConfig;
+    use crate::error::{AudioError, Result};
+    use crate::source::{OpenedSource, SourceFactory, SourceSpec, StreamCacheKey};
+
+    struct MemoryFactory {
+        bytes: Vec<u8>,
+        opens: AtomicUsize,
+    }
+
+    impl MemoryFactory {
+        fn new(len: usize) -> Arc<Self> {
+            Arc::new(Self {
+                bytes: (0..len).map(|index| (index % 251) as u8).collect(),
+                opens: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    impl SourceFactory for MemoryFactory {
+        fn open(&self, _spec: &SourceSpec) -> Result<OpenedSource> {
+            self.opens.fetch_add(1, Ordering::SeqCst);
+            Ok(OpenedSource {
+                media_source: Box::new(Cursor::new(self.bytes.clone())),
+                seekable: true,
+            })
+        }
+    }
+
+    struct OfflineFactory;
+
+    impl SourceFactory for OfflineFactory {
+        fn open(&self, _spec: &SourceSpec) -> Result<OpenedSource> {
+            Err(AudioError::Network {
+                reason: "offline".into(),
+            })
+        }
+    }
+
+    fn temp_cache_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("ame-stream-cache-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn spec(track_id: i64) -> SourceSpec {
+        SourceSpec::cached_stream(
+            "https://example.invalid/a.mp3",
+            StreamCacheKey::new(track_id, 320_000),
+        )
+    }
+
+    fn read_all(factory: &CachingSourceFactory, spec: &SourceSpec) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        factory
+            .open(spec)
+            .expect("open cached stream")
+            .media_source
+            .read_to_end(&mut bytes)
+            .expect("read cached stream");
+        bytes
+    }
+
+    #[test]
+    fn complete_stream_replays_without_network() {
+        let dir = temp_cache_dir("replay");
+        let upstream = MemoryFactory::new(100_000);
+        let factory =
+            CachingSourceFactory::new(upstream.clone(), StreamCacheConfig::new(&dir)).unwrap();
+        assert_eq!(read_all(&factory, &spec(1)), upstream.bytes);
+        assert!(dir.join("1-320000.audio").exists());
+
+        let offline =
+            CachingSourceFactory::new(Arc::new(OfflineFactory), StreamCacheConfig::new(&dir))
+                .unwrap();
+        assert_eq!(read_all(&offline, &spec(1)), upstream.bytes);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn partial_ranges_are_served_from_disk() {
+        let dir = temp_cache_dir("partial");
+        let upstream = MemoryFactory::new(50_000);
+        let factory =
+            CachingSourceFactory::new(upstream.clone(), StreamCacheConfig::new(&dir)).unwrap();
+
+        {
+            let mut source = factory.open(&spec(2)).unwrap().media_source;
+            let mut head = vec![0; 10_000];
+            source.read_exact(&mut head).unwrap();
+            source.seek(SeekFrom::Start(40_000)).unwrap();
+            let mut tail = Vec::new();
+            source.read_to_end(&mut tail).unwrap();
+            assert_eq!(tail, upstream.bytes[40_000..]);
+        }
+        assert!(dir.join("2-320000.ranges").exists());
+
+        // Re-reading the cached head must not reopen the upstream.
+        let opens = upstream.opens.load(Ordering::SeqCst);
+        let mut source = factory.open(&spec(2)).unwrap().media_source;
+        let mut head = vec![0; 10_000];
+        source.read_exact(&mut head).unwrap();
+        assert_eq!(head, upstream.bytes[..10_000]);
+        assert_eq!(upstream.opens.load(Ordering::SeqCst), opens);
+
+        let mut rest = Vec::new();
+        source.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, upstream.bytes[10_000..]);
+        drop(source);
+        assert!(dir.join("2-320000.audio").exists());
+        assert!(!dir.join("2-320000.ranges").exists());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn second_source_for_an_entry_leaves_its_index_alone() {
+        let dir = temp_cache_dir("single-writer");
+        let upstream = MemoryFactory::new(50_000);
+        let factory =
+            CachingSourceFactory::new(upstream.clone(), StreamCacheConfig::new(&dir)).unwrap();
+
+        let mut first = factory.open(&spec(4)).unwrap().media_source;
+        let mut second = factory.open(&spec(4)).unwrap().media_source;
+        let mut head = vec![0; 10_000];
+        first.read_exact(&mut head).unwrap();
+        second.seek(SeekFrom::Start(30_000)).unwrap();
+        second.read_exact(&mut head).unwrap();
+        drop(first);
+        drop(second);
+
+        // The head read by the writer is still served once offline.
+        let offline =
+            CachingSourceFactory::new(Arc::new(OfflineFactory), StreamCacheConfig::new(&dir))
+                .unwrap();
+        let mut source = offline.open(&spec(4)).unwrap().media_source;
+        source.read_exact(&mut head).unwrap();
+        assert_eq!(head, upstream.bytes[..10_000]);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn index_without_its_part_file_is_discarded() {
+        let dir = temp_cache_dir("missing-part");
+        let upstream = MemoryFactory::new(50_000);
+        let factory =
+            CachingSourceFactory::new(upstream.clone(), StreamCacheConfig::new(&dir)).unwrap();
+        {
+            let mut source = factory.open(&spec(5)).unwrap().media_source;
+            let mut head = vec![0; 10_000];
+            source.read_exact(&mut head).unwrap();
+        }
+        std::fs::File::options()
+            .write(true)
+            .open(dir.join("5-320000.part"))
+            .unwrap()
+            .set_len(5_000)
+            .unwrap();
+
+        let mut source = factory.open(&spec(5)).unwrap().media_source;
+        let mut head = vec![0; 10_000];
+        source.read_exact(&mut head).unwrap();
+        assert_eq!(head, upstream.bytes[..10_000]);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn least_recently_used_entries_are_evicted() {
+        let dir = temp_cache_dir("evict");
+        let upstream = MemoryFactory::new(40_000);
+        let config = StreamCacheConfig {
+            dir: dir.clone(),
+            max_bytes: 100_000,
+        };
+        let factory = CachingSourceFactory::new(upstream, config).unwrap();
+        for track_id in 1..=3 {
+            read_all(&factory, &spec(track_id));
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        assert!(!dir.join("1-320000.audio").exists());
+        assert!(dir.join("2-320000.audio").exists());
+        assert!(dir.join("3-320000.audio").exists());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn range_set_merges_adjacent_ranges() {
+        let mut ranges = RangeSet::default();
+        ranges.insert(10, 20);
+        ranges.insert(0, 5);
+        ranges.insert(5, 10);
+        assert!(ranges.is_complete(20));
+        assert_eq!(ranges.covering(7), Some(20));
+
+        let (len, parsed) = RangeSet::parse_index(&ranges.to_index(30)).unwrap();
+        assert_eq!((len, parsed), (30, ranges));
+    }
+}
@@ -0,0 +1,414 @@
+use std::f32::consts::FRAC_1_SQRT_2;
+
+use symphonia::core::audio::Channels;
+
+use crate::Sample;
+use crate::config::ChannelMixMode;
+
+const FL: Channels = Channels::FRONT_LEFT;
+const FR: Channels = Channels::FRONT_RIGHT;
+const FC: Channels = Channels::FRONT_CENTRE;
+const LFE: Channels = Channels::LFE1;
+const RL: Channels = Channels::REAR_LEFT;
+const RR: Channels = Channels::REAR_RIGHT;
+const RC: Channels = Channels::REAR_CENTRE;
+const SL: Channels = Channels::SIDE_LEFT;
+const SR: Channels = Channels::SIDE_RIGHT;
+
+// ITU-R BS.775 folds centre and surrounds into the fronts at -3 dB. The LFE
+// is not part of that matrix; it is kept at -6 dB below the centre so bass
+// that was never mixed into the mains is not lost entirely.
+const CENTRE_GAIN: f32 = FRAC_1_SQRT_2;
+const SURROUND_GAIN: f32 = FRAC_1_SQRT_2;
+const LFE_GAIN: f32 = 0.5;
+
+// Remixes interleaved frames from the decoded layout to the output's channel
+// count. Layouts that already match pass through untouched.
+pub(crate) struct ChannelMixer {
+    inputs: usize,
+    outputs: usize,
+    // Row per output channel, column per input channel.
+    matrix: Option<Vec<f32>>,
+    scratch: Vec<Sample>,
+}
+
+impl ChannelMixer {
+    pub fn new(source: Channels, inputs: usize, outputs: usize, mode: ChannelMixMode) -> Self {
+        let inputs = inputs.max(1);
+        let outputs = outputs.max(1);
+        let matrix = match mode {
+            ChannelMixMode::Downmix => mix_matrix(source, inputs, outputs, false),
+            ChannelMixMode::Upmix => mix_matrix(source, inputs, outputs, true),
+            ChannelMixMode::Discrete => None,
+        }
+        .unwrap_or_else(|| discrete_matrix(inputs, outputs));
+        let identity = inputs == outputs
+            && matrix.iter().enumerate().all(|(index, gain)| {
+                let expected = if index / inputs == index % inputs {
+                    1.0
+                } else {
+                    0.0
+                };
+                *gain == expected
+            });
+
+        Self {
+            inputs,
+            outputs,
+            matrix: (!identity).then_some(matrix),
+            scratch: Vec::new(),
+        }
+    }
+
+    pub fn process<'a>(&'a mut self, samples: &'a [Sample]) -> &'a [Sample] {
+        let Some(matrix) = &self.matrix else {
+            return samples;
+        };
+
+        self.scratch.clear();
+        self.scratch
+            .reserve(samples.len() / self.inputs * self.outputs);
+        for frame in samples.chunks_exact(self.inputs) {
+            for row in matrix.chunks_exact(self.inputs) {
+                let mixed = row
+                    .iter()
+                    .zip(frame)
+                    .map(|(gain, sample)| gain * sample)
+                    .sum();
+                self.scratch.push(mixed);
+            }
+        }
+        &self.scratch
+    }
+}
+
+// Speaker positions assumed for a device that only reports a channel count,
+// in WAVEFORMATEXTENSIBLE order.
+fn device_layout(count: usize) -> Option<&'static [Channels]> {
+    Some(match count {
+        1 => &[FC],
+        2 => &[FL, FR],
+        3 => &[FL, FR, FC],
+        4 => &[FL, FR, RL, RR],
+        5 => &[FL, FR, FC, RL, RR],
+        6 => &[FL, FR, FC, LFE, RL, RR],
+        7 => &[FL, FR, FC, LFE, RC, SL, SR],
+        8 => &[FL, FR, FC, LFE, RL, RR, SL, SR],
+        _ => return None,
+    })
+}
+
+fn source_layout(source: Channels, count: usize) -> Option<Vec<Channels>> {
+    // Symphonia reports mono as front-left; it belongs in the centre.
+    if count == 1 {
+        return Some(vec![FC]);
+    }
+    if source.count() == count {
+        return Some(source.iter().collect());
+    }
+    device_layout(count).map(<[Channels]>::to_vec)
+}
+
+fn mix_matrix(source: Channels, inputs: usize, outputs: usize, upmix: bool) -> Option<Vec<f32>> {
+    let source = source_layout(source, inputs)?;
+    let target = device_layout(outputs)?;
+    let target_mask = target
+        .iter()
+        .fold(Channels::empty(), |mask, channel| mask | *channel);
+
+    let mut matrix = vec![0.0; outputs * inputs];
+    let mut add = |input: usize, channel: Channels, gain: f32| {
+        if let Some(output) = target.iter().position(|speaker| *speaker == channel) {
+            matrix[output * inputs + input] += gain;
+        }
+    };
+
+    let upmix = upmix && outputs > 2;
+    if source == [FC] && (upmix || !target_mask.contains(FC)) {
+        // Mono plays at full level on both sides rather than at -3 dB, as it
+        // always has.
+        for side in [FL, FR] {
+            for (channel, gain) in upmix_routes(side, target_mask, upmix) {
+                add(0, channel, gain);
+            }
+        }
+    } else if source == [FL, FR] && upmix {
+        for (input, side) in [FL, FR].into_iter().enumerate() {
+            for (channel, gain) in upmix_routes(side, target_mask, true) {
+                add(input, channel, gain);
+            }
+        }
+    } else {
+        for (input, channel) in source.iter().enumerate() {
+            let mut routes = Vec::new();
+            route(*channel, target_mask, 1.0, 3, &mut routes);
+            for (channel, gain) in routes {
+                add(input, channel, gain);
+            }
+        }
+    }
+
+    // Scale the whole matrix so no output can exceed full scale, which keeps
+    // the balance between channels intact.
+    let loudest = matrix
+        .chunks_exact(inputs)
+        .map(|row| row.iter().sum::<f32>())
+        .fold(1.0, f32::max);
+    if loudest > 1.0 {
+        matrix.iter_mut().for_each(|gain| *gain /= loudest);
+    }
+    Some(matrix)
+}
+
+// Where one side of a stereo signal goes. Upmixing adds a phantom centre and
+// feeds the surrounds; the LFE is left alone since there is no crossover.
+fn upmix_routes(side: Channels, target: Channels, upmix: bool) -> Vec<(Channels, f32)> {
+    let mut routes = vec![(side, 1.0)];
+    if !upmix {
+        return routes;
+    }
+    let (rear, surround) = if side == FL { (RL, SL) } else { (RR, SR) };
+    if target.contains(FC) {
+        routes.push((FC, 0.5));
+    }
+    for channel in [rear, surround] {
+        if target.contains(channel) {
+            routes.push((channel, SURROUND_GAIN));
+        }
+    }
+    if target.contains(RC) {
+        routes.push((RC, 0.5 * SURROUND_GAIN));
+    }
+    routes
+}
+
+// Sends `channel` to the output speaker it names, or to the nearest
+// substitute. Alternatives are tried in order; the last one is followed
+// further if the output has none of them.
+fn route(
+    channel: Channels,
+    target: Channels,
+    gain: f32,
+    depth: u8,
+    routes: &mut Vec<(Channels, f32)>,
+) {
+    if target.contains(channel) {
+        routes.push((channel, gain));
+        return;
+    }
+    let alternatives = fallbacks(channel);
+    if let Some(alternative) = alternatives
+        .iter()
+        .find(|alternative| alternative.iter().all(|(to, _)| target.contains(*to)))
+    {
+        routes.extend(alternative.iter().map(|(to, scale)| (*to, gain * scale)));
+        return;
+    }
+    if depth == 0 {
+        return;
+    }
+    if let Some(last) = alternatives.last() {
+        for (to, scale) in last.iter() {
+            route(*to, target, gain * scale, depth - 1, routes);
+        }
+    }
+}
+
+type Routes = &'static [&'static [(Channels, f32)]];
+
+fn fallbacks(channel: Channels) -> Routes {
+    const LEFT: Routes = &[&[(FL, SURROUND_GAIN)]];
+    const RIGHT: Routes = &[&[(FR, SURROUND_GAIN)]];
+    const CENTRE: Routes = &[&[(FC, CENTRE_GAIN)]];
+
+    match channel {
+        FC => &[&[(FL, CENTRE_GAIN), (FR, CENTRE_GAIN)]],
+        FL | FR => CENTRE,
+        LFE | Channels::LFE2 => &[&[(LFE, 1.0)], &[(FC, LFE_GAIN)]],
+        RL => &[&[(SL, 1.0)], &[(FL, SURROUND_GAIN)]],
+        RR => &[&[(SR, 1.0)], &[(FR, SURROUND_GAIN)]],
+        SL => &[&[(RL, 1.0)], &[(FL, SURROUND_GAIN)]],
+        SR => &[&[(RR, 1.0)], &[(FR, SURROUND_GAIN)]],
+        RC => &[
+            &[(RL, SURROUND_GAIN), (RR, SURROUND_GAIN)],
+            &[(SL, SURROUND_GAIN), (SR, SURROUND_GAIN)],
+            &[(FL, 0.5), (FR, 0.5)],
+        ],
+        Channels::FRONT_LEFT_CENTRE => &[&[(FL, FRAC_1_SQRT_2), (FC, FRAC_1_SQRT_2)], &[(FL, 1.0)]],
+        Channels::FRONT_RIGHT_CENTRE => {
+            &[&[(FR, FRAC_1_SQRT_2), (FC, FRAC_1_SQRT_2)], &[(FR, 1.0)]]
+        }
+        Channels::REAR_LEFT_CENTRE => &[&[(RL, 1.0)], &[(SL, 1.0)], &[(FL, SURROUND_GAIN)]],
+        Channels::REAR_RIGHT_CENTRE => &[&[(RR, 1.0)], &[(SR, 1.0)], &[(FR, SURROUND_GAIN)]],
+        Channels::TOP_FRONT_LEFT
+        | Channels::TOP_REAR_LEFT
+        | Channels::FRONT_LEFT_WIDE
+        | Channels::FRONT_LEFT_HIGH => LEFT,
+        Channels::TOP_FRONT_RIGHT
+        | Channels::TOP_REAR_RIGHT
+        | Channels::FRONT_RIGHT_WIDE
+        | Channels::FRONT_RIGHT_HIGH => RIGHT,
+        _ => CENTRE,
+    }
+}
+
+// Channel `n` to channel `n`, dropping or silencing the rest; mono and stereo
+// still convert into each other.
+fn discrete_matrix(inputs: usize, outputs: usize) -> Vec<f32> {
+    let mut matrix = vec![0.0; outputs * inputs];
+    match (inputs, outputs) {
+        (1, 2) => matrix.fill(1.0),
+        (2, 1) => matrix.fill(0.5),
+        _ => {
+            for channel in 0..inputs.min(outputs) {
+                matrix[channel * inputs + channel] = 1.0;
+            }
+        }
+    }
+    matrix
+}
+
+#[cfg(test)]
+mod tests {
+    use symphonia::core::audio::Channels;
+
+    use super::{ChannelMixer, FC, FL, FR, LFE, RL, RR, SL, SR};
+    use crate::config::ChannelMixMode;
+
+    const FIVE_ONE_SIDE: Channels = FL.union(FR).union(FC).union(LFE).union(SL).union(SR);
+
+    fn mix(source: Channels, outputs: usize, mode: ChannelMixMode, frame: &[f32]) -> Vec<f32> {
+        ChannelMixer::new(source, frame.len(), outputs, mode)
+            .process(frame)
+            .to_vec()
+    }
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?}");
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-4, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn matching_layouts_pass_through() {
+        let mut mixer = ChannelMixer::new(FL | FR, 2, 2, ChannelMixMode::Downmix);
+        assert!(mixer.matrix.is_none());
+        assert_eq!(mixer.process(&[0.1, 0.2]), &[0.1, 0.2]);
+    }
+
+    #[test]
+    fn mono_and_stereo_convert_into_each_other() {
+        assert_close(&mix(FL, 2, ChannelMixMode::Downmix, &[0.5]), &[0.5, 0.5]);
+        assert_close(
+            &mix(FL | FR, 1, ChannelMixMode::Downmix, &[0.2, 0.6]),
+            &[0.4],
+        );
+    }
+
+    #[test]
+    fn five_one_downmixes_to_stereo_with_itu_gains() {
+        let frame = [0.0, 0.0, 1.0, 0.0, 0.0, 0.0];
+        let centre = mix(FIVE_ONE_SIDE, 2, ChannelMixMode::Downmix, &frame);
+        let left_row = 1.0 + 2.0 * std::f32::consts::FRAC_1_SQRT_2 + 0.5 * 0.5f32.sqrt();
+        let expected = std::f32::consts::FRAC_1_SQRT_2 / left_row;
+        assert_close(&centre, &[expected, expected]);
+
+        let side_left = mix(
+            FIVE_ONE_SIDE,
+            2,
+            ChannelMixMode::Downmix,
+            &[0.0, 0.0, 0.0, 0.0, 1.0, 0.0],
+        );
+        assert_close(&side_left, &[expected, 0.0]);
+
+        let lfe = mix(
+            FIVE_ONE_SIDE,
+            2,
+            ChannelMixMode::Downmix,
+            &[0.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+        );
+        assert!(lfe[0] > 0.0 && lfe[0] == lfe[1]);
+
+        let full = mix(FIVE_ONE_SIDE, 2, ChannelMixMode::Downmix, &[1.0; 6]);
+        assert_close(&full, &[1.0, 1.0]);
+    }
+
+    #[test]
+    fn five_one_downmixes_to_mono() {
+        let mono = mix(FIVE_ONE_SIDE, 1, ChannelMixMode::Downmix, &[1.0; 6]);
+        assert_close(&mono, &[1.0]);
+        let left = mix(
+            FIVE_ONE_SIDE,
+            1,
+            ChannelMixMode::Downmix,
+            &[1.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+        );
+        let right = mix(
+            FIVE_ONE_SIDE,
+            1,
+            ChannelMixMode::Downmix,
+            &[0.0, 1.0, 0.0, 0.0, 0.0, 0.0],
+        );
+        assert!(left[0] > 0.0);
+        assert_close(&left, &right);
+    }
+
+    #[test]
+    fn side_surrounds_map_onto_rear_speakers() {
+        let frame = [0.1, 0.2, 0.3, 0.4, 0.5, 0.6];
+        let mixed = mix(FIVE_ONE_SIDE, 6, ChannelMixMode::Downmix, &frame);
+        assert_close(&mixed, &frame);
+    }
+
+    #[test]
+    fn seven_one_folds_into_five_one() {
+        let seven_one = FL | FR | FC | LFE | RL | RR | SL | SR;
+        let frame = [0.1, 0.1, 0.1, 0.1, 0.1, 0.2, 0.3, 0.4];
+        let mixed = mix(seven_one, 6, ChannelMixMode::Downmix, &frame);
+        assert_close(&mixed, &[0.05, 0.05, 0.05, 0.05, 0.2, 0.3]);
+    }
+
+    #[test]
+    fn quad_downmixes_to_stereo() {
+        let quad = FL | FR | RL | RR;
+        let mixed = mix(quad, 2, ChannelMixMode::Downmix, &[1.0, 0.0, 1.0, 0.0]);
+        assert_close(&mixed, &[1.0, 0.0]);
+        let rear = mix(quad, 2, ChannelMixMode::Downmix, &[0.0, 0.0, 0.0, 1.0]);
+        let expected = std::f32::consts::FRAC_1_SQRT_2 / (1.0 + std::f32::consts::FRAC_1_SQRT_2);
+        assert_close(&rear, &[0.0, expected]);
+    }
+
+    #[test]
+    fn stereo_upmixes_only_when_asked() {
+        let plain = mix(FL | FR, 6, ChannelMixMode::Downmix, &[0.4, 0.8]);
+        assert_close(&plain, &[0.4, 0.8, 0.0, 0.0, 0.0, 0.0]);
+
+        let upmixed = mix(FL | FR, 6, ChannelMixMode::Upmix, &[0.4, 0.8]);
+        let surround = std::f32::consts::FRAC_1_SQRT_2;
+        assert_close(
+            &upmixed,
+            &[0.4, 0.8, 0.6, 0.0, 0.4 * surround, 0.8 * surround],
+        );
+    }
+
+    #[test]
+    fn mono_upmixes_to_every_main_speaker() {
+        let plain = mix(FL, 6, ChannelMixMode::Downmix, &[0.5]);
+        assert_close(&plain, &[0.0, 0.0, 0.5, 0.0, 0.0, 0.0]);
+
+        let upmixed = mix(FL, 6, ChannelMixMode::Upmix, &[0.5]);
+        assert!(upmixed[..3].iter().all(|sample| *sample > 0.0));
+        assert_eq!(upmixed[3], 0.0);
+        assert!(upmixed[4] > 0.0 && upmixed[4] == upmixed[5]);
+    }
+
+    #[test]
+    fn discrete_keeps_channels_by_index() {
+        let frame = [0.1, 0.2, 0.3, 0.4, 0.5, 0.6];
+        let mixed = mix(FIVE_ONE_SIDE, 2, ChannelMixMode::Discrete, &frame);
+        assert_close(&mixed, &[0.1, 0.2]);
+        let widened = mix(FL | FR, 4, ChannelMixMode::Discrete, &[0.1, 0.2]);
+        assert_close(&widened, &[0.1, 0.2, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,299 @@
+use std::sync::OnceLock;
+
+use symphonia::core::audio::Channels;
+use symphonia::core::codecs::{
+    CODEC_TYPE_AAC, CODEC_TYPE_ALAC, CODEC_TYPE_FLAC, CODEC_TYPE_MP3, CODEC_TYPE_OPUS,
+    CODEC_TYPE_PCM_ALAW, CODEC_TYPE_PCM_F32BE, CODEC_TYPE_PCM_F32LE, CODEC_TYPE_PCM_F64BE,
+    CODEC_TYPE_PCM_F64LE, CODEC_TYPE_PCM_MULAW, CODEC_TYPE_PCM_S8, CODEC_TYPE_PCM_S16BE,
+    CODEC_TYPE_PCM_S16LE, CODEC_TYPE_PCM_S24BE, CODEC_TYPE_PCM_S24LE, CODEC_TYPE_PCM_S32BE,
+    CODEC_TYPE_PCM_S32LE, CODEC_TYPE_PCM_U8, CODEC_TYPE_PCM_U16BE, CODEC_TYPE_PCM_U16LE,
+    CODEC_TYPE_PCM_U24BE, CODEC_TYPE_PCM_U24LE, CODEC_TYPE_PCM_U32BE, CODEC_TYPE_PCM_U32LE,
+    CODEC_TYPE_VORBIS, CodecParameters, CodecRegistry, CodecType,
+};
+
+use crate::snapshot::TrackFormat;
+
+pub(crate) fn codec_registry() -> &'static CodecRegistry {
+    static REGISTRY: OnceLock<CodecRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut registry = CodecRegistry::new();
+        symphonia::default::register_enabled_codecs(&mut registry);
+        #[cfg(feature = "codec-opus")]
+        registry.register_all::<crate::opus::OpusDecoder>();
+        registry
+    })
+}
+
+// MP4 sample entries leave the channel layout (and, for ALAC, the bit depth)
+// to the codec configuration, so fill them in before opening the decoder.
+pub(crate) fn complete_codec_params(params: &CodecParameters) -> CodecParameters {
+    let mut params = params.clone();
+    match (params.codec, params.extra_data.as_deref()) {
+        (CODEC_TYPE_ALAC, Some(cookie)) if cookie.len() >= 24 => {
+            let bit_depth = u32::from(cookie[5]);
+            let channels = usize::from(cookie[9]);
+            let sample_rate = u32::from_be_bytes([cookie[20], cookie[21], cookie[22], cookie[23]]);
+            params.with_bits_per_sample(bit_depth);
+            if sample_rate > 0 {
+                params.with_sample_rate(sample_rate);
+            }
+            if params.channels.is_none()
+                && let Some(channels) = channels_for_count(channels)
+            {
+                params.with_channels(channels);
+            }
+        }
+        (CODEC_TYPE_AAC, Some(config)) if params.channels.is_none() => {
+            if let Some(channels) = aac_channel_count(config).and_then(channels_for_count) {
+                params.with_channels(channels);
+            }
+        }
+        _ => {}
+    }
+    params
+}
+
+pub(crate) fn describe_format(
+    params: &CodecParameters,
+    byte_len: Option<u64>,
+    duration_ms: Option<u64>,
+) -> TrackFormat {
+    let sample_rate = params.sample_rate.unwrap_or(0);
+    let channels = params
+        .channels
+        .map(|channels| channels.count() as u16)
+        .unwrap_or(0);
+    let bit_depth = params.bits_per_sample;
+
+    let bitrate_kbps = if is_pcm(params.codec) {
+        bit_depth.map(|bits| sample_rate * bits * channels as u32 / 1000)
+    } else {
+        // Containers rarely carry a reliable bitrate, so derive the average
+        // from the stream size. Container overhead is negligible here.
+        byte_len
+            .zip(duration_ms.filter(|duration_ms| *duration_ms > 0))
+            .map(|(bytes, duration_ms)| (bytes * 8 / duration_ms) as u32)
+    };
+
+    TrackFormat {
+        codec: codec_label(params.codec),
+        sample_rate,
+        channels,
+        bit_depth,
+        bitrate_kbps,
+    }
+}
+
+// Reads the channel configuration from an AudioSpecificConfig (ISO 14496-3).
+fn aac_channel_count(config: &[u8]) -> Option<usize> {
+    if config.len() < 2 {
+        return None;
+    }
+    let mut bits = config
+        .iter()
+        .take(8)
+        .fold(0u64, |bits, byte| (bits << 8) | u64::from(*byte));
+    bits <<= 8 * (8 - config.len().min(8));
+    let mut offset = 0;
+    let mut read = |count: u32| {
+        let value = (bits << offset) >> (64 - count);
+        offset += count;
+        value
+    };
+
+    if read(5) == 31 {
+        read(6);
+    }
+    if read(4) == 15 {
+        read(24);
+    }
+    match read(4) {
+        count @ 1..=6 => Some(count as usize),
+        7 => Some(8),
+        _ => None,
+    }
+}
+
+fn channels_for_count(count: usize) -> Option<Channels> {
+    match count {
+        1..=32 => Channels::from_bits((u64::MAX >> (64 - count)) as u32),
+        _ => None,
+    }
+}
+
+fn codec_label(codec: CodecType) -> String {
+    let label = match codec {
+        CODEC_TYPE_FLAC => "FLAC",
+        CODEC_TYPE_MP3 => "MP3",
+        CODEC_TYPE_AAC => "AAC",
+        CODEC_TYPE_ALAC => "ALAC",
+        CODEC_TYPE_VORBIS => "Vorbis",
+        CODEC_TYPE_OPUS => "Opus",
+        codec if is_pcm(codec) => "PCM",
+        codec => {
+            return codec_registry()
+                .get_codec(codec)
+                .map(|descriptor| descriptor.short_name.to_uppercase())
+                .unwrap_or_else(|| "Unknown".to_string());
+        }
+    };
+    label.to_string()
+}
+
+fn is_pcm(codec: CodecType) -> bool {
+    matches!(
+        codec,
+        CODEC_TYPE_PCM_S8
+            | CODEC_TYPE_PCM_S16LE
+            | CODEC_TYPE_PCM_S16BE
+            | CODEC_TYPE_PCM_S24LE
+            | CODEC_TYPE_PCM_S24BE
+            | CODEC_TYPE_PCM_S32LE
+            | CODEC_TYPE_PCM_S32BE
+            | CODEC_TYPE_PCM_U8
+            | CODEC_TYPE_PCM_U16LE
+            | CODEC_TYPE_PCM_U16BE
+            | CODEC_TYPE_PCM_U24LE
+            | CODEC_TYPE_PCM_U24BE
+            | CODEC_TYPE_PCM_U32LE
+            | CODEC_TYPE_PCM_U32BE
+            | CODEC_TYPE_PCM_F32LE
+            | CODEC_TYPE_PCM_F32BE
+            | CODEC_TYPE_PCM_F64LE
+            | CODEC_TYPE_PCM_F64BE
+            | CODEC_TYPE_PCM_ALAW
+            | CODEC_TYPE_PCM_MULAW
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use symphonia::core::audio::SampleBuffer;
+    use symphonia::core::errors::Error;
+    use symphonia::core::io::{MediaSource, MediaSourceStream};
+    use symphonia::core::probe::Hint;
+    use symphonia::default::get_probe;
+
+    use super::{codec_registry, complete_codec_params, describe_format};
+    use crate::pipeline::fixtures;
+    use crate::snapshot::TrackFormat;
+
+    struct Decoded {
+        format: TrackFormat,
+        frames: usize,
+        peak: f32,
+    }
+
+    fn probe(bytes: Vec<u8>, extension: &str) -> Decoded {
+        let source = Cursor::new(bytes);
+        let byte_len = source.byte_len();
+        let mut hint = Hint::new();
+        hint.with_extension(extension);
+        let mut reader = get_probe()
+            .format(
+                &hint,
+                MediaSourceStream::new(Box::new(source), Default::default()),
+                &Default::default(),
+                &Default::default(),
+            )
+            .expect("probe fixture")
+            .format;
+        let track = reader.default_track().expect("default track");
+        let track_id = track.id;
+        let params = complete_codec_params(&track.codec_params);
+        let duration_ms = params
+            .n_frames
+            .zip(params.sample_rate)
+            .map(|(frames, rate)| frames * 1000 / u64::from(rate));
+        let format = describe_format(&params, byte_len, duration_ms);
+
+        let mut decoded = Decoded {
+            format,
+            frames: 0,
+            peak: 0.0,
+        };
+        let Ok(mut decoder) = codec_registry().make(&params, &Default::default()) else {
+            return decoded;
+        };
+        loop {
+            let packet = match reader.next_packet() {
+                Ok(packet) => packet,
+                Err(Error::IoError(_)) => break,
+                Err(err) => panic!("demux failed: {err}"),
+            };
+            if packet.track_id() != track_id {
+                continue;
+            }
+            let audio = decoder.decode(&packet).expect("decode packet");
+            let mut samples = SampleBuffer::<f32>::new(audio.capacity() as u64, *audio.spec());
+            samples.copy_interleaved_ref(audio);
+            let channels = usize::from(decoded.format.channels);
+            decoded.frames += samples.samples().len() / channels;
+            decoded.peak = samples
+                .samples()
+                .iter()
+                .fold(decoded.peak, |peak, sample| peak.max(sample.abs()));
+        }
+        decoded
+    }
+
+    #[test]
+    fn wav_reports_pcm_depth_and_bitrate() {
+        let pcm16 = probe(fixtures::wav(44_100, 2, 16, 4_410), "wav");
+        assert_eq!(pcm16.format.codec, "PCM");
+        assert_eq!(pcm16.format.channels, 2);
+        assert_eq!(pcm16.format.bitrate_kbps, Some(1_411));
+        assert_eq!(pcm16.format.badge(), "PCM 16/44.1");
+        assert_eq!(pcm16.frames, 4_410);
+        assert!((pcm16.peak - 0.5).abs() < 0.01);
+
+        let pcm24 = probe(fixtures::wav(96_000, 2, 24, 9_600), "wav");
+        assert_eq!(pcm24.format.bit_depth, Some(24));
+        assert_eq!(pcm24.format.badge(), "PCM 24/96");
+        assert_eq!(pcm24.frames, 9_600);
+        assert!((pcm24.peak - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn alac_in_mp4_decodes_losslessly() {
+        let decoded = probe(fixtures::alac_m4a(96_000, 24, 10_000), "m4a");
+        assert_eq!(decoded.format.codec, "ALAC");
+        assert_eq!(decoded.format.sample_rate, 96_000);
+        assert_eq!(decoded.format.channels, 1);
+        assert_eq!(decoded.format.badge(), "ALAC 24/96");
+        assert_eq!(decoded.frames, 10_000);
+        assert!((decoded.peak - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn aac_in_mp4_decodes() {
+        let decoded = probe(fixtures::aac_m4a(48_000, 8), "m4a");
+        assert_eq!(decoded.format.codec, "AAC");
+        assert_eq!(decoded.format.channels, 1);
+        assert_eq!(decoded.format.bit_depth, None);
+        assert!(decoded.format.bitrate_kbps.is_some());
+        assert_eq!(decoded.frames, 8 * 1024);
+    }
+
+    #[test]
+    fn ogg_vorbis_decodes() {
+        let decoded = probe(fixtures::ogg_vorbis(44_100, 16), "ogg");
+        assert_eq!(decoded.format.codec, "Vorbis");
+        assert_eq!(decoded.format.sample_rate, 44_100);
+        assert_eq!(decoded.format.channels, 1);
+        assert_eq!(decoded.frames, 15 * 128);
+    }
+
+    #[test]
+    fn ogg_opus_is_recognised() {
+        let decoded = probe(fixtures::ogg_opus(10), "opus");
+        assert_eq!(decoded.format.codec, "Opus");
+        assert_eq!(decoded.format.sample_rate, 48_000);
+        assert_eq!(decoded.format.channels, 2);
+        if cfg!(feature = "codec-opus") {
+            assert_eq!(decoded.frames, 10 * 960 - 312);
+        }
+    }
+}
@@ -1,6 +1,7 @@
-use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::Sender;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::{Arc, Mutex};
 use std::thread;
 
 use audioadapter_buffers::direct::InterleavedSlice;
@@ -8,95 +9,506 @@ use ringbuf::traits::Producer;
 use rubato::{
     Async, FixedAsync, SincInterpolationParameters, SincInterpolationType, WindowFunction,
 };
-use symphonia::core::audio::SampleBuffer;
-use symphonia::core::codecs::DecoderOptions;
-use symphonia::core::formats::{FormatOptions, SeekMode, SeekTo};
-use symphonia::core::io::MediaSourceStream;
-use symphonia::core::meta::MetadataOptions;
-use symphonia::core::units::Time;
-use symphonia::default::{get_codecs, get_probe};
+use symphonia::core::audio::{Channels, SampleBuffer};
+use symphonia::core::codecs::{Decoder, DecoderOptions};
+use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo, Track};
+use symphonia::core::io::{MediaSource, MediaSourceStream};
+use symphonia::core::meta::{MetadataOptions, MetadataRevision};
+use symphonia::core::probe::ProbeResult;
+use symphonia::core::units::{Time, TimeBase};
+use symphonia::default::get_probe;
 use tracing::debug;
 
 use crate::Sample;
-use crate::backend::{PlaybackDrainState, SampleRing};
-use crate::config::ResampleQualityPreset;
+use crate::backend::{PlaybackDrainState, SampleRing, equal_power};
+use crate::channels::ChannelMixer;
+use crate::codecs::{codec_registry, complete_codec_params, describe_format};
+use crate::config::{ChannelMixMode, NormalizationMode, ResampleQualityPreset};
+use crate::dsp::{DspChain, EqualizerControl};
 use crate::error::{AudioError, Result};
+use crate::loudness::{LoudnessNormalizer, ReplayGainTags};
+use crate::metadata::TrackMetadata;
+use crate::snapshot::TrackFormat;
 use crate::source::{SourceFactory, SourceSpec};
+use crate::stretch::TimeStretch;
+use crate::tags::read_metadata;
 
 pub(crate) type RingProducer = <SampleRing as ringbuf::traits::Split>::Prod;
+pub(crate) type NextSourceSlot = Arc<Mutex<Option<SourceSpec>>>;
+pub(crate) type SkipSlot = Arc<Mutex<Option<SkipRequest>>>;
+
+// A track the user switched to mid-playback. It is mixed in over `fade_ms`
+// from the playhead rather than after everything already queued.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SkipRequest {
+    pub source: SourceSpec,
+    pub start_ms: u64,
+    pub fade_ms: u64,
+}
+
+// Reports below this change are not worth waking the runtime for.
+const GAIN_REPORT_STEP_DB: f32 = 0.1;
 
 #[derive(Debug, Clone)]
 pub(crate) enum DecoderNotification {
     Duration(u64),
+    // Format of the track the decoder opened. A bit-perfect output is opened
+    // at it, so decoding waits for the output to be handed over.
+    Format {
+        source: SourceSpec,
+        format: TrackFormat,
+    },
+    Metadata {
+        source: SourceSpec,
+        metadata: TrackMetadata,
+    },
+    NormalizationGain {
+        source: SourceSpec,
+        gain_db: f32,
+    },
+    NextTrackStarted {
+        source: SourceSpec,
+        duration_ms: Option<u64>,
+        format: TrackFormat,
+    },
+    // The requested skip is being mixed in from the playhead onwards.
+    Skipped {
+        source: SourceSpec,
+        duration_ms: Option<u64>,
+        format: TrackFormat,
+    },
+    // The requested skip could not be mixed into this stream and needs a
+    // fresh one.
+    SkipFailed {
+        source: SourceSpec,
+    },
+    NextTrackFailed {
+        source: SourceSpec,
+        error: AudioError,
+    },
+    // The queued track was left for a fresh stream because its rate differs
+    // from the one the output runs at.
+    NextTrackRateChanged {
+        source: SourceSpec,
+        format: TrackFormat,
+    },
     Error(AudioError),
 }
 
+// How far into its source the track being decoded has read, for the buffer
+// health report. A total of zero means the length is unknown. `decoded_ms` is
+// how far into the track it has decoded, ahead of what is audible.
+#[derive(Debug, Default)]
+pub(crate) struct StreamProgress {
+    read_bytes: AtomicU64,
+    total_bytes: AtomicU64,
+    decoded_ms: AtomicU64,
+}
+
+impl StreamProgress {
+    pub fn get(&self) -> (u64, Option<u64>) {
+        let total = self.total_bytes.load(Ordering::Relaxed);
+        (
+            self.read_bytes.load(Ordering::Relaxed),
+            (total > 0).then_some(total),
+        )
+    }
+
+    pub fn decoded_ms(&self) -> u64 {
+        self.decoded_ms.load(Ordering::Acquire)
+    }
+
+    fn set_decoded_ms(&self, ms: u64) {
+        self.decoded_ms.store(ms, Ordering::Release);
+    }
+
+    fn mirror(&self, other: &StreamProgress) {
+        self.read_bytes
+            .store(other.read_bytes.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.total_bytes
+            .store(other.total_bytes.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+}
+
+// A-B loop on the current track, shared with the runtime. An end of zero means
+// no loop is set.
+#[derive(Debug, Default)]
+pub(crate) struct LoopRegion {
+    start_ms: AtomicU64,
+    end_ms: AtomicU64,
+}
+
+impl LoopRegion {
+    pub fn set(&self, region: Option<(u64, u64)>) {
+        self.end_ms.store(0, Ordering::Release);
+        if let Some((start_ms, end_ms)) = region {
+            self.start_ms.store(start_ms, Ordering::Release);
+            self.end_ms.store(end_ms, Ordering::Release);
+        }
+    }
+
+    pub fn get(&self) -> Option<(u64, u64)> {
+        let end_ms = self.end_ms.load(Ordering::Acquire);
+        (end_ms > 0).then(|| (self.start_ms.load(Ordering::Acquire), end_ms))
+    }
+}
+
+// The ring decoded audio goes into and the format the output runs at.
+pub(crate) struct DecoderOutput {
+    pub producer: RingProducer,
+    pub sample_rate: u32,
+    pub channels: usize,
+    // Tracks change with a hard cut; a crossfade would alter the samples.
+    pub bit_perfect: bool,
+}
+
 pub(crate) struct DecoderSpawnRequest {
     pub source_factory: Arc<dyn SourceFactory>,
     pub source_spec: SourceSpec,
-    pub target_sample_rate: u32,
-    pub target_channels: usize,
+    pub next_source: NextSourceSlot,
+    pub skip: SkipSlot,
+    // Sent once the output is open; dropped if it never will be.
+    pub output: Receiver<DecoderOutput>,
     pub start_ms: u64,
+    // Bit-perfect streams run at one track rate and end where it changes.
+    pub split_on_rate_change: bool,
     pub quality: ResampleQualityPreset,
+    pub channel_mix: ChannelMixMode,
+    pub normalization: NormalizationMode,
+    pub target_lufs: f32,
+    pub crossfade_ms: Arc<AtomicU64>,
+    pub playback_rate: Arc<AtomicU32>,
+    pub equalizer: Arc<EqualizerControl>,
     pub cancel: Arc<AtomicBool>,
     pub notification_tx: Sender<DecoderNotification>,
-    pub producer: RingProducer,
     pub drain_state: Arc<PlaybackDrainState>,
+    pub progress: Arc<StreamProgress>,
+    pub loop_region: Arc<LoopRegion>,
 }
 
 pub(crate) fn spawn_decoder(request: DecoderSpawnRequest) -> thread::JoinHandle<()> {
     thread::spawn(move || {
-        let DecoderSpawnRequest {
-            source_factory,
-            source_spec,
-            target_sample_rate,
-            target_channels,
-            start_ms,
-            quality,
-            cancel,
-            notification_tx,
-            producer,
-            drain_state,
-        } = request;
-        let result = decode_loop(
-            source_factory,
-            source_spec,
-            target_sample_rate,
-            target_channels,
-            start_ms,
-            quality,
-            cancel,
-            notification_tx.clone(),
-            producer,
-            drain_state,
-        );
-
-        if let Err(err) = result {
+        let notification_tx = request.notification_tx.clone();
+        if let Err(err) = decode_loop(request) {
             let _ = notification_tx.send(DecoderNotification::Error(err));
         }
     })
 }
 
-#[allow(clippy::too_many_arguments)]
-fn decode_loop(
-    source_factory: Arc<dyn SourceFactory>,
-    source_spec: SourceSpec,
-    target_sample_rate: u32,
-    target_channels: usize,
-    start_ms: u64,
-    quality: ResampleQualityPreset,
-    cancel: Arc<AtomicBool>,
-    notification_tx: Sender<DecoderNotification>,
-    mut producer: RingProducer,
-    drain_state: Arc<PlaybackDrainState>,
+fn decode_loop(request: DecoderSpawnRequest) -> Result<()> {
+    let DecoderSpawnRequest {
+        source_factory,
+        source_spec,
+        next_source,
+        skip,
+        output,
+        start_ms,
+        split_on_rate_change,
+        quality,
+        channel_mix,
+        normalization,
+        target_lufs,
+        crossfade_ms,
+        playback_rate,
+        equalizer,
+        cancel,
+        notification_tx,
+        drain_state,
+        progress,
+        loop_region,
+    } = request;
+
+    let opened = OpenedTrack::open(source_factory.as_ref(), source_spec, start_ms)?;
+    if let Some(duration_ms) = opened.duration_ms {
+        let _ = notification_tx.send(DecoderNotification::Duration(duration_ms));
+    }
+    let _ = notification_tx.send(DecoderNotification::Format {
+        source: opened.source_spec.clone(),
+        format: opened.format_info.clone(),
+    });
+    let Ok(DecoderOutput {
+        producer,
+        sample_rate: target_sample_rate,
+        channels: target_channels,
+        bit_perfect,
+    }) = output.recv()
+    else {
+        return Ok(());
+    };
+    let options = TrackOptions {
+        sample_rate: target_sample_rate,
+        channels: target_channels,
+        quality,
+        channel_mix,
+        normalization,
+        target_lufs,
+    };
+
+    let report_metadata = |track: &mut TrackDecoder| {
+        if let Some(metadata) = track.metadata.take() {
+            let _ = notification_tx.send(DecoderNotification::Metadata {
+                source: track.source_spec.clone(),
+                metadata,
+            });
+        }
+    };
+    let open_next = |spec: SourceSpec| -> Option<TrackDecoder> {
+        match TrackDecoder::open(source_factory.as_ref(), spec.clone(), 0, options) {
+            Ok(mut next) => {
+                report_metadata(&mut next);
+                Some(next)
+            }
+            Err(error) => {
+                let _ = notification_tx.send(DecoderNotification::NextTrackFailed {
+                    source: spec,
+                    error,
+                });
+                None
+            }
+        }
+    };
+    let accept_next = |next: TrackDecoder, rate: u32| -> Option<TrackDecoder> {
+        if !split_on_rate_change || next.src_sample_rate == rate {
+            return Some(next);
+        }
+        let _ = notification_tx.send(DecoderNotification::NextTrackRateChanged {
+            source: next.source_spec.clone(),
+            format: next.format_info.clone(),
+        });
+        None
+    };
+    let mut sink = SampleSink {
+        producer,
+        cancel: Arc::clone(&cancel),
+        drain_state: Arc::clone(&drain_state),
+        stretch: TimeStretch::new(target_sample_rate, target_channels, playback_rate),
+        dsp: DspChain::new(target_sample_rate, target_channels, equalizer),
+    };
+    let take_next = || next_source.lock().ok().and_then(|mut slot| slot.take());
+    // Waits while a gapless boundary is still queued, so the runtime sees the
+    // tracks change in the order they were played.
+    let take_skip = || {
+        if drain_state.has_pending_boundary() {
+            return None;
+        }
+        skip.lock().ok().and_then(|mut slot| slot.take())
+    };
+    let open_skip = |request: SkipRequest, rate: u32| -> Option<TrackDecoder> {
+        let opened = TrackDecoder::open(
+            source_factory.as_ref(),
+            request.source.clone(),
+            request.start_ms,
+            options,
+        )
+        .ok()
+        .filter(|next| !split_on_rate_change || next.src_sample_rate == rate);
+        if opened.is_none() {
+            let _ = notification_tx.send(DecoderNotification::SkipFailed {
+                source: request.source,
+            });
+        }
+        opened
+    };
+
+    let mut track = opened.into_decoder(options)?;
+    report_metadata(&mut track);
+    progress.set_decoded_ms(track.position_ms());
+
+    let mut samples = Vec::new();
+    loop {
+        let mut overlapping = None;
+        let mut skipping = None;
+        loop {
+            if cancel.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+
+            if let Some(request) = take_skip() {
+                let fade_ms = request.fade_ms;
+                if let Some(mut next) = open_skip(request, track.src_sample_rate) {
+                    report_metadata(&mut next);
+                    skipping = Some((next, fade_ms));
+                    break;
+                }
+            }
+
+            let region = loop_region.get();
+            let crossfade_ms = if bit_perfect {
+                0
+            } else {
+                crossfade_ms.load(Ordering::Relaxed)
+            };
+            if crossfade_ms > 0
+                && region.is_none()
+                && let Some(remaining_ms) = track.remaining_ms()
+                && remaining_ms <= crossfade_ms
+                && let Some(next) = take_next()
+                    .and_then(&open_next)
+                    .and_then(|next| accept_next(next, track.src_sample_rate))
+            {
+                overlapping = Some((next, remaining_ms));
+                break;
+            }
+
+            samples.clear();
+            let decoded = track.decode_looped(&mut samples, region)?;
+            progress.mirror(&track.progress);
+            progress.set_decoded_ms(track.position_ms());
+            if !decoded {
+                break;
+            }
+            if let Some(gain_db) = track.take_gain_report() {
+                let _ = notification_tx.send(DecoderNotification::NormalizationGain {
+                    source: track.source_spec.clone(),
+                    gain_db,
+                });
+            }
+            sink.push(&mut samples)?;
+        }
+        if let Some((mut next, fade_ms)) = skipping {
+            // The output drops what is queued, so the outgoing track is picked
+            // up again at the playhead and fades out from there.
+            let behind = sink.discard(target_channels) * track.src_sample_rate as u64
+                / target_sample_rate as u64;
+            let playhead = track.position_frames.saturating_sub(behind);
+            if let Err(err) = track.seek_to_frame(playhead) {
+                debug!("failed to rewind {}: {err}", track.source_spec.describe());
+            }
+            let _ = notification_tx.send(DecoderNotification::Skipped {
+                source: next.source_spec.clone(),
+                duration_ms: next.duration_ms,
+                format: next.format_info.clone(),
+            });
+            let fade_frames = fade_ms * target_sample_rate as u64 / 1000;
+            crossfade(
+                &mut track,
+                &mut next,
+                fade_frames,
+                target_channels,
+                &mut sink,
+            )?;
+            track = next;
+            continue;
+        }
+        debug!("decoder finished for {}", track.source_spec.describe());
+        if cancel.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let (mut next, fade_ms) = match overlapping {
+            Some(overlapping) => overlapping,
+            None => match take_next()
+                .and_then(&open_next)
+                .and_then(|next| accept_next(next, track.src_sample_rate))
+            {
+                Some(next) => (next, 0),
+                None => break,
+            },
+        };
+        sink.flush()?;
+
+        // Only one boundary can be pending at a time; very short tracks wait
+        // here until the output has caught up with the previous one.
+        while drain_state.has_pending_boundary() {
+            if cancel.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+            thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        drain_state.mark_track_boundary();
+        let _ = notification_tx.send(DecoderNotification::NextTrackStarted {
+            source: next.source_spec.clone(),
+            duration_ms: next.duration_ms,
+            format: next.format_info.clone(),
+        });
+
+        if fade_ms > 0 {
+            let fade_frames = fade_ms * target_sample_rate as u64 / 1000;
+            crossfade(
+                &mut track,
+                &mut next,
+                fade_frames,
+                target_channels,
+                &mut sink,
+            )?;
+        }
+        track = next;
+    }
+
+    if !cancel.load(Ordering::Relaxed) {
+        sink.flush()?;
+        drain_state.mark_decoder_finished();
+    }
+
+    Ok(())
+}
+
+// Mixes the tail of `outgoing` with the head of `incoming` using an
+// equal-power curve, leaving `incoming` positioned right after the fade.
+fn crossfade(
+    outgoing: &mut TrackDecoder,
+    incoming: &mut TrackDecoder,
+    fade_frames: u64,
+    channels: usize,
+    sink: &mut SampleSink,
 ) -> Result<()> {
-    let opened = source_factory.open(&source_spec)?;
-    if start_ms > 0 && !opened.seekable {
-        return Err(AudioError::UnsupportedSeek);
+    let fade_frames = fade_frames.max(1);
+    let mut outgoing_buf = Vec::new();
+    let mut incoming_buf = Vec::new();
+    let mut mixed = Vec::new();
+    let mut outgoing_done = false;
+    let mut incoming_done = false;
+    let mut faded_frames = 0u64;
+
+    while faded_frames < fade_frames && !(outgoing_done && incoming_done) {
+        if sink.cancel.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        if !outgoing_done && outgoing_buf.len() < channels {
+            outgoing_done = !outgoing.decode_next(&mut outgoing_buf)?;
+        }
+        if !incoming_done && incoming_buf.len() < channels {
+            incoming_done = !incoming.decode_next(&mut incoming_buf)?;
+        }
+
+        let outgoing_frames = outgoing_buf.len() / channels;
+        let incoming_frames = incoming_buf.len() / channels;
+        let frames = match (outgoing_done, incoming_done) {
+            (false, false) => outgoing_frames.min(incoming_frames),
+            (true, false) => incoming_frames,
+            (false, true) => outgoing_frames,
+            (true, true) => outgoing_frames.max(incoming_frames),
+        };
+        let frames = frames.min((fade_frames - faded_frames) as usize);
+
+        mixed.clear();
+        for frame in 0..frames {
+            let progress = faded_frames as f32 / fade_frames as f32;
+            let (out_gain, in_gain) = (equal_power(1.0 - progress), equal_power(progress));
+            for channel in 0..channels {
+                let index = frame * channels + channel;
+                let out_sample = outgoing_buf.get(index).copied().unwrap_or(0.0);
+                let in_sample = incoming_buf.get(index).copied().unwrap_or(0.0);
+                mixed.push(out_sample * out_gain + in_sample * in_gain);
+            }
+            faded_frames += 1;
+        }
+
+        let consumed = frames * channels;
+        outgoing_buf.drain(..consumed.min(outgoing_buf.len()));
+        incoming_buf.drain(..consumed.min(incoming_buf.len()));
+        sink.push(&mut mixed)?;
     }
 
-    let media_stream = MediaSourceStream::new(opened.media_source, Default::default());
-    let probed = get_probe()
+    // Whatever the incoming track decoded past the fade window plays as-is.
+    sink.push(&mut incoming_buf)
+}
+
+fn probe(media_source: Box<dyn MediaSource>) -> Result<ProbeResult> {
+    let media_stream = MediaSourceStream::new(media_source, Default::default());
+    get_probe()
         .format(
             &Default::default(),
             media_stream,
@@ -105,87 +517,342 @@ fn decode_loop(
         )
         .map_err(|err| AudioError::DecodeFailed {
             reason: err.to_string(),
-        })?;
+        })
+}
 
-    let mut format = probed.format;
-    let track = format
+fn default_track(format: &dyn FormatReader) -> Result<&Track> {
+    format
         .default_track()
         .ok_or_else(|| AudioError::DecodeFailed {
             reason: "missing default track".into(),
-        })?;
+        })
+}
 
-    let track_id = track.id;
-    let codec_params = &track.codec_params;
-    let src_sample_rate = codec_params
-        .sample_rate
-        .ok_or_else(|| AudioError::DecodeFailed {
-            reason: "unknown source sample rate".into(),
-        })? as u32;
-    let src_channels = codec_params
-        .channels
-        .ok_or_else(|| AudioError::DecodeFailed {
-            reason: "unknown channel layout".into(),
-        })?
-        .count();
+struct SampleSink {
+    producer: RingProducer,
+    cancel: Arc<AtomicBool>,
+    drain_state: Arc<PlaybackDrainState>,
+    stretch: TimeStretch,
+    dsp: DspChain,
+}
 
-    if let (Some(frames), Some(sample_rate)) = (codec_params.n_frames, codec_params.sample_rate) {
-        let duration_ms = frames.saturating_mul(1000) / sample_rate as u64;
-        let _ = notification_tx.send(DecoderNotification::Duration(duration_ms));
+impl SampleSink {
+    fn push(&mut self, samples: &mut [Sample]) -> Result<()> {
+        let samples = self.stretch.process(samples);
+        self.dsp.process(samples);
+        push_samples(&mut self.producer, samples, &self.cancel, &self.drain_state)
     }
 
-    let mut decoder = get_codecs()
-        .make(codec_params, &DecoderOptions::default())
-        .map_err(|err| AudioError::DecodeFailed {
-            reason: err.to_string(),
-        })?;
+    // Drops everything queued but not yet played along with what the time
+    // stretcher holds, and returns how many frames of the track that covered
+    // at the output rate.
+    fn discard(&mut self, channels: usize) -> u64 {
+        let held = self.stretch.flush().len() / channels;
+        let queued = self.drain_state.discard_queued() / channels as u64;
+        held as u64 + (queued as f64 * self.stretch.rate() as f64) as u64
+    }
+
+    // Pushes whatever the time stretcher still holds so it lands before a
+    // track boundary or the end of playback.
+    fn flush(&mut self) -> Result<()> {
+        let samples = self.stretch.flush();
+        if samples.is_empty() {
+            return Ok(());
+        }
+        self.dsp.process(samples);
+        push_samples(&mut self.producer, samples, &self.cancel, &self.drain_state)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TrackOptions {
+    sample_rate: u32,
+    channels: usize,
+    quality: ResampleQualityPreset,
+    channel_mix: ChannelMixMode,
+    normalization: NormalizationMode,
+    target_lufs: f32,
+}
+
+struct TrackDecoder {
+    source_spec: SourceSpec,
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    time_base: Option<TimeBase>,
+    src_sample_rate: u32,
+    position_frames: u64,
+    // Frames still to drop after a seek landed before the requested frame.
+    skip_frames: u64,
+    duration_ms: Option<u64>,
+    format_info: TrackFormat,
+    // Taken once it has been reported.
+    metadata: Option<TrackMetadata>,
+    resampler: Option<ResamplerPipeline>,
+    mixer: ChannelMixer,
+    normalizer: LoudnessNormalizer,
+    reported_gain_db: Option<f32>,
+    progress: Arc<StreamProgress>,
+}
+
+// A source that has been opened and probed but not yet set up for the output,
+// whose format may still depend on it.
+struct OpenedTrack {
+    source_spec: SourceSpec,
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    time_base: Option<TimeBase>,
+    src_sample_rate: u32,
+    src_layout: Channels,
+    duration_ms: Option<u64>,
+    format_info: TrackFormat,
+    metadata: TrackMetadata,
+    replay_gain: ReplayGainTags,
+    progress: Arc<StreamProgress>,
+    start_ms: u64,
+}
+
+impl OpenedTrack {
+    fn open(
+        source_factory: &dyn SourceFactory,
+        source_spec: SourceSpec,
+        start_ms: u64,
+    ) -> Result<Self> {
+        let opened = source_factory.open(&source_spec)?;
+        if start_ms > 0 && !opened.seekable {
+            return Err(AudioError::UnsupportedSeek);
+        }
+
+        let byte_len = opened.media_source.byte_len();
+        let progress = Arc::new(StreamProgress::default());
+        progress
+            .total_bytes
+            .store(byte_len.unwrap_or(0), Ordering::Relaxed);
+        let media_source = ProgressSource {
+            inner: opened.media_source,
+            position: 0,
+            progress: Arc::clone(&progress),
+        };
+        let mut probed = probe(Box::new(media_source))?;
+
+        let mut format = probed.format;
+        let (replay_gain, metadata) = {
+            let probed_metadata = probed.metadata.get();
+            let format_metadata = format.metadata();
+            let revisions: Vec<&MetadataRevision> = probed_metadata
+                .as_ref()
+                .and_then(|metadata| metadata.current())
+                .into_iter()
+                .chain(format_metadata.current())
+                .collect();
+            (
+                ReplayGainTags::from_tags(revisions.iter().flat_map(|revision| revision.tags())),
+                read_metadata(&revisions),
+            )
+        };
+        let track = default_track(format.as_ref())?;
+
+        let track_id = track.id;
+        let codec_params = &complete_codec_params(&track.codec_params);
+        let src_sample_rate = codec_params
+            .sample_rate
+            .ok_or_else(|| AudioError::DecodeFailed {
+                reason: "unknown source sample rate".into(),
+            })? as u32;
+        let src_layout = codec_params
+            .channels
+            .ok_or_else(|| AudioError::DecodeFailed {
+                reason: "unknown channel layout".into(),
+            })?;
+
+        let duration_ms = codec_params
+            .n_frames
+            .map(|frames| frames.saturating_mul(1000) / src_sample_rate as u64);
+
+        let format_info = describe_format(codec_params, byte_len, duration_ms);
+        let decoder = codec_registry()
+            .make(codec_params, &DecoderOptions::default())
+            .map_err(|err| AudioError::DecodeFailed {
+                reason: err.to_string(),
+            })?;
+
+        Ok(Self {
+            source_spec,
+            time_base: codec_params.time_base,
+            format,
+            decoder,
+            track_id,
+            src_sample_rate,
+            src_layout,
+            duration_ms,
+            format_info,
+            metadata,
+            replay_gain,
+            progress,
+            start_ms,
+        })
+    }
+
+    fn into_decoder(self, options: TrackOptions) -> Result<TrackDecoder> {
+        let src_channels = self.src_layout.count();
+        let resampler = if self.src_sample_rate != options.sample_rate {
+            Some(ResamplerPipeline::new(
+                self.src_sample_rate,
+                options.sample_rate,
+                src_channels,
+                options.quality,
+            )?)
+        } else {
+            None
+        };
+
+        let mut track = TrackDecoder {
+            source_spec: self.source_spec,
+            format: self.format,
+            decoder: self.decoder,
+            track_id: self.track_id,
+            time_base: self.time_base,
+            src_sample_rate: self.src_sample_rate,
+            position_frames: 0,
+            skip_frames: 0,
+            duration_ms: self.duration_ms,
+            format_info: self.format_info,
+            metadata: (!self.metadata.is_empty()).then_some(self.metadata),
+            resampler,
+            mixer: ChannelMixer::new(
+                self.src_layout,
+                src_channels,
+                options.channels,
+                options.channel_mix,
+            ),
+            normalizer: LoudnessNormalizer::new(
+                options.normalization,
+                options.target_lufs,
+                self.replay_gain,
+                options.sample_rate,
+                options.channels,
+            ),
+            reported_gain_db: None,
+            progress: self.progress,
+        };
+        if self.start_ms > 0 {
+            track.seek_to_frame(track.ms_to_frames(self.start_ms))?;
+        }
+        Ok(track)
+    }
+}
 
-    if start_ms > 0 {
-        let seconds = start_ms / 1000;
-        let frac = (start_ms % 1000) as f64 / 1000.0;
-        format
+impl TrackDecoder {
+    fn open(
+        source_factory: &dyn SourceFactory,
+        source_spec: SourceSpec,
+        start_ms: u64,
+        options: TrackOptions,
+    ) -> Result<Self> {
+        OpenedTrack::open(source_factory, source_spec, start_ms)?.into_decoder(options)
+    }
+
+    fn ms_to_frames(&self, ms: u64) -> u64 {
+        ms * self.src_sample_rate as u64 / 1000
+    }
+
+    // Seeks so the next decoded frame is exactly `frame`. Accurate seeks may
+    // land a little early; the difference is skipped while decoding.
+    fn seek_to_frame(&mut self, frame: u64) -> Result<()> {
+        let sample_rate = self.src_sample_rate as u64;
+        let seeked = self
+            .format
             .seek(
                 SeekMode::Accurate,
                 SeekTo::Time {
-                    time: Time::new(seconds, frac),
-                    track_id: Some(track_id),
+                    time: Time::new(
+                        frame / sample_rate,
+                        (frame % sample_rate) as f64 / sample_rate as f64,
+                    ),
+                    track_id: Some(self.track_id),
                 },
             )
             .map_err(|err| AudioError::DecodeFailed {
                 reason: format!("seek failed: {err}"),
             })?;
+        self.decoder.reset();
+
+        let to_frames = |ts: u64| match self.time_base {
+            Some(base) => ts * base.numer as u64 * sample_rate / base.denom as u64,
+            None => ts,
+        };
+        let landed = to_frames(seeked.actual_ts);
+        self.skip_frames = frame.saturating_sub(landed);
+        self.position_frames = frame.max(landed);
+        Ok(())
     }
 
-    let mut resampler = if src_sample_rate != target_sample_rate {
-        Some(ResamplerPipeline::new(
-            src_sample_rate,
-            target_sample_rate,
-            src_channels,
-            quality,
-        )?)
-    } else {
+    fn position_ms(&self) -> u64 {
+        self.position_frames * 1000 / self.src_sample_rate as u64
+    }
+
+    fn remaining_ms(&self) -> Option<u64> {
+        let position_ms = self.position_ms();
+        self.duration_ms
+            .map(|duration_ms| duration_ms.saturating_sub(position_ms))
+    }
+
+    fn take_gain_report(&mut self) -> Option<f32> {
+        if !self.normalizer.is_enabled() {
+            return None;
+        }
+        let gain_db = self.normalizer.gain_db();
+        let changed = self
+            .reported_gain_db
+            .is_none_or(|reported| (reported - gain_db).abs() >= GAIN_REPORT_STEP_DB);
+        if changed {
+            self.reported_gain_db = Some(gain_db);
+            return Some(gain_db);
+        }
         None
-    };
+    }
 
-    let mut channel_scratch = Vec::new();
+    // Decodes one packet and appends it to `out` at the output rate and
+    // channel count. Returns `false` once the source is exhausted.
+    fn decode_next(&mut self, out: &mut Vec<Sample>) -> Result<bool> {
+        self.decode_until(out, None)
+    }
 
-    loop {
-        if cancel.load(Ordering::Relaxed) {
-            break;
+    // Like `decode_next`, but whenever the loop end is reached the packet is
+    // cut at that exact frame and decoding rewinds to the loop start. A loop
+    // end past the end of the track wraps at the end of the track.
+    fn decode_looped(&mut self, out: &mut Vec<Sample>, region: Option<(u64, u64)>) -> Result<bool> {
+        let Some((start_ms, end_ms)) = region else {
+            return self.decode_next(out);
+        };
+        let (start, end) = (self.ms_to_frames(start_ms), self.ms_to_frames(end_ms));
+        if start >= end || self.position_frames >= end {
+            return self.decode_next(out);
         }
 
-        let packet = match format.next_packet() {
+        let decoded = self.decode_until(out, Some(end))?;
+        if self.position_frames >= end || (!decoded && self.position_frames > start) {
+            self.seek_to_frame(start)?;
+            return Ok(true);
+        }
+        Ok(decoded)
+    }
+
+    fn decode_until(&mut self, out: &mut Vec<Sample>, end_frame: Option<u64>) -> Result<bool> {
+        let packet = match self.format.next_packet() {
             Ok(packet) => packet,
             Err(symphonia::core::errors::Error::IoError(io_err))
                 if io_err.kind() == std::io::ErrorKind::UnexpectedEof =>
             {
-                break;
+                return Ok(false);
             }
             Err(symphonia::core::errors::Error::IoError(io_err))
                 if io_err.kind() == std::io::ErrorKind::PermissionDenied =>
             {
                 return Err(AudioError::HttpStatus {
                     code: 403,
-                    url: source_spec.describe(),
+                    url: self.source_spec.describe(),
                 });
             }
             Err(err) => {
@@ -195,9 +862,9 @@ fn decode_loop(
             }
         };
 
-        let decoded = match decoder.decode(&packet) {
+        let decoded = match self.decoder.decode(&packet) {
             Ok(decoded) => decoded,
-            Err(symphonia::core::errors::Error::DecodeError(_)) => continue,
+            Err(symphonia::core::errors::Error::DecodeError(_)) => return Ok(true),
             Err(err) => {
                 return Err(AudioError::DecodeFailed {
                     reason: err.to_string(),
@@ -205,68 +872,66 @@ fn decode_loop(
             }
         };
 
-        let mut sample_buf = SampleBuffer::<Sample>::new(decoded.frames() as u64, *decoded.spec());
+        let frames = decoded.frames() as u64;
+        let channels = decoded.spec().channels.count();
+        let skipped = self.skip_frames.min(frames);
+        self.skip_frames -= skipped;
+        let mut kept = frames - skipped;
+        if let Some(end_frame) = end_frame {
+            kept = kept.min(end_frame.saturating_sub(self.position_frames));
+        }
+        self.position_frames += kept;
+        let mut sample_buf = SampleBuffer::<Sample>::new(frames, *decoded.spec());
         sample_buf.copy_interleaved_ref(decoded);
 
-        let interleaved = sample_buf.samples();
-        let output_samples = if let Some(resampler) = &mut resampler {
-            resampler.process(interleaved, &mut producer, &cancel, &drain_state)?;
-            None
+        let interleaved = &sample_buf.samples()
+            [skipped as usize * channels..(skipped + kept) as usize * channels];
+        let resampled = if let Some(resampler) = &mut self.resampler {
+            resampler.process(interleaved)?
         } else {
-            Some(interleaved)
+            interleaved
         };
 
-        if let Some(samples) = output_samples {
-            let channels_adjusted =
-                convert_channels(samples, src_channels, target_channels, &mut channel_scratch);
-            push_samples(&mut producer, channels_adjusted, &cancel, &drain_state)?;
-        }
-    }
-
-    debug!("decoder finished for {}", source_spec.describe());
-    if !cancel.load(Ordering::Relaxed) {
-        drain_state.mark_decoder_finished();
+        let start = out.len();
+        out.extend_from_slice(self.mixer.process(resampled));
+        self.normalizer.process(&mut out[start..]);
+        Ok(true)
     }
+}
 
-    Ok(())
+// Records the furthest byte the demuxer has pulled from the source.
+struct ProgressSource {
+    inner: Box<dyn MediaSource>,
+    position: u64,
+    progress: Arc<StreamProgress>,
 }
 
-fn convert_channels<'a>(
-    samples: &'a [Sample],
-    input_channels: usize,
-    output_channels: usize,
-    scratch: &'a mut Vec<Sample>,
-) -> &'a [Sample] {
-    if input_channels == output_channels || input_channels == 0 || output_channels == 0 {
-        return samples;
+impl Read for ProgressSource {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.position += read as u64;
+        self.progress
+            .read_bytes
+            .fetch_max(self.position, Ordering::Relaxed);
+        Ok(read)
     }
+}
 
-    scratch.clear();
-    let frames = samples.len() / input_channels;
-    scratch.reserve(frames * output_channels);
+impl Seek for ProgressSource {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.position = self.inner.seek(pos)?;
+        Ok(self.position)
+    }
+}
 
-    match (input_channels, output_channels) {
-        (1, 2) => {
-            for &sample in samples {
-                scratch.push(sample);
-                scratch.push(sample);
-            }
-        }
-        (2, 1) => {
-            for frame in samples.chunks_exact(2) {
-                scratch.push((frame[0] + frame[1]) * 0.5);
-            }
-        }
-        _ => {
-            for frame in samples.chunks_exact(input_channels) {
-                for channel in 0..output_channels {
-                    scratch.push(*frame.get(channel).unwrap_or(&0.0));
-                }
-            }
-        }
+impl MediaSource for ProgressSource {
+    fn is_seekable(&self) -> bool {
+        self.inner.is_seekable()
     }
 
-    scratch.as_slice()
+    fn byte_len(&self) -> Option<u64> {
+        self.inner.byte_len()
+    }
 }
 
 fn push_samples(
@@ -297,6 +962,7 @@ struct ResamplerPipeline {
     input: Vec<Sample>,
     input_start: usize,
     output: Vec<Sample>,
+    produced: Vec<Sample>,
 }
 
 impl ResamplerPipeline {
@@ -333,17 +999,13 @@ impl ResamplerPipeline {
             input: Vec::with_capacity(16 * 1024),
             input_start: 0,
             output: Vec::with_capacity(16 * 1024),
+            produced: Vec::with_capacity(16 * 1024),
         })
     }
 
-    fn process(
-        &mut self,
-        samples: &[Sample],
-        producer: &mut RingProducer,
-        cancel: &Arc<AtomicBool>,
-        drain_state: &Arc<PlaybackDrainState>,
-    ) -> Result<()> {
+    fn process(&mut self, samples: &[Sample]) -> Result<&[Sample]> {
         self.input.extend_from_slice(samples);
+        self.produced.clear();
 
         let needed_frames = self.resampler.input_frames_next();
 
@@ -378,12 +1040,8 @@ impl ResamplerPipeline {
                 })?;
 
             let produced_samples = produced_frames * self.channels;
-            push_samples(
-                producer,
-                &self.output[..produced_samples],
-                cancel,
-                drain_state,
-            )?;
+            self.produced
+                .extend_from_slice(&self.output[..produced_samples]);
 
             self.input_start += consumed_frames * self.channels;
             if self.input_start > self.input.len() / 2 {
@@ -392,12 +1050,8 @@ impl ResamplerPipeline {
                 self.input.truncate(remaining);
                 self.input_start = 0;
             }
-
-            if cancel.load(Ordering::Relaxed) {
-                break;
-            }
         }
 
-        Ok(())
+        Ok(&self.produced)
     }
 }
@@ -0,0 +1,98 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use tracing::warn;
+
+use crate::backend::{AudioDevice, BackendNotification, OutputBackend};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(1_000);
+const CANCEL_CHECK: Duration = Duration::from_millis(50);
+
+// Polls the backend's device list and reports whenever it or the system
+// default changes. The first poll always reports, so the runtime starts out
+// with a complete picture.
+pub(crate) struct DeviceWatcher {
+    cancel: Arc<AtomicBool>,
+    refresh: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl DeviceWatcher {
+    pub fn spawn(backend: Box<dyn OutputBackend>, event_tx: Sender<BackendNotification>) -> Self {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let refresh = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let cancel = Arc::clone(&cancel);
+            let refresh = Arc::clone(&refresh);
+            thread::Builder::new()
+                .name("ame-audio-device-watcher".into())
+                .spawn(move || watch(backend.as_ref(), &event_tx, &cancel, &refresh))
+                .inspect_err(|err| warn!("failed to start device watcher: {err}"))
+                .ok()
+        };
+        Self {
+            cancel,
+            refresh,
+            thread,
+        }
+    }
+
+    // Polls right away and reports even an unchanged list. A device that
+    // dropped out and came back between two polls looks unchanged otherwise.
+    pub fn refresh(&self) {
+        self.refresh.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for DeviceWatcher {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn watch(
+    backend: &dyn OutputBackend,
+    event_tx: &Sender<BackendNotification>,
+    cancel: &AtomicBool,
+    refresh: &AtomicBool,
+) {
+    let mut reported: Option<(Vec<AudioDevice>, Option<String>)> = None;
+    while !cancel.load(Ordering::Relaxed) {
+        if refresh.swap(false, Ordering::Relaxed) {
+            reported = None;
+        }
+        match backend.list_devices() {
+            Ok(devices) => {
+                let default_device = backend.default_device().ok().map(|device| device.name);
+                let current = (devices, default_device);
+                if reported.as_ref() != Some(&current) {
+                    let (devices, default_device) = current.clone();
+                    let notification = BackendNotification::DevicesChanged {
+                        backend: backend.kind(),
+                        devices,
+                        default_device,
+                    };
+                    if event_tx.send(notification).is_err() {
+                        return;
+                    }
+                    reported = Some(current);
+                }
+            }
+            Err(err) => warn!("failed to list output devices: {err}"),
+        }
+
+        let next_poll = Instant::now() + POLL_INTERVAL;
+        while !cancel.load(Ordering::Relaxed)
+            && !refresh.load(Ordering::Relaxed)
+            && Instant::now() < next_poll
+        {
+            thread::sleep(CANCEL_CHECK);
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use std::f64::consts::PI;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::Sample;
+use crate::equalizer::EqualizerSettings;
+
+// A processing stage between decoding and the output ring. Stages always see
+// interleaved frames at the output sample rate.
+pub trait DspStage: Send {
+    fn process(&mut self, samples: &mut [Sample], channels: usize);
+
+    fn reset(&mut self) {}
+}
+
+pub(crate) struct DspChain {
+    channels: usize,
+    stages: Vec<Box<dyn DspStage>>,
+}
+
+impl DspChain {
+    pub fn new(sample_rate: u32, channels: usize, equalizer: Arc<EqualizerControl>) -> Self {
+        Self {
+            channels: channels.max(1),
+            stages: vec![Box::new(Equalizer::new(equalizer, sample_rate))],
+        }
+    }
+
+    pub fn process(&mut self, samples: &mut [Sample]) {
+        for stage in &mut self.stages {
+            stage.process(samples, self.channels);
+        }
+    }
+}
+
+// Shared between the runtime and the decoder thread so EQ changes apply to the
+// running stream without reopening the source.
+pub(crate) struct EqualizerControl {
+    settings: Mutex<EqualizerSettings>,
+    revision: AtomicU64,
+}
+
+impl EqualizerControl {
+    pub fn new(settings: EqualizerSettings) -> Self {
+        Self {
+            settings: Mutex::new(settings.clamped()),
+            revision: AtomicU64::new(0),
+        }
+    }
+
+    pub fn set(&self, settings: EqualizerSettings) {
+        if let Ok(mut current) = self.settings.lock() {
+            *current = settings.clamped();
+        }
+        self.revision.fetch_add(1, Ordering::Release);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.settings.lock().is_ok_and(|settings| settings.enabled)
+    }
+
+    fn revision(&self) -> u64 {
+        self.revision.load(Ordering::Acquire)
+    }
+
+    fn settings(&self) -> EqualizerSettings {
+        self.settings
+            .lock()
+            .map(|settings| settings.clone())
+            .unwrap_or_default()
+    }
+}
+
+pub(crate) struct Equalizer {
+    control: Arc<EqualizerControl>,
+    revision: Option<u64>,
+    sample_rate: u32,
+    enabled: bool,
+    preamp: f32,
+    filters: Vec<Biquad>,
+    states: Vec<BiquadState>,
+}
+
+impl Equalizer {
+    pub fn new(control: Arc<EqualizerControl>, sample_rate: u32) -> Self {
+        Self {
+            control,
+            revision: None,
+            sample_rate: sample_rate.max(1),
+            enabled: false,
+            preamp: 1.0,
+            filters: Vec::new(),
+            states: Vec::new(),
+        }
+    }
+
+    fn sync(&mut self) {
+        let revision = self.control.revision();
+        if self.revision == Some(revision) {
+            return;
+        }
+        self.revision = Some(revision);
+
+        let settings = self.control.settings();
+        if settings.enabled && !self.enabled {
+            self.reset();
+        }
+        self.enabled = settings.enabled;
+        self.preamp = 10f32.powf(settings.preamp_db / 20.0);
+
+        // Filter state is kept across coefficient changes so dragging a
+        // slider does not click.
+        let rate = self.sample_rate as f64;
+        self.filters = settings
+            .bands
+            .iter()
+            .filter(|band| band.gain_db.abs() > 0.01)
+            .map(|band| {
+                let frequency = (band.frequency_hz as f64).min(rate * 0.45);
+                Biquad::peaking(rate, frequency, band.q as f64, band.gain_db as f64)
+            })
+            .collect();
+    }
+}
+
+impl DspStage for Equalizer {
+    fn process(&mut self, samples: &mut [Sample], channels: usize) {
+        self.sync();
+        if !self.enabled {
+            return;
+        }
+
+        let state_len = self.filters.len() * channels;
+        if self.states.len() != state_len {
+            self.states.resize(state_len, BiquadState::default());
+        }
+
+        for frame in samples.chunks_exact_mut(channels) {
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                let mut value = (*sample * self.preamp) as f64;
+                for (index, filter) in self.filters.iter().enumerate() {
+                    value = self.states[index * channels + channel].process(filter, value);
+                }
+                *sample = value as Sample;
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.states.fill(BiquadState::default());
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Biquad {
+    pub b0: f64,
+    pub b1: f64,
+    pub b2: f64,
+    pub a1: f64,
+    pub a2: f64,
+}
+
+impl Biquad {
+    // RBJ audio EQ cookbook peaking filter.
+    pub fn peaking(sample_rate: f64, frequency: f64, q: f64, gain_db: f64) -> Self {
+        let a = 10f64.powf(gain_db / 40.0);
+        let w0 = 2.0 * PI * frequency / sample_rate;
+        let alpha = w0.sin() / (2.0 * q);
+        let cos_w0 = w0.cos();
+        let a0 = 1.0 + alpha / a;
+        Self {
+            b0: (1.0 + alpha * a) / a0,
+            b1: -2.0 * cos_w0 / a0,
+            b2: (1.0 - alpha * a) / a0,
+            a1: -2.0 * cos_w0 / a0,
+            a2: (1.0 - alpha / a) / a0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct BiquadState {
+    x1: f64,
+    x2: f64,
+    y1: f64,
+    y2: f64,
+}
+
+impl BiquadState {
+    pub fn process(&mut self, filter: &Biquad, x: f64) -> f64 {
+        let y = filter.b0 * x + filter.b1 * self.x1 + filter.b2 * self.x2
+            - filter.a1 * self.y1
+            - filter.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{DspStage, Equalizer, EqualizerControl};
+    use crate::equalizer::{EqualizerPreset, EqualizerSettings};
+
+    fn sine(frequency: f32, sample_rate: u32, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|index| {
+                let t = index as f32 / sample_rate as f32;
+                let value = 0.25 * (2.0 * std::f32::consts::PI * frequency * t).sin();
+                [value, value]
+            })
+            .collect()
+    }
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples
+            .iter()
+            .fold(0.0, |peak, sample| peak.max(sample.abs()))
+    }
+
+    #[test]
+    fn disabled_equalizer_is_transparent() {
+        let control = Arc::new(EqualizerControl::new(EqualizerSettings::default()));
+        let mut equalizer = Equalizer::new(control, 48_000);
+        let input = sine(1_000.0, 48_000, 4_800);
+        let mut output = input.clone();
+        equalizer.process(&mut output, 2);
+        assert_eq!(input, output);
+    }
+
+    #[test]
+    fn band_gain_applies_live() {
+        let control = Arc::new(EqualizerControl::new(EqualizerSettings::default()));
+        let mut equalizer = Equalizer::new(Arc::clone(&control), 48_000);
+
+        let mut settings = EqualizerSettings::from_preset(EqualizerPreset::Flat);
+        settings.enabled = true;
+        settings.set_band_gain(5, 6.0);
+        control.set(settings);
+
+        let mut output = sine(1_000.0, 48_000, 48_000);
+        equalizer.process(&mut output, 2);
+        // Skip the filter's settling time before measuring the steady state.
+        let gain = peak(&output[48_000..]) / 0.25;
+        let expected = 10f32.powf(6.0 / 20.0);
+        assert!((gain - expected).abs() < 0.05, "gain = {gain}");
+    }
+}
@@ -0,0 +1,539 @@
+// Tiny media files generated in memory so codec tests do not need binary
+// fixtures or an encoder. Lossy formats carry silent frames; PCM and ALAC carry
+// a 440 Hz sine at half scale.
+
+const SINE_HZ: f64 = 440.0;
+const SINE_AMPLITUDE: f64 = 0.5;
+
+fn sine(sample_rate: u32, bits: u32, frames: usize) -> Vec<i32> {
+    let scale = SINE_AMPLITUDE * ((1i64 << (bits - 1)) - 1) as f64;
+    (0..frames)
+        .map(|frame| {
+            let t = frame as f64 / sample_rate as f64;
+            (scale * (2.0 * std::f64::consts::PI * SINE_HZ * t).sin()).round() as i32
+        })
+        .collect()
+}
+
+pub fn wav(sample_rate: u32, channels: u16, bits: u16, frames: usize) -> Vec<u8> {
+    wav_with_info(sample_rate, channels, bits, frames, &[])
+}
+
+// Same as `wav` with a LIST/INFO chunk of NUL-terminated strings before the
+// audio, the way most taggers write it.
+pub fn wav_with_info(
+    sample_rate: u32,
+    channels: u16,
+    bits: u16,
+    frames: usize,
+    info: &[(&[u8; 4], &str)],
+) -> Vec<u8> {
+    let mut list = Vec::new();
+    if !info.is_empty() {
+        list.extend_from_slice(b"INFO");
+        for (id, value) in info {
+            let mut value = value.as_bytes().to_vec();
+            value.push(0);
+            list.extend_from_slice(*id);
+            list.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            if value.len() % 2 == 1 {
+                value.push(0);
+            }
+            list.extend_from_slice(&value);
+        }
+    }
+
+    let bytes_per_sample = usize::from(bits / 8);
+    let mut data = Vec::with_capacity(frames * usize::from(channels) * bytes_per_sample);
+    for sample in sine(sample_rate, u32::from(bits), frames) {
+        for _ in 0..channels {
+            data.extend_from_slice(&sample.to_le_bytes()[..bytes_per_sample]);
+        }
+    }
+
+    let block_align = channels * bits / 8;
+    let mut out = Vec::new();
+    let list_len = if list.is_empty() { 0 } else { 8 + list.len() };
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&((36 + list_len + data.len()) as u32).to_le_bytes());
+    out.extend_from_slice(b"WAVEfmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&channels.to_le_bytes());
+    out.extend_from_slice(&sample_rate.to_le_bytes());
+    out.extend_from_slice(&(sample_rate * u32::from(block_align)).to_le_bytes());
+    out.extend_from_slice(&block_align.to_le_bytes());
+    out.extend_from_slice(&bits.to_le_bytes());
+    if !list.is_empty() {
+        out.extend_from_slice(b"LIST");
+        out.extend_from_slice(&(list.len() as u32).to_le_bytes());
+        out.extend_from_slice(&list);
+    }
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(&data);
+    out
+}
+
+// Mono ALAC using uncompressed escape frames, which every decoder must accept.
+pub fn alac_m4a(sample_rate: u32, bits: u8, frames: usize) -> Vec<u8> {
+    const FRAME_LENGTH: usize = 4096;
+
+    let samples = sine(sample_rate, u32::from(bits), frames);
+    let packets = samples
+        .chunks(FRAME_LENGTH)
+        .map(|chunk| {
+            let mut writer = BitWriter::msb_first();
+            writer.write(0, 3); // single channel element
+            writer.write(0, 4);
+            writer.write(0, 12);
+            writer.write(1, 1); // partial frame
+            writer.write(0, 2);
+            writer.write(1, 1); // uncompressed
+            writer.write(chunk.len() as u32, 32);
+            for sample in chunk {
+                writer.write(
+                    *sample as u32 & (u32::MAX >> (32 - u32::from(bits))),
+                    bits.into(),
+                );
+            }
+            writer.write(7, 3); // end
+            writer.finish()
+        })
+        .collect::<Vec<_>>();
+
+    let mut cookie = Vec::new();
+    cookie.extend_from_slice(&(FRAME_LENGTH as u32).to_be_bytes());
+    cookie.extend_from_slice(&[0, bits, 40, 10, 14, 1]);
+    cookie.extend_from_slice(&255u16.to_be_bytes());
+    cookie.extend_from_slice(&0u32.to_be_bytes());
+    cookie.extend_from_slice(&0u32.to_be_bytes());
+    cookie.extend_from_slice(&sample_rate.to_be_bytes());
+
+    let entry = audio_sample_entry(
+        b"alac",
+        1,
+        bits.into(),
+        sample_rate,
+        &full_atom(b"alac", 0, &cookie),
+    );
+    mp4(
+        entry,
+        sample_rate,
+        &packets,
+        FRAME_LENGTH as u32,
+        frames as u32,
+    )
+}
+
+// Mono AAC-LC where every raw frame is a silent single channel element.
+pub fn aac_m4a(sample_rate: u32, packet_count: usize) -> Vec<u8> {
+    const FRAME_LENGTH: u32 = 1024;
+    const SAMPLE_RATES: [u32; 12] = [
+        96_000, 88_200, 64_000, 48_000, 44_100, 32_000, 24_000, 22_050, 16_000, 12_000, 11_025,
+        8_000,
+    ];
+    let rate_index = SAMPLE_RATES
+        .iter()
+        .position(|rate| *rate == sample_rate)
+        .expect("standard AAC sample rate") as u32;
+
+    let mut packet = BitWriter::msb_first();
+    packet.write(0, 3); // single channel element
+    packet.write(0, 4);
+    packet.write(100, 8); // global gain
+    packet.write(0, 1);
+    packet.write(0, 2); // only long window
+    packet.write(0, 1);
+    packet.write(0, 6); // max_sfb
+    packet.write(0, 1);
+    packet.write(0, 3); // no pulse, TNS or gain control data
+    packet.write(7, 3); // end
+    let packet = packet.finish();
+    let packets = vec![packet; packet_count];
+
+    let mut config = BitWriter::msb_first();
+    config.write(2, 5); // AAC LC
+    config.write(rate_index, 4);
+    config.write(1, 4);
+    config.write(0, 3);
+    let config = config.finish();
+
+    let mut decoder_config = vec![0x40, 0x15, 0, 0, 0];
+    decoder_config.extend_from_slice(&0u32.to_be_bytes());
+    decoder_config.extend_from_slice(&0u32.to_be_bytes());
+    decoder_config.extend(descriptor(0x05, &config));
+    let mut es = vec![0, 1, 0];
+    es.extend(descriptor(0x04, &decoder_config));
+    es.extend(descriptor(0x06, &[0x02]));
+    let esds = full_atom(b"esds", 0, &descriptor(0x03, &es));
+
+    let entry = audio_sample_entry(b"mp4a", 1, 16, sample_rate, &esds);
+    let frames = FRAME_LENGTH * packet_count as u32;
+    mp4(entry, sample_rate, &packets, FRAME_LENGTH, frames)
+}
+
+// Mono Vorbis with a minimal setup header: one codebook, one unused floor and
+// short blocks only, so every audio packet is a single zero byte.
+pub fn ogg_vorbis(sample_rate: u32, packet_count: usize) -> Vec<u8> {
+    const SHORT_BLOCK: u64 = 256;
+
+    let mut ident = vec![1];
+    ident.extend_from_slice(b"vorbis");
+    ident.extend_from_slice(&0u32.to_le_bytes());
+    ident.push(1);
+    ident.extend_from_slice(&sample_rate.to_le_bytes());
+    ident.extend_from_slice(&0i32.to_le_bytes());
+    ident.extend_from_slice(&0i32.to_le_bytes());
+    ident.extend_from_slice(&0i32.to_le_bytes());
+    ident.push(0xB8); // block sizes 256 and 2048
+    ident.push(1);
+
+    let mut comment = vec![3];
+    comment.extend_from_slice(b"vorbis");
+    comment.extend(vendor_string());
+    comment.extend_from_slice(&0u32.to_le_bytes());
+    comment.push(1);
+
+    let mut setup = BitWriter::lsb_first();
+    setup.write(0, 8); // one codebook
+    setup.write(0x564342, 24);
+    setup.write(1, 16);
+    setup.write(2, 24);
+    setup.write(0, 2); // unordered, dense
+    setup.write(0, 5);
+    setup.write(0, 5);
+    setup.write(0, 4); // no lookup table
+    setup.write(0, 6); // one time domain transform
+    setup.write(0, 16);
+    setup.write(0, 6); // one floor
+    setup.write(1, 16);
+    setup.write(0, 5);
+    setup.write(0, 2);
+    setup.write(0, 4);
+    setup.write(0, 6); // one residue
+    setup.write(0, 16);
+    setup.write(0, 24);
+    setup.write(0, 24);
+    setup.write(0, 24);
+    setup.write(0, 6);
+    setup.write(0, 8);
+    setup.write(0, 4);
+    setup.write(0, 6); // one mapping
+    setup.write(0, 16);
+    setup.write(0, 4);
+    setup.write(0, 24);
+    setup.write(0, 6); // one mode
+    setup.write(0, 1);
+    setup.write(0, 16);
+    setup.write(0, 16);
+    setup.write(0, 8);
+    setup.write(1, 1);
+    let mut setup_packet = vec![5];
+    setup_packet.extend_from_slice(b"vorbis");
+    setup_packet.extend(setup.finish());
+
+    let mut packets = vec![(ident, 0), (comment, 0), (setup_packet, 0)];
+    // Overlapping short blocks each complete half a block of output.
+    for index in 0..packet_count {
+        packets.push((vec![0], index as u64 * SHORT_BLOCK / 2));
+    }
+    ogg(&packets)
+}
+
+// Stereo Ogg Opus made of 20 ms CELT silence frames.
+pub fn ogg_opus(packet_count: usize) -> Vec<u8> {
+    const PRE_SKIP: u16 = 312;
+    const FRAME_LENGTH: u64 = 960;
+
+    let mut head = b"OpusHead".to_vec();
+    head.push(1);
+    head.push(2);
+    head.extend_from_slice(&PRE_SKIP.to_le_bytes());
+    head.extend_from_slice(&48_000u32.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes());
+    head.push(0);
+
+    let mut tags = b"OpusTags".to_vec();
+    tags.extend(vendor_string());
+    tags.extend_from_slice(&0u32.to_le_bytes());
+
+    let mut packets = vec![(head, 0), (tags, 0)];
+    for index in 0..packet_count {
+        packets.push((vec![0xF8, 0xFF, 0xFE], (index as u64 + 1) * FRAME_LENGTH));
+    }
+    ogg(&packets)
+}
+
+fn vendor_string() -> Vec<u8> {
+    let vendor = b"ame-audio fixtures";
+    let mut out = (vendor.len() as u32).to_le_bytes().to_vec();
+    out.extend_from_slice(vendor);
+    out
+}
+
+fn atom(kind: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    let mut out = ((payload.len() + 8) as u32).to_be_bytes().to_vec();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(payload);
+    out
+}
+
+fn full_atom(kind: &[u8; 4], flags: u32, payload: &[u8]) -> Vec<u8> {
+    let mut body = flags.to_be_bytes().to_vec();
+    body.extend_from_slice(payload);
+    atom(kind, &body)
+}
+
+fn descriptor(tag: u8, payload: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag, payload.len() as u8];
+    out.extend_from_slice(payload);
+    out
+}
+
+fn audio_sample_entry(
+    kind: &[u8; 4],
+    channels: u16,
+    sample_size: u16,
+    sample_rate: u32,
+    config: &[u8],
+) -> Vec<u8> {
+    let mut body = vec![0; 6];
+    body.extend_from_slice(&1u16.to_be_bytes());
+    body.extend_from_slice(&[0; 8]);
+    body.extend_from_slice(&channels.to_be_bytes());
+    body.extend_from_slice(&sample_size.to_be_bytes());
+    body.extend_from_slice(&[0; 4]);
+    // 16.16 fixed point; rates above 65535 Hz come from the codec config.
+    body.extend_from_slice(&(sample_rate.min(0xFFFF) << 16).to_be_bytes());
+    body.extend_from_slice(config);
+    atom(kind, &body)
+}
+
+fn mp4(
+    entry: Vec<u8>,
+    timescale: u32,
+    packets: &[Vec<u8>],
+    frames_per_packet: u32,
+    frames: u32,
+) -> Vec<u8> {
+    let ftyp = atom(b"ftyp", b"M4A \0\0\0\0M4A mp42isom");
+    let moov = |chunk_offset: u32| {
+        let mut mvhd = vec![0; 8];
+        mvhd.extend_from_slice(&timescale.to_be_bytes());
+        mvhd.extend_from_slice(&frames.to_be_bytes());
+        mvhd.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        mvhd.extend_from_slice(&0x0100u16.to_be_bytes());
+        mvhd.extend_from_slice(&[0; 70]);
+        mvhd.extend_from_slice(&2u32.to_be_bytes());
+
+        let mut tkhd = vec![0; 8];
+        tkhd.extend_from_slice(&1u32.to_be_bytes());
+        tkhd.extend_from_slice(&[0; 4]);
+        tkhd.extend_from_slice(&frames.to_be_bytes());
+        tkhd.extend_from_slice(&[0; 12]);
+        tkhd.extend_from_slice(&0x0100u16.to_be_bytes());
+        tkhd.extend_from_slice(&[0; 46]);
+
+        let mut mdhd = vec![0; 8];
+        mdhd.extend_from_slice(&timescale.to_be_bytes());
+        mdhd.extend_from_slice(&frames.to_be_bytes());
+        mdhd.extend_from_slice(&0x55C4u16.to_be_bytes());
+        mdhd.extend_from_slice(&[0; 2]);
+
+        let mut hdlr = vec![0; 4];
+        hdlr.extend_from_slice(b"soun");
+        hdlr.extend_from_slice(&[0; 12]);
+        hdlr.extend_from_slice(b"SoundHandler\0");
+
+        let mut stsd = 1u32.to_be_bytes().to_vec();
+        stsd.extend_from_slice(&entry);
+        let mut stts = 1u32.to_be_bytes().to_vec();
+        stts.extend_from_slice(&(packets.len() as u32).to_be_bytes());
+        stts.extend_from_slice(&frames_per_packet.to_be_bytes());
+        let mut stsc = 1u32.to_be_bytes().to_vec();
+        stsc.extend_from_slice(&1u32.to_be_bytes());
+        stsc.extend_from_slice(&(packets.len() as u32).to_be_bytes());
+        stsc.extend_from_slice(&1u32.to_be_bytes());
+        let mut stsz = 0u32.to_be_bytes().to_vec();
+        stsz.extend_from_slice(&(packets.len() as u32).to_be_bytes());
+        for packet in packets {
+            stsz.extend_from_slice(&(packet.len() as u32).to_be_bytes());
+        }
+        let mut stco = 1u32.to_be_bytes().to_vec();
+        stco.extend_from_slice(&chunk_offset.to_be_bytes());
+        let stbl = [
+            full_atom(b"stsd", 0, &stsd),
+            full_atom(b"stts", 0, &stts),
+            full_atom(b"stsc", 0, &stsc),
+            full_atom(b"stsz", 0, &stsz),
+            full_atom(b"stco", 0, &stco),
+        ]
+        .concat();
+
+        let mut dref = 1u32.to_be_bytes().to_vec();
+        dref.extend(full_atom(b"url ", 1, &[]));
+        let minf = [
+            full_atom(b"smhd", 0, &[0; 4]),
+            atom(b"dinf", &full_atom(b"dref", 0, &dref)),
+            atom(b"stbl", &stbl),
+        ]
+        .concat();
+        let mdia = [
+            full_atom(b"mdhd", 0, &mdhd),
+            full_atom(b"hdlr", 0, &hdlr),
+            atom(b"minf", &minf),
+        ]
+        .concat();
+        let trak = [full_atom(b"tkhd", 7, &tkhd), atom(b"mdia", &mdia)].concat();
+        atom(
+            b"moov",
+            &[full_atom(b"mvhd", 0, &mvhd), atom(b"trak", &trak)].concat(),
+        )
+    };
+
+    let header_len = ftyp.len() + moov(0).len() + 8;
+    [
+        ftyp,
+        moov(header_len as u32),
+        atom(b"mdat", &packets.concat()),
+    ]
+    .concat()
+}
+
+// One packet per page keeps the lacing trivial; the granule position is the
+// sample position at the end of the page.
+fn ogg(packets: &[(Vec<u8>, u64)]) -> Vec<u8> {
+    const SERIAL: u32 = 0x616D_6521;
+
+    let mut out = Vec::new();
+    for (sequence, (packet, granule)) in packets.iter().enumerate() {
+        let mut header_type = 0;
+        if sequence == 0 {
+            header_type |= 0x02;
+        }
+        if sequence + 1 == packets.len() {
+            header_type |= 0x04;
+        }
+
+        let mut lacing = vec![255u8; packet.len() / 255];
+        lacing.push((packet.len() % 255) as u8);
+
+        let mut page = b"OggS".to_vec();
+        page.push(0);
+        page.push(header_type);
+        page.extend_from_slice(&granule.to_le_bytes());
+        page.extend_from_slice(&SERIAL.to_le_bytes());
+        page.extend_from_slice(&(sequence as u32).to_le_bytes());
+        page.extend_from_slice(&0u32.to_le_bytes());
+        page.push(lacing.len() as u8);
+        page.extend_from_slice(&lacing);
+        page.extend_from_slice(packet);
+
+        let crc = ogg_crc(&page);
+        page[22..26].copy_from_slice(&crc.to_le_bytes());
+        out.extend(page);
+    }
+    out
+}
+
+fn ogg_crc(data: &[u8]) -> u32 {
+    data.iter().fold(0u32, |crc, byte| {
+        let mut crc = crc ^ (u32::from(*byte) << 24);
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04C1_1DB7
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    len: usize,
+    msb_first: bool,
+}
+
+impl BitWriter {
+    fn msb_first() -> Self {
+        Self {
+            bytes: Vec::new(),
+            len: 0,
+            msb_first: true,
+        }
+    }
+
+    fn lsb_first() -> Self {
+        Self {
+            bytes: Vec::new(),
+            len: 0,
+            msb_first: false,
+        }
+    }
+
+    fn write(&mut self, value: u32, bits: u32) {
+        for index in 0..bits {
+            let shift = if self.msb_first {
+                bits - 1 - index
+            } else {
+                index
+            };
+            if self.len.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if (value >> shift) & 1 == 1 {
+                let bit = self.len % 8;
+                let mask = if self.msb_first {
+                    0x80 >> bit
+                } else {
+                    1 << bit
+                };
+                *self.bytes.last_mut().expect("byte pushed above") |= mask;
+            }
+            self.len += 1;
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+// Serves `bytes` over HTTP on localhost without range support, holding the
+// body back for `stall` once `stall_after` bytes have been sent.
+pub fn serve_stalling(bytes: Vec<u8>, stall_after: usize, stall: std::time::Duration) -> String {
+    use std::io::{BufRead, BufReader, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/track.wav", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            let _ = reader.read_line(&mut request_line);
+            let mut header = String::new();
+            while reader.read_line(&mut header).is_ok_and(|read| read > 2) {
+                header.clear();
+            }
+
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nContent-Type: audio/wav\r\nConnection: close\r\n\r\n",
+                bytes.len()
+            );
+            let _ = stream.write_all(head.as_bytes());
+            if request_line.starts_with("GET") {
+                let _ = stream.write_all(&bytes[..stall_after]);
+                let _ = stream.flush();
+                std::thread::sleep(stall);
+                let _ = stream.write_all(&bytes[stall_after..]);
+            }
+        }
+    });
+    url
+}
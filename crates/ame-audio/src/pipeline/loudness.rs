@@ -0,0 +1,417 @@
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+
+use symphonia::core::meta::{StandardTagKey, Tag, Value};
+
+use crate::Sample;
+use crate::config::NormalizationMode;
+use crate::dsp::{Biquad, BiquadState};
+
+// ReplayGain 2.0 gains are relative to this reference loudness.
+const REPLAYGAIN_REFERENCE_LUFS: f32 = -18.0;
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+const RELATIVE_GATE_LU: f64 = -10.0;
+// Blocks above the absolute gate are binned by loudness so the relative gate
+// can be applied without keeping every block around.
+const HISTOGRAM_STEP_LU: f64 = 0.1;
+const HISTOGRAM_BINS: usize = 751;
+const MIN_GAIN_DB: f32 = -24.0;
+const MAX_GAIN_DB: f32 = 12.0;
+// The running estimate is only trusted after this many 400 ms blocks.
+const MIN_MEASURED_BLOCKS: usize = 30;
+const LIMITER_CEILING: f32 = 0.98;
+const LIMITER_RELEASE_MS: f32 = 80.0;
+const GAIN_SMOOTHING_MS: f32 = 500.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct ReplayGainTags {
+    pub track_gain_db: Option<f32>,
+    pub track_peak: Option<f32>,
+    pub album_gain_db: Option<f32>,
+    pub album_peak: Option<f32>,
+}
+
+impl ReplayGainTags {
+    pub fn from_tags<'a>(tags: impl IntoIterator<Item = &'a Tag>) -> Self {
+        let mut parsed = Self::default();
+        for tag in tags {
+            let slot = match tag.std_key {
+                Some(StandardTagKey::ReplayGainTrackGain) => &mut parsed.track_gain_db,
+                Some(StandardTagKey::ReplayGainTrackPeak) => &mut parsed.track_peak,
+                Some(StandardTagKey::ReplayGainAlbumGain) => &mut parsed.album_gain_db,
+                Some(StandardTagKey::ReplayGainAlbumPeak) => &mut parsed.album_peak,
+                _ => continue,
+            };
+            if let Some(value) = parse_tag_number(&tag.value) {
+                *slot = Some(value);
+            }
+        }
+        parsed
+    }
+
+    fn select(&self, mode: NormalizationMode) -> Option<(f32, Option<f32>)> {
+        let track = self.track_gain_db.map(|gain| (gain, self.track_peak));
+        let album = self.album_gain_db.map(|gain| (gain, self.album_peak));
+        match mode {
+            NormalizationMode::Off => None,
+            NormalizationMode::Track => track.or(album),
+            NormalizationMode::Album => album.or(track),
+        }
+    }
+}
+
+fn parse_tag_number(value: &Value) -> Option<f32> {
+    match value {
+        Value::Float(value) => Some(*value as f32),
+        Value::SignedInt(value) => Some(*value as f32),
+        Value::UnsignedInt(value) => Some(*value as f32),
+        Value::String(text) => {
+            let trimmed = text.trim();
+            let number = trimmed
+                .strip_suffix("dB")
+                .or_else(|| trimmed.strip_suffix("db"))
+                .or_else(|| trimmed.strip_suffix("DB"))
+                .unwrap_or(trimmed)
+                .trim();
+            number.parse::<f32>().ok().filter(|value| value.is_finite())
+        }
+        _ => None,
+    }
+}
+
+// K-weighting pre-filter (high shelf) from ITU-R BS.1770, derived for
+// arbitrary sample rates the same way libebur128 does.
+fn k_shelf(sample_rate: f64) -> Biquad {
+    let f0 = 1_681.974_450_955_533;
+    let gain_db = 3.999_843_853_973_347;
+    let q = 0.707_175_236_955_419_6;
+
+    let k = (PI * f0 / sample_rate).tan();
+    let vh = 10f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.499_666_774_154_541_6);
+    let a0 = 1.0 + k / q + k * k;
+    Biquad {
+        b0: (vh + vb * k / q + k * k) / a0,
+        b1: 2.0 * (k * k - vh) / a0,
+        b2: (vh - vb * k / q + k * k) / a0,
+        a1: 2.0 * (k * k - 1.0) / a0,
+        a2: (1.0 - k / q + k * k) / a0,
+    }
+}
+
+fn k_highpass(sample_rate: f64) -> Biquad {
+    let f0 = 38.135_470_876_024_44;
+    let q = 0.500_327_037_323_877_3;
+
+    let k = (PI * f0 / sample_rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    Biquad {
+        b0: 1.0,
+        b1: -2.0,
+        b2: 1.0,
+        a1: 2.0 * (k * k - 1.0) / a0,
+        a2: (1.0 - k / q + k * k) / a0,
+    }
+}
+
+// Running EBU R128 integrated loudness: K-weighted mean square over 400 ms
+// blocks with 75% overlap, absolute and relative gating. The relative gate is
+// resolved to 0.1 LU, which is how libebur128 does it as well.
+pub(crate) struct LoudnessMeter {
+    channels: usize,
+    shelf: Biquad,
+    highpass: Biquad,
+    states: Vec<(BiquadState, BiquadState)>,
+    sub_block_frames: usize,
+    sub_block_energy: f64,
+    sub_block_filled: usize,
+    recent_sub_blocks: VecDeque<f64>,
+    blocks: usize,
+    gated_energy: f64,
+    gated_blocks: usize,
+    histogram: Vec<(f64, usize)>,
+}
+
+impl LoudnessMeter {
+    pub fn new(sample_rate: u32, channels: usize) -> Self {
+        let rate = sample_rate.max(1) as f64;
+        Self {
+            channels: channels.max(1),
+            shelf: k_shelf(rate),
+            highpass: k_highpass(rate),
+            states: vec![Default::default(); channels.max(1)],
+            sub_block_frames: (sample_rate as usize / 10).max(1),
+            sub_block_energy: 0.0,
+            sub_block_filled: 0,
+            recent_sub_blocks: VecDeque::with_capacity(4),
+            blocks: 0,
+            gated_energy: 0.0,
+            gated_blocks: 0,
+            histogram: vec![(0.0, 0); HISTOGRAM_BINS],
+        }
+    }
+
+    pub fn push(&mut self, samples: &[Sample]) {
+        for frame in samples.chunks_exact(self.channels) {
+            let mut energy = 0.0;
+            for (sample, (shelf, highpass)) in frame.iter().zip(self.states.iter_mut()) {
+                let shelved = shelf.process(&self.shelf, *sample as f64);
+                let weighted = highpass.process(&self.highpass, shelved);
+                energy += weighted * weighted;
+            }
+            self.sub_block_energy += energy;
+            self.sub_block_filled += 1;
+
+            if self.sub_block_filled == self.sub_block_frames {
+                self.finish_sub_block();
+            }
+        }
+    }
+
+    fn finish_sub_block(&mut self) {
+        let mean = self.sub_block_energy / self.sub_block_frames as f64;
+        self.sub_block_energy = 0.0;
+        self.sub_block_filled = 0;
+
+        if self.recent_sub_blocks.len() == 4 {
+            self.recent_sub_blocks.pop_front();
+        }
+        self.recent_sub_blocks.push_back(mean);
+        if self.recent_sub_blocks.len() == 4 {
+            let block = self.recent_sub_blocks.iter().sum::<f64>() / 4.0;
+            self.add_block(block);
+        }
+    }
+
+    fn add_block(&mut self, energy: f64) {
+        self.blocks += 1;
+        if energy <= lufs_to_energy(ABSOLUTE_GATE_LUFS) {
+            return;
+        }
+        self.gated_energy += energy;
+        self.gated_blocks += 1;
+        let bin = &mut self.histogram[histogram_bin(energy_to_lufs(energy))];
+        bin.0 += energy;
+        bin.1 += 1;
+    }
+
+    pub fn measured_blocks(&self) -> usize {
+        self.blocks
+    }
+
+    pub fn integrated_lufs(&self) -> Option<f64> {
+        if self.gated_blocks == 0 {
+            return None;
+        }
+        let relative_gate =
+            energy_to_lufs(self.gated_energy / self.gated_blocks as f64) + RELATIVE_GATE_LU;
+        let (sum, count) = self.histogram[histogram_bin(relative_gate)..]
+            .iter()
+            .fold((0.0, 0usize), |(sum, count), (energy, blocks)| {
+                (sum + energy, count + blocks)
+            });
+        (count > 0).then(|| energy_to_lufs(sum / count as f64))
+    }
+}
+
+fn histogram_bin(lufs: f64) -> usize {
+    let bin = ((lufs - ABSOLUTE_GATE_LUFS) / HISTOGRAM_STEP_LU).floor();
+    (bin.max(0.0) as usize).min(HISTOGRAM_BINS - 1)
+}
+
+fn energy_to_lufs(energy: f64) -> f64 {
+    -0.691 + 10.0 * energy.log10()
+}
+
+fn lufs_to_energy(lufs: f64) -> f64 {
+    10f64.powf((lufs + 0.691) / 10.0)
+}
+
+pub(crate) fn db_to_linear(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+// Applies the normalization gain to decoded samples at the output rate. The
+// gain comes from ReplayGain tags when present and otherwise follows the
+// running loudness estimate, smoothed so the correction never jumps.
+pub(crate) struct LoudnessNormalizer {
+    mode: NormalizationMode,
+    target_lufs: f32,
+    channels: usize,
+    meter: Option<LoudnessMeter>,
+    fixed_gain_db: Option<f32>,
+    desired_gain: f32,
+    current_gain: f32,
+    smoothing: f32,
+    limiter_gain: f32,
+    limiter_release: f32,
+}
+
+impl LoudnessNormalizer {
+    pub fn new(
+        mode: NormalizationMode,
+        target_lufs: f32,
+        replay_gain: ReplayGainTags,
+        sample_rate: u32,
+        channels: usize,
+    ) -> Self {
+        let fixed_gain_db = replay_gain.select(mode).map(|(gain_db, peak)| {
+            let gain_db = gain_db + (target_lufs - REPLAYGAIN_REFERENCE_LUFS);
+            // With a known peak, cap the gain so the loudest sample stays below
+            // full scale; the limiter only has to catch inter-sample overs.
+            let peak_cap = peak
+                .filter(|peak| *peak > 0.0)
+                .map(|peak| -20.0 * peak.log10());
+            match peak_cap {
+                Some(cap) => gain_db.min(cap),
+                None => gain_db,
+            }
+        });
+        let meter = (mode != NormalizationMode::Off && fixed_gain_db.is_none())
+            .then(|| LoudnessMeter::new(sample_rate, channels));
+        let initial_gain =
+            db_to_linear(fixed_gain_db.unwrap_or(0.0).clamp(MIN_GAIN_DB, MAX_GAIN_DB));
+        let rate = sample_rate.max(1) as f32;
+
+        Self {
+            mode,
+            target_lufs,
+            channels: channels.max(1),
+            meter,
+            fixed_gain_db,
+            desired_gain: initial_gain,
+            current_gain: initial_gain,
+            smoothing: 1.0 - (-1.0 / (GAIN_SMOOTHING_MS * rate / 1000.0)).exp(),
+            limiter_gain: 1.0,
+            limiter_release: 1.0 - (-1.0 / (LIMITER_RELEASE_MS * rate / 1000.0)).exp(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.mode != NormalizationMode::Off
+    }
+
+    pub fn gain_db(&self) -> f32 {
+        20.0 * (self.current_gain * self.limiter_gain).max(1e-6).log10()
+    }
+
+    pub fn process(&mut self, samples: &mut [Sample]) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        if let Some(meter) = &mut self.meter {
+            let measured = meter.measured_blocks();
+            meter.push(samples);
+            // The estimate only moves when another block has been completed.
+            if meter.measured_blocks() > measured
+                && meter.measured_blocks() >= MIN_MEASURED_BLOCKS
+                && let Some(lufs) = meter.integrated_lufs()
+            {
+                let gain_db = (self.target_lufs - lufs as f32).clamp(MIN_GAIN_DB, MAX_GAIN_DB);
+                self.desired_gain = db_to_linear(gain_db);
+            }
+        } else if let Some(gain_db) = self.fixed_gain_db {
+            self.desired_gain = db_to_linear(gain_db.clamp(MIN_GAIN_DB, MAX_GAIN_DB));
+        }
+
+        for frame in samples.chunks_exact_mut(self.channels) {
+            self.current_gain += (self.desired_gain - self.current_gain) * self.smoothing;
+
+            let peak = frame
+                .iter()
+                .fold(0.0f32, |peak, sample| peak.max(sample.abs()))
+                * self.current_gain;
+            if peak * self.limiter_gain > LIMITER_CEILING {
+                self.limiter_gain = LIMITER_CEILING / peak;
+            } else {
+                self.limiter_gain += (1.0 - self.limiter_gain) * self.limiter_release;
+                if peak * self.limiter_gain > LIMITER_CEILING {
+                    self.limiter_gain = LIMITER_CEILING / peak;
+                }
+            }
+
+            let gain = self.current_gain * self.limiter_gain;
+            for sample in frame.iter_mut() {
+                *sample *= gain;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use symphonia::core::meta::{StandardTagKey, Tag, Value};
+
+    use super::{LoudnessMeter, LoudnessNormalizer, ReplayGainTags};
+    use crate::config::NormalizationMode;
+
+    fn tag(key: StandardTagKey, value: &str) -> Tag {
+        Tag::new(Some(key), "", Value::String(value.to_string()))
+    }
+
+    #[test]
+    fn replay_gain_tags_parse_db_suffix() {
+        let tags = [
+            tag(StandardTagKey::ReplayGainTrackGain, "-6.50 dB"),
+            tag(StandardTagKey::ReplayGainTrackPeak, "0.988553"),
+            tag(StandardTagKey::ReplayGainAlbumGain, "+1.25 dB"),
+        ];
+        let parsed = ReplayGainTags::from_tags(&tags);
+        assert_eq!(parsed.track_gain_db, Some(-6.5));
+        assert_eq!(parsed.track_peak, Some(0.988553));
+        assert_eq!(parsed.album_gain_db, Some(1.25));
+        assert_eq!(parsed.album_peak, None);
+    }
+
+    #[test]
+    fn full_scale_sine_measures_zero_lufs() {
+        let sample_rate = 48_000;
+        let mut meter = LoudnessMeter::new(sample_rate, 2);
+        let samples = (0..sample_rate * 5)
+            .flat_map(|index| {
+                let t = index as f32 / sample_rate as f32;
+                let value = (2.0 * std::f32::consts::PI * 997.0 * t).sin();
+                [value, value]
+            })
+            .collect::<Vec<_>>();
+        meter.push(&samples);
+
+        // BS.1770 calibration: a 0 dBFS 997 Hz sine in both front channels
+        // reads 0 LKFS.
+        let lufs = meter.integrated_lufs().expect("loudness");
+        assert!(lufs.abs() < 0.1, "lufs = {lufs}");
+    }
+
+    #[test]
+    fn relative_gate_ignores_quiet_passages() {
+        let sample_rate = 48_000;
+        let mut meter = LoudnessMeter::new(sample_rate, 2);
+        let samples = (0..sample_rate * 10)
+            .flat_map(|index| {
+                let t = index as f32 / sample_rate as f32;
+                let level = if index < sample_rate * 5 { 1.0 } else { 0.03 };
+                let value = level * (2.0 * std::f32::consts::PI * 997.0 * t).sin();
+                [value, value]
+            })
+            .collect::<Vec<_>>();
+        meter.push(&samples);
+
+        // The -30 dB half sits below the relative gate and does not count.
+        let lufs = meter.integrated_lufs().expect("loudness");
+        assert!(lufs.abs() < 0.5, "lufs = {lufs}");
+    }
+
+    #[test]
+    fn limiter_keeps_positive_gain_below_full_scale() {
+        let replay_gain = ReplayGainTags {
+            track_gain_db: Some(12.0),
+            ..ReplayGainTags::default()
+        };
+        let mut normalizer =
+            LoudnessNormalizer::new(NormalizationMode::Track, -18.0, replay_gain, 48_000, 2);
+        let mut samples = vec![0.9; 4800];
+        normalizer.process(&mut samples);
+        assert!(samples.iter().all(|sample| sample.abs() <= 1.0));
+    }
+}
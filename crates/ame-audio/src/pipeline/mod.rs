@@ -1,5 +1,21 @@
+pub mod analyzer;
 pub mod backend;
+pub mod cache;
+pub mod channels;
+pub mod codecs;
 pub mod decoder;
+pub mod devices;
+pub mod dsp;
+#[cfg(any(test, feature = "test-support"))]
+pub mod fixtures;
+pub mod loudness;
+pub mod ncm;
+pub mod offline;
+#[cfg(feature = "codec-opus")]
+pub mod opus;
+pub mod prefetch;
 pub mod runtime;
 pub mod service;
 pub mod source;
+pub mod stretch;
+pub mod tags;
@@ -0,0 +1,417 @@
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use aes::Aes128;
+use aes::cipher::{Array, BlockCipherDecrypt, KeyInit};
+use base64::Engine;
+use serde_json::Value;
+use symphonia::core::io::MediaSource;
+
+use crate::error::{AudioError, Result};
+
+const MAGIC: &[u8; 8] = b"CTENFDAM";
+const CORE_KEY: &[u8; 16] = b"hzHRAmso5kInbaxW";
+const META_KEY: &[u8; 16] = b"#14ljk_!\\]&0U<'(";
+const KEY_PREFIX: &[u8] = b"neteasecloudmusic";
+const META_PREFIX: &[u8] = b"163 key(Don't modify):";
+
+// What the official client embeds about the track, so a local file can be
+// matched with its online counterpart.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NcmMetadata {
+    pub music_id: Option<i64>,
+    pub title: String,
+    pub artists: Vec<String>,
+    pub album: String,
+    pub album_id: Option<i64>,
+    pub album_pic_url: Option<String>,
+    pub format: Option<String>,
+    pub bitrate: Option<u32>,
+    pub duration_ms: Option<u64>,
+}
+
+// A NetEase Cloud Music `.ncm` container: an AES-wrapped key for the RC4-style
+// keystream over the audio, AES-wrapped JSON metadata and an optional cover.
+pub struct NcmFile {
+    metadata: NcmMetadata,
+    cover: Option<Vec<u8>>,
+    source: NcmSource,
+}
+
+impl NcmFile {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|err| AudioError::SourceOpenFailed {
+            reason: format!("{} ({err})", path.display()),
+        })?;
+        let file_len = file.metadata()?.len();
+        let mut reader = BufReader::new(file);
+        let header = read_header(&mut reader).map_err(|err| match err {
+            NcmError::Io(err) => AudioError::SourceOpenFailed {
+                reason: format!("ncm: {} ({err})", path.display()),
+            },
+            NcmError::Invalid(reason) => AudioError::SourceOpenFailed {
+                reason: format!("ncm: {} ({reason})", path.display()),
+            },
+        })?;
+        let audio_offset = reader.stream_position()?;
+
+        Ok(Self {
+            metadata: header.metadata,
+            cover: header.cover,
+            source: NcmSource {
+                file: reader.into_inner(),
+                audio_offset,
+                len: file_len.saturating_sub(audio_offset),
+                position: None,
+                keystream: header.keystream,
+            },
+        })
+    }
+
+    pub fn metadata(&self) -> &NcmMetadata {
+        &self.metadata
+    }
+
+    pub fn cover(&self) -> Option<&[u8]> {
+        self.cover.as_deref()
+    }
+
+    pub fn into_media_source(self) -> Box<dyn MediaSource> {
+        Box::new(self.source)
+    }
+}
+
+struct NcmHeader {
+    keystream: [u8; 256],
+    metadata: NcmMetadata,
+    cover: Option<Vec<u8>>,
+}
+
+enum NcmError {
+    Io(std::io::Error),
+    Invalid(&'static str),
+}
+
+impl From<std::io::Error> for NcmError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+fn read_header(reader: &mut (impl Read + Seek)) -> std::result::Result<NcmHeader, NcmError> {
+    let mut magic = [0; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(NcmError::Invalid("not an ncm file"));
+    }
+    reader.seek(SeekFrom::Current(2))?;
+
+    let mut key = read_block(reader)?;
+    key.iter_mut().for_each(|byte| *byte ^= 0x64);
+    let key = aes_ecb_decrypt(CORE_KEY, &key)
+        .and_then(|key| key.strip_prefix(KEY_PREFIX).map(<[u8]>::to_vec))
+        .filter(|key| !key.is_empty())
+        .ok_or(NcmError::Invalid("bad key block"))?;
+
+    let mut meta = read_block(reader)?;
+    meta.iter_mut().for_each(|byte| *byte ^= 0x63);
+    let metadata = if meta.is_empty() {
+        NcmMetadata::default()
+    } else {
+        parse_metadata(&meta).ok_or(NcmError::Invalid("bad metadata block"))?
+    };
+
+    // CRC32 of the key and metadata, then bytes of unknown purpose.
+    reader.seek(SeekFrom::Current(9))?;
+    let cover = read_cover(reader)?;
+
+    Ok(NcmHeader {
+        keystream: keystream(&key),
+        metadata,
+        cover,
+    })
+}
+
+fn read_u32(reader: &mut impl Read) -> std::io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_block(reader: &mut impl Read) -> std::io::Result<Vec<u8>> {
+    let len = read_u32(reader)? as usize;
+    let mut block = vec![0; len];
+    reader.read_exact(&mut block)?;
+    Ok(block)
+}
+
+// Older files store the cover size followed by the image. Newer ones reserve a
+// frame first and store the actual image size after it, padding the rest; a
+// second length no larger than the first tells the two apart, since image
+// magic bytes always read as a much larger number.
+fn read_cover(reader: &mut (impl Read + Seek)) -> std::result::Result<Option<Vec<u8>>, NcmError> {
+    let first = read_u32(reader)?;
+    let second = read_u32(reader)?;
+    let (frame_len, image_len) = if second <= first {
+        (first, second)
+    } else {
+        reader.seek(SeekFrom::Current(-4))?;
+        (first, first)
+    };
+
+    let mut image = vec![0; image_len as usize];
+    reader.read_exact(&mut image)?;
+    reader.seek(SeekFrom::Current(i64::from(frame_len - image_len)))?;
+    Ok((!image.is_empty()).then_some(image))
+}
+
+fn parse_metadata(meta: &[u8]) -> Option<NcmMetadata> {
+    let encoded = meta.strip_prefix(META_PREFIX)?;
+    let encrypted = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .ok()?;
+    let decrypted = aes_ecb_decrypt(META_KEY, &encrypted)?;
+    let (kind, json) = decrypted.split_at(decrypted.iter().position(|&byte| byte == b':')? + 1);
+    let mut value: Value = serde_json::from_slice(json).ok()?;
+    // Radio programs wrap the track in an outer object.
+    if kind == b"dj:" {
+        value = value.get("mainMusic")?.clone();
+    }
+
+    let text = |key: &str| value.get(key).and_then(Value::as_str).map(str::to_owned);
+    // Ids show up as numbers or strings depending on the client version.
+    let id = |key: &str| {
+        let field = value.get(key)?;
+        field
+            .as_i64()
+            .or_else(|| field.as_str().and_then(|id| id.parse().ok()))
+    };
+    let artists = value
+        .get("artist")
+        .and_then(Value::as_array)
+        .map(|artists| {
+            artists
+                .iter()
+                .filter_map(|artist| artist.get(0).and_then(Value::as_str))
+                .map(str::to_owned)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(NcmMetadata {
+        music_id: id("musicId"),
+        title: text("musicName").unwrap_or_default(),
+        artists,
+        album: text("album").unwrap_or_default(),
+        album_id: id("albumId"),
+        album_pic_url: text("albumPic"),
+        format: text("format"),
+        bitrate: id("bitrate").and_then(|bitrate| u32::try_from(bitrate).ok()),
+        duration_ms: id("duration").and_then(|duration| u64::try_from(duration).ok()),
+    })
+}
+
+fn aes_ecb_decrypt(key: &[u8; 16], data: &[u8]) -> Option<Vec<u8>> {
+    if data.is_empty() || !data.len().is_multiple_of(16) {
+        return None;
+    }
+
+    let cipher = Aes128::new(&Array::from(*key));
+    let mut result = Vec::with_capacity(data.len());
+    for chunk in data.chunks_exact(16) {
+        let mut block = Array::from([0u8; 16]);
+        block.copy_from_slice(chunk);
+        cipher.decrypt_block(&mut block);
+        result.extend_from_slice(&block);
+    }
+
+    let pad_len = *result.last()? as usize;
+    if pad_len == 0 || pad_len > 16 || !result.ends_with(&vec![pad_len as u8; pad_len]) {
+        return None;
+    }
+    result.truncate(result.len() - pad_len);
+    Some(result)
+}
+
+// RC4 key scheduling, but the keystream byte only depends on the position
+// modulo 256, which is what makes the audio seekable.
+fn keystream(key: &[u8]) -> [u8; 256] {
+    let mut sbox: [u8; 256] = std::array::from_fn(|index| index as u8);
+    let mut j = 0u8;
+    for i in 0..256 {
+        j = j.wrapping_add(sbox[i]).wrapping_add(key[i % key.len()]);
+        sbox.swap(i, j as usize);
+    }
+
+    std::array::from_fn(|offset| {
+        let i = (offset + 1) & 0xff;
+        let si = sbox[i] as usize;
+        let sj = sbox[(si + i) & 0xff] as usize;
+        sbox[(si + sj) & 0xff]
+    })
+}
+
+struct NcmSource {
+    file: File,
+    audio_offset: u64,
+    len: u64,
+    // `None` until the first read: the header was parsed through a buffered
+    // reader, so the file offset is somewhere past the audio start.
+    position: Option<u64>,
+    keystream: [u8; 256],
+}
+
+impl Read for NcmSource {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let position = match self.position {
+            Some(position) => position,
+            None => self.seek(SeekFrom::Start(0))?,
+        };
+        let read = self.file.read(buf)?;
+        for (offset, byte) in buf[..read].iter_mut().enumerate() {
+            *byte ^= self.keystream[((position + offset as u64) & 0xff) as usize];
+        }
+        self.position = Some(position + read as u64);
+        Ok(read)
+    }
+}
+
+impl Seek for NcmSource {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.unwrap_or(0).checked_add_signed(delta),
+            SeekFrom::End(delta) => self.len.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "seek before start")
+        })?;
+
+        self.file
+            .seek(SeekFrom::Start(self.audio_offset + target))?;
+        self.position = Some(target);
+        Ok(target)
+    }
+}
+
+impl MediaSource for NcmSource {
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        Some(self.len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Seek, SeekFrom};
+
+    use aes::Aes128;
+    use aes::cipher::{Array, BlockCipherEncrypt, KeyInit};
+    use base64::Engine;
+
+    use super::{CORE_KEY, META_KEY, NcmFile, keystream};
+    use crate::pipeline::fixtures;
+
+    fn aes_ecb_encrypt(key: &[u8; 16], data: &[u8]) -> Vec<u8> {
+        let cipher = Aes128::new(&Array::from(*key));
+        let pad_len = 16 - data.len() % 16;
+        let mut padded = data.to_vec();
+        padded.extend(std::iter::repeat_n(pad_len as u8, pad_len));
+        for chunk in padded.chunks_exact_mut(16) {
+            let mut block = Array::from([0u8; 16]);
+            block.copy_from_slice(chunk);
+            cipher.encrypt_block(&mut block);
+            chunk.copy_from_slice(&block);
+        }
+        padded
+    }
+
+    fn block(data: &[u8]) -> Vec<u8> {
+        let mut out = (data.len() as u32).to_le_bytes().to_vec();
+        out.extend_from_slice(data);
+        out
+    }
+
+    fn ncm(audio: &[u8], cover: &[u8], framed_cover: bool) -> Vec<u8> {
+        let rc4_key = b"0123456789abcdef-track-key";
+        let mut key = aes_ecb_encrypt(CORE_KEY, &[b"neteasecloudmusic", &rc4_key[..]].concat());
+        key.iter_mut().for_each(|byte| *byte ^= 0x64);
+
+        let json = br#"{"musicId":"1901371647","musicName":"Example","artist":[["Alice",1],["Bob",2]],"album":"Album","albumId":42,"format":"flac","bitrate":999000,"duration":1000}"#;
+        let encrypted = aes_ecb_encrypt(META_KEY, &[b"music:", &json[..]].concat());
+        let mut meta = [
+            &b"163 key(Don't modify):"[..],
+            base64::engine::general_purpose::STANDARD
+                .encode(encrypted)
+                .as_bytes(),
+        ]
+        .concat();
+        meta.iter_mut().for_each(|byte| *byte ^= 0x63);
+
+        let mut out = b"CTENFDAM".to_vec();
+        out.extend_from_slice(&[0; 2]);
+        out.extend(block(&key));
+        out.extend(block(&meta));
+        out.extend_from_slice(&[0; 9]);
+        if framed_cover {
+            out.extend_from_slice(&(cover.len() as u32 + 16).to_le_bytes());
+            out.extend(block(cover));
+            out.extend_from_slice(&[0; 16]);
+        } else {
+            out.extend(block(cover));
+        }
+        let stream = keystream(rc4_key);
+        out.extend(
+            audio
+                .iter()
+                .enumerate()
+                .map(|(offset, byte)| byte ^ stream[offset & 0xff]),
+        );
+        out
+    }
+
+    #[test]
+    fn decrypts_audio_metadata_and_cover() {
+        let audio = fixtures::wav(44_100, 2, 16, 4410);
+        let cover = b"\xFF\xD8\xFF\xE0fake-jpeg".to_vec();
+        for framed_cover in [false, true] {
+            let path = std::env::temp_dir()
+                .join(format!("ame-ncm-{}-{framed_cover}.ncm", std::process::id()));
+            std::fs::write(&path, ncm(&audio, &cover, framed_cover)).unwrap();
+
+            let file = NcmFile::open(&path).unwrap();
+            let metadata = file.metadata();
+            assert_eq!(metadata.music_id, Some(1_901_371_647));
+            assert_eq!(metadata.title, "Example");
+            assert_eq!(metadata.artists, ["Alice", "Bob"]);
+            assert_eq!(metadata.album_id, Some(42));
+            assert_eq!(metadata.format.as_deref(), Some("flac"));
+            assert_eq!(file.cover(), Some(&cover[..]));
+
+            let mut source = file.into_media_source();
+            assert_eq!(source.byte_len(), Some(audio.len() as u64));
+            let mut decrypted = Vec::new();
+            source.read_to_end(&mut decrypted).unwrap();
+            assert_eq!(decrypted, audio);
+
+            let mut buf = [0; 8];
+            source.seek(SeekFrom::Start(1000)).unwrap();
+            source.read_exact(&mut buf).unwrap();
+            assert_eq!(buf, audio[1000..1008]);
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn rejects_files_without_the_magic() {
+        let path = std::env::temp_dir().join(format!("ame-ncm-{}-plain.ncm", std::process::id()));
+        std::fs::write(&path, fixtures::wav(44_100, 2, 16, 10)).unwrap();
+        assert!(NcmFile::open(&path).is_err());
+        let _ = std::fs::remove_file(path);
+    }
+}
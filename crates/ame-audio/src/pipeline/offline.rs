@@ -0,0 +1,499 @@
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use ringbuf::traits::Observer;
+use tracing::warn;
+
+use crate::backend::{
+    AudioDevice, BackendNotification, FadeControl, OpenStreamRequest, OutputBackend,
+    OutputCallbackContext, OutputSession,
+};
+use crate::config::{OfflineClock, OfflineOutputConfig};
+use crate::error::{AudioError, Result};
+use crate::{OutputBackendKind, Sample};
+
+const BLOCK_MS: u64 = 10;
+const NULL_DEVICE: &str = "null";
+
+// Lets tests pull the plug on an offline output the way a real device can
+// disappear mid-stream, and plug extra devices in next to the built-in one.
+// The runtime hands the same faults to every offline backend it builds.
+#[derive(Debug, Clone, Default)]
+pub struct OfflineFaults {
+    disconnect: Arc<AtomicBool>,
+    plugged: Arc<Mutex<Vec<String>>>,
+}
+
+#[cfg(any(test, feature = "test-support"))]
+impl OfflineFaults {
+    pub fn disconnect(&self) {
+        self.disconnect.store(true, Ordering::Relaxed);
+    }
+
+    pub fn plug(&self, device: impl Into<String>) {
+        let device = device.into();
+        if let Ok(mut plugged) = self.plugged.lock()
+            && !plugged.contains(&device)
+        {
+            plugged.push(device);
+        }
+    }
+
+    // Streams running on the device fail the next time they render.
+    pub fn unplug(&self, device: &str) {
+        if let Ok(mut plugged) = self.plugged.lock() {
+            plugged.retain(|name| name != device);
+        }
+    }
+}
+
+impl OfflineFaults {
+    fn is_plugged(&self, device: &str) -> bool {
+        self.plugged
+            .lock()
+            .is_ok_and(|plugged| plugged.iter().any(|name| name == device))
+    }
+
+    fn plugged(&self) -> Vec<String> {
+        self.plugged
+            .lock()
+            .map(|plugged| plugged.clone())
+            .unwrap_or_default()
+    }
+
+    fn take_disconnect(&self) -> bool {
+        self.disconnect.swap(false, Ordering::Relaxed)
+    }
+}
+
+// Drives the output callback from a software clock instead of a sound card.
+// `Null` discards what it renders, `WavFile` writes it to a float WAV file that
+// is recreated for every stream the runtime opens.
+pub struct OfflineBackend {
+    kind: OutputBackendKind,
+    config: OfflineOutputConfig,
+    faults: OfflineFaults,
+}
+
+impl OfflineBackend {
+    pub fn new(kind: OutputBackendKind, config: OfflineOutputConfig) -> Self {
+        Self::with_faults(kind, config, OfflineFaults::default())
+    }
+
+    pub(crate) fn with_faults(
+        kind: OutputBackendKind,
+        config: OfflineOutputConfig,
+        faults: OfflineFaults,
+    ) -> Self {
+        Self {
+            kind,
+            config,
+            faults,
+        }
+    }
+
+    fn device(&self) -> Result<AudioDevice> {
+        let name = match self.kind {
+            OutputBackendKind::WavFile => self.wav_path()?.display().to_string(),
+            _ => NULL_DEVICE.to_string(),
+        };
+        Ok(AudioDevice {
+            id: name.clone(),
+            name,
+        })
+    }
+
+    fn wav_path(&self) -> Result<&Path> {
+        self.config
+            .wav_path
+            .as_deref()
+            .ok_or_else(|| AudioError::ConfigInvalid {
+                reason: "wav output needs a file path".into(),
+            })
+    }
+}
+
+impl OutputBackend for OfflineBackend {
+    fn kind(&self) -> OutputBackendKind {
+        self.kind
+    }
+
+    fn list_devices(&self) -> Result<Vec<AudioDevice>> {
+        let mut devices = vec![self.device()?];
+        devices.extend(self.faults.plugged().into_iter().map(|name| AudioDevice {
+            id: name.clone(),
+            name,
+        }));
+        Ok(devices)
+    }
+
+    fn default_device(&self) -> Result<AudioDevice> {
+        self.device()
+    }
+
+    fn open_stream(&self, request: OpenStreamRequest) -> Result<Box<dyn OutputSession>> {
+        let built_in = self.device()?;
+        let device = match request.preferred_device.as_deref() {
+            Some(preferred) if preferred != built_in.name => self
+                .list_devices()?
+                .into_iter()
+                .find(|device| device.name == preferred)
+                .ok_or_else(|| AudioError::DeviceNotAvailable {
+                    device: preferred.to_string(),
+                })?,
+            _ => built_in.clone(),
+        };
+
+        // A software clock runs at any rate, so native formats are always met.
+        let (sample_rate, channels) = match request.native_format.as_ref() {
+            Some(format) if format.sample_rate > 0 && format.channels > 0 => {
+                (format.sample_rate, format.channels)
+            }
+            _ => (self.config.sample_rate.max(1), self.config.channels.max(1)),
+        };
+        let sink = match self.kind {
+            OutputBackendKind::WavFile => {
+                let path = self.wav_path()?;
+                let writer = WavWriter::create(path, sample_rate, channels).map_err(|err| {
+                    AudioError::OutputInitFailed {
+                        reason: format!("{} ({err})", path.display()),
+                    }
+                })?;
+                Some(writer)
+            }
+            _ => None,
+        };
+
+        let context = OutputCallbackContext::new(request, sample_rate);
+        let volume = Arc::clone(&context.volume);
+        let fade = Arc::clone(&context.fade);
+        let playing = Arc::new(AtomicBool::new(false));
+        let stop = Arc::new(AtomicBool::new(false));
+        let stream = OfflineStream {
+            context,
+            sink,
+            clock: self.config.clock,
+            sample_rate,
+            channels: usize::from(channels),
+            playing: Arc::clone(&playing),
+            stop: Arc::clone(&stop),
+            faults: self.faults.clone(),
+            plugged_device: (device != built_in).then(|| device.name.clone()),
+        };
+        let thread = thread::Builder::new()
+            .name("ame-audio-offline-output".into())
+            .spawn(move || stream.run())
+            .map_err(|err| AudioError::OutputInitFailed {
+                reason: err.to_string(),
+            })?;
+
+        Ok(Box::new(OfflineOutputSession {
+            playing,
+            stop,
+            volume,
+            fade,
+            thread: Some(thread),
+            sample_rate,
+            channels,
+            device_name: device.name,
+        }))
+    }
+}
+
+struct OfflineStream {
+    context: OutputCallbackContext,
+    sink: Option<WavWriter>,
+    clock: OfflineClock,
+    sample_rate: u32,
+    channels: usize,
+    playing: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+    faults: OfflineFaults,
+    plugged_device: Option<String>,
+}
+
+impl OfflineStream {
+    fn run(mut self) {
+        let period = Duration::from_millis(BLOCK_MS);
+        let block_frames = (self.sample_rate as u64 * BLOCK_MS / 1000).max(1) as usize;
+        let mut buffer: Vec<Sample> = vec![0.0; block_frames * self.channels];
+        let mut deadline = Instant::now();
+
+        while !self.stop.load(Ordering::Relaxed) {
+            let unplugged = self
+                .plugged_device
+                .as_deref()
+                .is_some_and(|device| !self.faults.is_plugged(device));
+            if unplugged || self.faults.take_disconnect() {
+                let _ = self
+                    .context
+                    .event_tx
+                    .send(BackendNotification::StreamError {
+                        stream_id: self.context.stream_id,
+                        reason: "offline output disconnected".into(),
+                    });
+                break;
+            }
+            if !self.playing.load(Ordering::Relaxed) {
+                thread::sleep(period);
+                deadline = Instant::now();
+                continue;
+            }
+
+            let frames = match self.clock {
+                OfflineClock::Realtime => {
+                    deadline += period;
+                    if let Some(wait) = deadline.checked_duration_since(Instant::now()) {
+                        thread::sleep(wait);
+                    }
+                    block_frames
+                }
+                // Only what the decoder has produced is rendered, so a slow
+                // decoder never shows up as gaps in the output.
+                OfflineClock::Freewheel => {
+                    (self.context.consumer.occupied_len() / self.channels).min(block_frames)
+                }
+            };
+
+            let samples = &mut buffer[..frames * self.channels];
+            self.context.render(samples, self.channels);
+            if let Some(sink) = self.sink.as_mut()
+                && let Err(err) = sink.write(samples)
+            {
+                warn!("failed to write offline output: {err}");
+                self.sink = None;
+            }
+            if frames == 0 {
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+
+        if let Some(sink) = self.sink.take()
+            && let Err(err) = sink.finish()
+        {
+            warn!("failed to finish offline output: {err}");
+        }
+    }
+}
+
+struct OfflineOutputSession {
+    playing: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+    volume: Arc<AtomicU32>,
+    fade: Arc<FadeControl>,
+    thread: Option<JoinHandle<()>>,
+    sample_rate: u32,
+    channels: u16,
+    device_name: String,
+}
+
+impl OutputSession for OfflineOutputSession {
+    fn play(&self) -> Result<()> {
+        self.playing.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn pause(&self) -> Result<()> {
+        self.playing.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn set_volume(&self, volume: f32) {
+        self.volume
+            .store(volume.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+    }
+
+    fn fade_to(&self, level: f32, duration_ms: u64) {
+        self.fade.fade_to(level, duration_ms, self.sample_rate);
+    }
+
+    fn force_fade_level(&self, level: f32) {
+        self.fade.force_level(level);
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    // Offline output is always rendered as 32-bit float.
+    fn bit_depth(&self) -> u32 {
+        32
+    }
+
+    fn device_name(&self) -> String {
+        self.device_name.clone()
+    }
+}
+
+impl Drop for OfflineOutputSession {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+// 32-bit float WAV; the RIFF and data sizes are patched in once the stream ends.
+struct WavWriter {
+    file: BufWriter<File>,
+    data_bytes: u32,
+}
+
+impl WavWriter {
+    fn create(path: &Path, sample_rate: u32, channels: u16) -> std::io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let block_align = channels * 4;
+        file.write_all(b"RIFF")?;
+        file.write_all(&36u32.to_le_bytes())?;
+        file.write_all(b"WAVEfmt ")?;
+        file.write_all(&16u32.to_le_bytes())?;
+        file.write_all(&3u16.to_le_bytes())?;
+        file.write_all(&channels.to_le_bytes())?;
+        file.write_all(&sample_rate.to_le_bytes())?;
+        file.write_all(&(sample_rate * u32::from(block_align)).to_le_bytes())?;
+        file.write_all(&block_align.to_le_bytes())?;
+        file.write_all(&32u16.to_le_bytes())?;
+        file.write_all(b"data")?;
+        file.write_all(&0u32.to_le_bytes())?;
+        Ok(Self {
+            file,
+            data_bytes: 0,
+        })
+    }
+
+    fn write(&mut self, samples: &[Sample]) -> std::io::Result<()> {
+        for sample in samples {
+            self.file.write_all(&sample.to_le_bytes())?;
+        }
+        self.data_bytes = self.data_bytes.saturating_add((samples.len() * 4) as u32);
+        Ok(())
+    }
+
+    fn finish(mut self) -> std::io::Result<()> {
+        self.file.seek(SeekFrom::Start(4))?;
+        self.file
+            .write_all(&(36u32.saturating_add(self.data_bytes)).to_le_bytes())?;
+        self.file.seek(SeekFrom::Start(40))?;
+        self.file.write_all(&self.data_bytes.to_le_bytes())?;
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    use ringbuf::traits::{Producer, Split};
+
+    use super::OfflineBackend;
+    use crate::OutputBackendKind;
+    use crate::backend::{
+        BackendNotification, OpenStreamRequest, OutputBackend, OutputSession, PlaybackDrainState,
+        SampleRing,
+    };
+    use crate::config::{OfflineClock, OfflineOutputConfig};
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("ame-offline-{}-{name}", std::process::id()))
+    }
+
+    fn read_wav_samples(path: &Path) -> Vec<f32> {
+        let bytes = std::fs::read(path).unwrap();
+        assert_eq!(&bytes[..4], b"RIFF");
+        assert_eq!(u16::from_le_bytes([bytes[20], bytes[21]]), 3);
+        let data_len = u32::from_le_bytes(bytes[40..44].try_into().unwrap()) as usize;
+        assert_eq!(bytes.len(), 44 + data_len);
+        bytes[44..]
+            .chunks_exact(4)
+            .map(|sample| f32::from_le_bytes(sample.try_into().unwrap()))
+            .collect()
+    }
+
+    // Opens a stream on `backend` that plays `samples` and nothing more.
+    fn open_queued(
+        backend: &OfflineBackend,
+        samples: &[f32],
+    ) -> (Box<dyn OutputSession>, mpsc::Receiver<BackendNotification>) {
+        let (mut producer, consumer) = SampleRing::new(samples.len().max(1)).split();
+        assert_eq!(producer.push_slice(samples), samples.len());
+        let drain_state = Arc::new(PlaybackDrainState::default());
+        drain_state.on_samples_queued(samples.len());
+        drain_state.mark_decoder_finished();
+
+        let (event_tx, event_rx) = mpsc::channel();
+        let session = backend
+            .open_stream(OpenStreamRequest {
+                stream_id: 7,
+                preferred_device: None,
+                native_format: None,
+                volume: 1.0,
+                fade_in_ms: 0,
+                consumer,
+                analysis: None,
+                event_tx,
+                drain_state,
+            })
+            .unwrap();
+        (session, event_rx)
+    }
+
+    #[test]
+    fn wav_output_holds_exactly_the_queued_samples() {
+        let path = temp_path("direct.wav");
+        let backend = OfflineBackend::new(
+            OutputBackendKind::WavFile,
+            OfflineOutputConfig {
+                clock: OfflineClock::Freewheel,
+                wav_path: Some(path.clone()),
+                ..OfflineOutputConfig::default()
+            },
+        );
+        let samples = (0..2000)
+            .map(|index| index as f32 / 4000.0)
+            .collect::<Vec<_>>();
+        let (session, event_rx) = open_queued(&backend, &samples);
+        session.play().unwrap();
+        assert!(matches!(
+            event_rx.recv_timeout(TIMEOUT),
+            Ok(BackendNotification::PlaybackDrained { stream_id: 7 })
+        ));
+        drop(session);
+
+        assert_eq!(read_wav_samples(&path), samples);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn realtime_clock_renders_at_the_stream_rate() {
+        let backend = OfflineBackend::new(OutputBackendKind::Null, OfflineOutputConfig::default());
+        // 300 ms of 48 kHz stereo.
+        let samples = vec![0.25; 48_000 * 2 * 3 / 10];
+        let (session, event_rx) = open_queued(&backend, &samples);
+        let started = Instant::now();
+        session.play().unwrap();
+        assert!(matches!(
+            event_rx.recv_timeout(TIMEOUT),
+            Ok(BackendNotification::PlaybackDrained { stream_id: 7 })
+        ));
+        let elapsed = started.elapsed();
+        assert!(
+            elapsed >= Duration::from_millis(280) && elapsed < Duration::from_millis(1_000),
+            "drained after {elapsed:?}"
+        );
+    }
+}
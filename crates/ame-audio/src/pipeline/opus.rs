@@ -0,0 +1,126 @@
+use audiopus::coder::{Decoder as LibOpusDecoder, GenericCtl};
+use audiopus::packet::Packet as OpusPacket;
+use audiopus::{Channels as OpusChannels, MutSignals, SampleRate};
+use symphonia::core::audio::{AsAudioBufferRef, AudioBuffer, AudioBufferRef, Signal, SignalSpec};
+use symphonia::core::codecs::{
+    CODEC_TYPE_OPUS, CodecDescriptor, CodecParameters, Decoder, DecoderOptions, FinalizeResult,
+};
+use symphonia::core::errors::{Error, Result, decode_error, unsupported_error};
+use symphonia::core::formats::Packet;
+use symphonia::core::support_codec;
+
+// Opus always decodes at 48 kHz; 120 ms is the longest packet RFC 6716 allows.
+const OPUS_SAMPLE_RATE: u32 = 48_000;
+const MAX_PACKET_FRAMES: usize = 5_760;
+
+// Symphonia demuxes Ogg and MP4 Opus streams but ships no decoder for them, so
+// packets are handed to libopus here.
+pub(crate) struct OpusDecoder {
+    params: CodecParameters,
+    decoder: ExclusiveDecoder,
+    channels: usize,
+    pre_skip: usize,
+    interleaved: Vec<f32>,
+    buf: AudioBuffer<f32>,
+}
+
+// Symphonia wants decoders to be `Sync`, which the libopus handle is not. The
+// handle is only ever reached through `&mut self`, so no two threads can use it
+// at the same time.
+struct ExclusiveDecoder(LibOpusDecoder);
+
+// SAFETY: the wrapped decoder is private and only accessed via `&mut`.
+unsafe impl Sync for ExclusiveDecoder {}
+
+impl OpusDecoder {
+    fn decode_inner(&mut self, packet: &Packet) -> Result<()> {
+        let input = OpusPacket::try_from(packet.buf())
+            .map_err(|err| Error::DecodeError(opus_error_message(err)))?;
+        let output = MutSignals::try_from(&mut self.interleaved[..])
+            .map_err(|err| Error::DecodeError(opus_error_message(err)))?;
+        let frames = self
+            .decoder
+            .0
+            .decode_float(Some(input), output, false)
+            .map_err(|err| Error::DecodeError(opus_error_message(err)))?;
+
+        // The pre-skip samples are encoder priming and must not be played.
+        let skip = self.pre_skip.min(frames);
+        self.pre_skip -= skip;
+
+        self.buf.clear();
+        self.buf.render_reserved(Some(frames - skip));
+        for channel in 0..self.channels {
+            let plane = self.buf.chan_mut(channel);
+            for (frame, sample) in plane.iter_mut().enumerate() {
+                *sample = self.interleaved[(frame + skip) * self.channels + channel];
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Decoder for OpusDecoder {
+    fn try_new(params: &CodecParameters, _options: &DecoderOptions) -> Result<Self> {
+        let Some(layout) = params.channels else {
+            return decode_error("opus: missing channel layout");
+        };
+        let channels = layout.count();
+        let opus_channels = match channels {
+            1 => OpusChannels::Mono,
+            2 => OpusChannels::Stereo,
+            _ => return unsupported_error("opus: multistream layouts are not supported"),
+        };
+
+        let decoder = LibOpusDecoder::new(SampleRate::Hz48000, opus_channels)
+            .map_err(|err| Error::DecodeError(opus_error_message(err)))?;
+        let spec = SignalSpec::new(OPUS_SAMPLE_RATE, layout);
+
+        Ok(Self {
+            params: params.clone(),
+            decoder: ExclusiveDecoder(decoder),
+            channels,
+            pre_skip: params.delay.unwrap_or(0) as usize,
+            interleaved: vec![0.0; MAX_PACKET_FRAMES * channels],
+            buf: AudioBuffer::new(MAX_PACKET_FRAMES as u64, spec),
+        })
+    }
+
+    fn supported_codecs() -> &'static [CodecDescriptor] {
+        &[support_codec!(CODEC_TYPE_OPUS, "opus", "Opus")]
+    }
+
+    fn reset(&mut self) {
+        let _ = self.decoder.0.reset_state();
+        self.pre_skip = 0;
+    }
+
+    fn codec_params(&self) -> &CodecParameters {
+        &self.params
+    }
+
+    fn decode(&mut self, packet: &Packet) -> Result<AudioBufferRef<'_>> {
+        if let Err(err) = self.decode_inner(packet) {
+            self.buf.clear();
+            return Err(err);
+        }
+        Ok(self.buf.as_audio_buffer_ref())
+    }
+
+    fn finalize(&mut self) -> FinalizeResult {
+        FinalizeResult::default()
+    }
+
+    fn last_decoded(&self) -> AudioBufferRef<'_> {
+        self.buf.as_audio_buffer_ref()
+    }
+}
+
+fn opus_error_message(err: audiopus::Error) -> &'static str {
+    match err {
+        audiopus::Error::EmptyPacket => "opus: empty packet",
+        audiopus::Error::SignalsTooLarge => "opus: output buffer too large",
+        audiopus::Error::PacketTooLarge => "opus: packet too large",
+        _ => "opus: decode failed",
+    }
+}
@@ -0,0 +1,341 @@
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use symphonia::core::io::MediaSource;
+use tracing::{debug, warn};
+
+use crate::error::Result;
+use crate::source::{OpenedSource, SourceFactory, SourceSpec};
+
+// How long an open waits for a prefetch of the same source that is still in
+// flight before giving up on it and opening the source itself.
+const PREFETCH_WAIT: Duration = Duration::from_millis(1_500);
+
+// Opens the queued next source ahead of time and reads its first bytes, so the
+// decoder can move on to it without waiting for the network. Only one source is
+// kept warm; anything else is passed straight to the wrapped factory.
+pub(crate) struct PrefetchingSourceFactory {
+    inner: Arc<dyn SourceFactory>,
+    prebuffer_bytes: usize,
+    slot: Arc<PrefetchSlot>,
+}
+
+#[derive(Default)]
+struct PrefetchSlot {
+    entry: Mutex<Option<Prefetched>>,
+    ready: Condvar,
+}
+
+struct Prefetched {
+    spec: SourceSpec,
+    state: PrefetchState,
+}
+
+enum PrefetchState {
+    Pending,
+    Ready(OpenedSource),
+    Failed,
+}
+
+impl PrefetchingSourceFactory {
+    pub fn new(inner: Arc<dyn SourceFactory>, prebuffer_bytes: usize) -> Self {
+        Self {
+            inner,
+            prebuffer_bytes,
+            slot: Arc::new(PrefetchSlot::default()),
+        }
+    }
+
+    pub fn prefetch(&self, spec: Option<SourceSpec>) {
+        // Local files open instantly and need no warm-up.
+        let spec = spec.filter(|spec| spec.url().is_some());
+        {
+            let Ok(mut entry) = self.slot.entry.lock() else {
+                return;
+            };
+            if entry.as_ref().map(|entry| &entry.spec) == spec.as_ref() {
+                return;
+            }
+            *entry = spec.clone().map(|spec| Prefetched {
+                spec,
+                state: PrefetchState::Pending,
+            });
+        }
+        self.slot.ready.notify_all();
+
+        let Some(spec) = spec else {
+            return;
+        };
+        let inner = Arc::clone(&self.inner);
+        let slot = Arc::clone(&self.slot);
+        let prebuffer_bytes = self.prebuffer_bytes;
+        thread::spawn(move || {
+            let state = match warm(inner.as_ref(), &spec, prebuffer_bytes) {
+                Ok(source) => PrefetchState::Ready(source),
+                Err(err) => {
+                    warn!("failed to prefetch {}: {err}", spec.describe());
+                    PrefetchState::Failed
+                }
+            };
+            if let Ok(mut entry) = slot.entry.lock()
+                && let Some(current) = entry.as_mut()
+                && current.spec == spec
+                && matches!(current.state, PrefetchState::Pending)
+            {
+                debug!("prefetched {}", spec.describe());
+                current.state = state;
+            }
+            slot.ready.notify_all();
+        });
+    }
+
+    fn take_prefetched(&self, spec: &SourceSpec) -> Option<OpenedSource> {
+        let deadline = Instant::now() + PREFETCH_WAIT;
+        let mut entry = self.slot.entry.lock().ok()?;
+        loop {
+            let pending = match entry.as_ref() {
+                Some(current) if current.spec == *spec => {
+                    matches!(current.state, PrefetchState::Pending)
+                }
+                _ => return None,
+            };
+            if !pending {
+                break;
+            }
+            let Some(left) = deadline.checked_duration_since(Instant::now()) else {
+                // A stalled prefetch must not hold up the decoder; clearing the
+                // entry also makes its late result get dropped.
+                debug!("prefetch of {} timed out", spec.describe());
+                *entry = None;
+                return None;
+            };
+            entry = self.slot.ready.wait_timeout(entry, left).ok()?.0;
+        }
+
+        match entry.take()?.state {
+            PrefetchState::Ready(source) => Some(source),
+            PrefetchState::Pending | PrefetchState::Failed => None,
+        }
+    }
+}
+
+impl SourceFactory for PrefetchingSourceFactory {
+    fn open(&self, spec: &SourceSpec) -> Result<OpenedSource> {
+        match self.take_prefetched(spec) {
+            Some(source) => Ok(source),
+            None => self.inner.open(spec),
+        }
+    }
+}
+
+fn warm(
+    inner: &dyn SourceFactory,
+    spec: &SourceSpec,
+    prebuffer_bytes: usize,
+) -> Result<OpenedSource> {
+    let mut opened = inner.open(spec)?;
+    let mut prefix = vec![0; prebuffer_bytes];
+    let mut filled = 0;
+    while filled < prefix.len() {
+        let read = opened.media_source.read(&mut prefix[filled..])?;
+        if read == 0 {
+            break;
+        }
+        filled += read;
+    }
+    prefix.truncate(filled);
+
+    Ok(OpenedSource {
+        media_source: Box::new(WarmedSource {
+            inner_position: prefix.len() as u64,
+            prefix,
+            position: 0,
+            inner: opened.media_source,
+        }),
+        seekable: opened.seekable,
+    })
+}
+
+// Serves the prebuffered prefix from memory and only seeks the underlying
+// source when a read actually leaves it, so unseekable streams keep working.
+struct WarmedSource {
+    prefix: Vec<u8>,
+    position: u64,
+    inner: Box<dyn MediaSource>,
+    inner_position: u64,
+}
+
+impl Read for WarmedSource {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if let Some(buffered) = self.prefix.get(self.position as usize..)
+            && !buffered.is_empty()
+        {
+            let read = buffered.len().min(buf.len());
+            buf[..read].copy_from_slice(&buffered[..read]);
+            self.position += read as u64;
+            return Ok(read);
+        }
+
+        if self.inner_position != self.position {
+            self.inner_position = self.inner.seek(SeekFrom::Start(self.position))?;
+        }
+        let read = self.inner.read(buf)?;
+        self.inner_position += read as u64;
+        self.position = self.inner_position;
+        Ok(read)
+    }
+}
+
+impl Seek for WarmedSource {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => {
+                let len = self.inner.byte_len().ok_or_else(|| {
+                    std::io::Error::new(std::io::ErrorKind::Unsupported, "stream length unknown")
+                })?;
+                len.checked_add_signed(delta)
+            }
+        }
+        .ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "seek before start")
+        })?;
+
+        self.position = target;
+        Ok(target)
+    }
+}
+
+impl MediaSource for WarmedSource {
+    fn is_seekable(&self) -> bool {
+        self.inner.is_seekable()
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        self.inner.byte_len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex, mpsc};
+    use std::time::Instant;
+
+    use super::{PREFETCH_WAIT, PrefetchingSourceFactory};
+    use crate::error::Result;
+    use crate::source::{OpenedSource, SourceFactory, SourceSpec};
+
+    struct MemoryFactory {
+        bytes: Vec<u8>,
+        opens: AtomicUsize,
+    }
+
+    impl SourceFactory for MemoryFactory {
+        fn open(&self, _spec: &SourceSpec) -> Result<OpenedSource> {
+            self.opens.fetch_add(1, Ordering::SeqCst);
+            Ok(OpenedSource {
+                media_source: Box::new(Cursor::new(self.bytes.clone())),
+                seekable: true,
+            })
+        }
+    }
+
+    fn factory(len: usize) -> (Arc<MemoryFactory>, PrefetchingSourceFactory) {
+        let inner = Arc::new(MemoryFactory {
+            bytes: (0..len).map(|index| index as u8).collect(),
+            opens: AtomicUsize::new(0),
+        });
+        let prefetching = PrefetchingSourceFactory::new(inner.clone(), 64);
+        (inner, prefetching)
+    }
+
+    #[test]
+    fn prefetched_source_is_handed_out_once() {
+        let (inner, factory) = factory(1000);
+        let spec = SourceSpec::network("https://example.invalid/next.flac");
+        factory.prefetch(Some(spec.clone()));
+
+        let mut opened = factory.open(&spec).unwrap();
+        let mut bytes = Vec::new();
+        opened.media_source.read_to_end(&mut bytes).unwrap();
+        assert_eq!(bytes, inner.bytes);
+        assert_eq!(inner.opens.load(Ordering::SeqCst), 1);
+
+        factory.open(&spec).unwrap();
+        assert_eq!(inner.opens.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn stalled_prefetch_falls_back_to_a_direct_open() {
+        struct StallingFactory {
+            stall: Mutex<Option<mpsc::Receiver<()>>>,
+            opens: AtomicUsize,
+        }
+
+        impl SourceFactory for StallingFactory {
+            fn open(&self, _spec: &SourceSpec) -> Result<OpenedSource> {
+                self.opens.fetch_add(1, Ordering::SeqCst);
+                // The first open (the prefetch) hangs until the test ends.
+                let stall = self.stall.lock().unwrap().take();
+                if let Some(stall) = stall {
+                    let _ = stall.recv();
+                }
+                Ok(OpenedSource {
+                    media_source: Box::new(Cursor::new(vec![1, 2, 3])),
+                    seekable: true,
+                })
+            }
+        }
+
+        let (_release, stall) = mpsc::channel();
+        let inner = Arc::new(StallingFactory {
+            stall: Mutex::new(Some(stall)),
+            opens: AtomicUsize::new(0),
+        });
+        let factory = PrefetchingSourceFactory::new(inner.clone(), 64);
+        let spec = SourceSpec::network("https://example.invalid/next.flac");
+        factory.prefetch(Some(spec.clone()));
+        while inner.opens.load(Ordering::SeqCst) == 0 {
+            std::thread::yield_now();
+        }
+
+        let started = Instant::now();
+        let mut bytes = Vec::new();
+        factory
+            .open(&spec)
+            .unwrap()
+            .media_source
+            .read_to_end(&mut bytes)
+            .unwrap();
+        assert_eq!(bytes, [1, 2, 3]);
+        assert_eq!(inner.opens.load(Ordering::SeqCst), 2);
+        assert!(started.elapsed() < PREFETCH_WAIT * 2);
+    }
+
+    #[test]
+    fn seeks_cross_the_prebuffered_prefix() {
+        let (inner, factory) = factory(1000);
+        let spec = SourceSpec::network("https://example.invalid/next.flac");
+        factory.prefetch(Some(spec.clone()));
+        let mut source = factory.open(&spec).unwrap().media_source;
+
+        let mut buf = [0; 4];
+        source.seek(SeekFrom::Start(500)).unwrap();
+        source.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, inner.bytes[500..504]);
+
+        source.seek(SeekFrom::Start(62)).unwrap();
+        source.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, inner.bytes[62..66]);
+
+        source.seek(SeekFrom::End(-2)).unwrap();
+        let mut tail = Vec::new();
+        source.read_to_end(&mut tail).unwrap();
+        assert_eq!(tail, inner.bytes[998..]);
+    }
+}
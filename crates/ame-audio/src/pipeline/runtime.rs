@@ -1,38 +1,95 @@
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, RwLock, mpsc};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock, mpsc};
 use std::time::{Duration, Instant};
 
 use ringbuf::traits::Split;
 use tracing::warn;
 
+use crate::analysis::AudioAnalysis;
+use crate::analyzer::{AnalysisSettings, AnalyzerSpawnRequest, analysis_ring, spawn_analyzer};
 use crate::backend::{
-    BackendNotification, OpenStreamRequest, OutputSession, PlaybackDrainState, SampleRing,
-    backend_for_kind,
+    AudioDevice, BackendNotification, OpenStreamRequest, OutputBackend, OutputSession,
+    PlaybackDrainState, SampleRing, backend_for_kind,
 };
+use crate::cache::CachingSourceFactory;
 use crate::command::AudioCommand;
-use crate::config::{AudioConfig, RuntimeConfigPatch};
-use crate::decoder::{DecoderNotification, DecoderSpawnRequest, spawn_decoder};
+use crate::config::{
+    AudioConfig, DevicePolicy, MAX_CROSSFADE_MS, MAX_PLAYBACK_RATE, MAX_SLEEP_FADE_MS,
+    MIN_PLAYBACK_RATE, NormalizationMode, OutputMode, RuntimeConfigPatch,
+};
+use crate::decoder::{
+    DecoderNotification, DecoderOutput, DecoderSpawnRequest, LoopRegion, NextSourceSlot,
+    SkipRequest, SkipSlot, StreamProgress, spawn_decoder,
+};
+use crate::devices::DeviceWatcher;
+use crate::dsp::EqualizerControl;
+use crate::equalizer::EqualizerSettings;
 use crate::error::{AudioError, Result};
 use crate::event::AudioEvent;
+use crate::offline::{OfflineBackend, OfflineFaults};
+use crate::prefetch::PrefetchingSourceFactory;
 use crate::service::SubscriptionHub;
-use crate::snapshot::AudioSnapshot;
+use crate::snapshot::{AudioSnapshot, OutputFormat, TrackFormat};
 use crate::source::{DefaultSourceFactory, SeekTarget, SourceFactory, SourceSpec};
 use crate::{EngineState, OutputBackendKind};
 
+// The output has to run dry for a while before playback counts as stalled, so
+// the first callbacks of a fresh stream do not flicker into `Buffering`.
+const STALL_GRACE: Duration = Duration::from_millis(300);
+const STALL_RECOVERED_MS: u64 = 250;
+const BUFFER_HEALTH_INTERVAL: Duration = Duration::from_millis(250);
+// Longer than any packet the decoder hands over in one go.
+const LOOP_DECODE_MARGIN_MS: u64 = 250;
+
 pub(crate) struct AudioRuntime {
     config: AudioConfig,
     state: EngineState,
     command_rx: mpsc::Receiver<AudioCommand>,
     event_hub: SubscriptionHub<AudioEvent>,
     snapshot_hub: SubscriptionHub<AudioSnapshot>,
+    analysis_hub: SubscriptionHub<AudioAnalysis>,
     latest_snapshot: Arc<RwLock<AudioSnapshot>>,
-    source_factory: Arc<dyn SourceFactory>,
+    source_factory: Arc<PrefetchingSourceFactory>,
+    offline_faults: OfflineFaults,
     playback: Option<PlaybackPipeline>,
     current_source: Option<SourceSpec>,
+    next_source: Option<SourceSpec>,
+    next_duration_ms: Option<u64>,
+    next_committed: bool,
+    // A new choice made after the decoder committed `next_source`; it takes
+    // effect once the committed track has started playing.
+    pending_next: Option<Option<SourceSpec>>,
     duration_ms: u64,
+    normalization_gain_db: f32,
+    next_normalization_gain_db: Option<f32>,
+    format: Option<TrackFormat>,
+    next_format: Option<TrackFormat>,
+    // Format of a queued track the decoder left for a fresh bit-perfect stream,
+    // so it does not have to be probed again.
+    native_hint: Option<(SourceSpec, TrackFormat)>,
+    metadata_source: Option<SourceSpec>,
     position_base_ms: u64,
     playing_anchor: Option<Instant>,
+    buffer_health: Option<(u64, u64, Option<u64>)>,
+    buffer_health_at: Instant,
+    sleep_timer: Option<SleepTimer>,
+    fading: Option<PendingFade>,
+    skipping: Option<PendingSkip>,
+    pending_stream: Option<PendingStream>,
+    loop_region: Arc<LoopRegion>,
+    device_watcher: Option<DeviceWatcher>,
+    // Set while the preferred device is gone and playback runs on the default
+    // one instead; cleared when it shows up again.
+    preferred_device_missing: bool,
     snapshot_interval: Duration,
+    crossfade_ms: Arc<AtomicU64>,
+    playback_rate: Arc<AtomicU32>,
+    // Rate the position advances at. Lags `playback_rate` while samples
+    // stretched at the previous rate are still queued ahead of the output.
+    audible_rate: f32,
+    equalizer: Arc<EqualizerControl>,
+    analysis: Arc<AnalysisSettings>,
     decoder_notify_tx: mpsc::Sender<DecoderNotification>,
     decoder_notify_rx: mpsc::Receiver<DecoderNotification>,
     backend_notify_tx: mpsc::Sender<BackendNotification>,
@@ -43,19 +100,110 @@ pub(crate) struct AudioRuntime {
 struct PlaybackPipeline {
     cancel: Arc<AtomicBool>,
     decoder_thread: Option<std::thread::JoinHandle<()>>,
+    analyzer_thread: Option<std::thread::JoinHandle<()>>,
+    next_slot: NextSourceSlot,
+    skip_slot: SkipSlot,
     output: Box<dyn OutputSession>,
     backend: OutputBackendKind,
     device_name: String,
+    // The output runs in the track's own format, with software volume off.
+    bit_perfect: bool,
     stream_id: u64,
+    drain_state: Arc<PlaybackDrainState>,
+    progress: Arc<StreamProgress>,
+    samples_per_ms: u64,
+    ring_ms: u64,
+    underruns_seen: u64,
+    starved_since: Option<Instant>,
+    // Rate changes still waiting behind queued samples, each with the played
+    // sample count from which it is audible.
+    rate_changes: VecDeque<(u64, f32)>,
+}
+
+// A command held back until the fade-out it started has finished, along with
+// the commands that arrived meanwhile so they still run in order.
+struct PendingFade {
+    deadline: Instant,
+    command: AudioCommand,
+    queued: VecDeque<AudioCommand>,
+}
+
+// A track handed to the decoder to be crossfaded in, with the commands that
+// arrived before it started so they run after it.
+struct PendingSkip {
+    stream_id: u64,
+    source: SourceSpec,
+    start_ms: u64,
+    queued: VecDeque<AudioCommand>,
+}
+
+// A bit-perfect stream whose decoder is opening the track, so the output can be
+// opened at the format it reports. Commands wait behind it.
+struct PendingStream {
+    stream_id: u64,
+    source: SourceSpec,
+    start_ms: u64,
+    autoplay: bool,
+    cancel: Arc<AtomicBool>,
+    decoder_thread: std::thread::JoinHandle<()>,
+    output_tx: mpsc::Sender<DecoderOutput>,
+    next_slot: NextSourceSlot,
+    skip_slot: SkipSlot,
+    drain_state: Arc<PlaybackDrainState>,
+    progress: Arc<StreamProgress>,
+    queued: VecDeque<AudioCommand>,
+}
+
+impl PendingStream {
+    fn stop(self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        // The decoder gives up once it learns no output is coming.
+        drop(self.output_tx);
+        let _ = self.decoder_thread.join();
+    }
+}
+
+struct SleepTimer {
+    deadline: Instant,
+    fade_ms: u64,
+    // Stream the fade-out was started on; a new output starts at full level
+    // and needs the fade applied again.
+    faded_stream: Option<u64>,
 }
 
 impl PlaybackPipeline {
+    fn set_volume(&self, volume: f32) {
+        self.output
+            .set_volume(if self.bit_perfect { 1.0 } else { volume });
+    }
+
+    // Fades scale samples just like volume, so a bit-perfect stream is never
+    // ramped and starts and stops at full level.
+    fn fade_to(&self, level: f32, duration_ms: u64) {
+        if !self.bit_perfect {
+            self.output.fade_to(level, duration_ms);
+        }
+    }
+
+    fn force_fade_level(&self, level: f32) {
+        if !self.bit_perfect {
+            self.output.force_fade_level(level);
+        }
+    }
+
+    fn buffered_ms(&self) -> u64 {
+        self.drain_state.pending_samples() as u64 / self.samples_per_ms.max(1)
+    }
+
     fn stop(mut self) {
         self.cancel.store(true, Ordering::Relaxed);
         let _ = self.output.pause();
         if let Some(thread) = self.decoder_thread.take() {
             let _ = thread.join();
         }
+        if let Some(thread) = self.analyzer_thread.take() {
+            let _ = thread.join();
+        }
     }
 }
 
@@ -65,10 +213,17 @@ impl AudioRuntime {
         command_rx: mpsc::Receiver<AudioCommand>,
         event_hub: SubscriptionHub<AudioEvent>,
         snapshot_hub: SubscriptionHub<AudioSnapshot>,
+        analysis_hub: SubscriptionHub<AudioAnalysis>,
         latest_snapshot: Arc<RwLock<AudioSnapshot>>,
+        offline_faults: OfflineFaults,
     ) -> Result<Self> {
-        let source_factory = Arc::new(DefaultSourceFactory::new(config.network.clone())?);
+        let source_factory = build_source_factory(&config)?;
         let snapshot_interval = Duration::from_secs_f32(1.0 / config.snapshot_hz.max(1) as f32);
+        let crossfade_ms = Arc::new(AtomicU64::new(config.crossfade_ms.min(MAX_CROSSFADE_MS)));
+        let analysis = Arc::new(AnalysisSettings::new(
+            config.analysis_hz,
+            config.spectrum_bins,
+        ));
         let (decoder_notify_tx, decoder_notify_rx) = mpsc::channel();
         let (backend_notify_tx, backend_notify_rx) = mpsc::channel();
 
@@ -78,14 +233,40 @@ impl AudioRuntime {
             command_rx,
             event_hub,
             snapshot_hub,
+            analysis_hub,
             latest_snapshot,
             source_factory,
+            offline_faults,
             playback: None,
             current_source: None,
+            next_source: None,
+            next_duration_ms: None,
+            next_committed: false,
+            pending_next: None,
             duration_ms: 0,
+            normalization_gain_db: 0.0,
+            next_normalization_gain_db: None,
+            format: None,
+            next_format: None,
+            native_hint: None,
+            metadata_source: None,
             position_base_ms: 0,
             playing_anchor: None,
+            buffer_health: None,
+            buffer_health_at: Instant::now(),
+            sleep_timer: None,
+            fading: None,
+            skipping: None,
+            pending_stream: None,
+            loop_region: Arc::new(LoopRegion::default()),
+            device_watcher: None,
+            preferred_device_missing: false,
             snapshot_interval,
+            crossfade_ms,
+            playback_rate: Arc::new(AtomicU32::new(1.0f32.to_bits())),
+            audible_rate: 1.0,
+            equalizer: Arc::new(EqualizerControl::new(EqualizerSettings::default())),
+            analysis,
             decoder_notify_tx,
             decoder_notify_rx,
             backend_notify_tx,
@@ -95,24 +276,22 @@ impl AudioRuntime {
     }
 
     pub(crate) fn run(mut self) {
+        self.restart_device_watcher();
         self.publish_snapshot();
         let mut last_snapshot = Instant::now();
 
         loop {
             self.drain_decoder_notifications();
             self.drain_backend_notifications();
+            self.watch_buffer();
+            self.watch_sleep_timer();
+            self.watch_rate_changes();
+            self.watch_skip();
+            self.finish_fade();
 
             match self.command_rx.recv_timeout(Duration::from_millis(10)) {
                 Ok(AudioCommand::Shutdown) => break,
-                Ok(command) => {
-                    if let Err(err) = self.handle_command(command) {
-                        if matches!(err, AudioError::InvalidStateTransition { .. }) {
-                            self.event_hub.publish(AudioEvent::Error(err));
-                        } else {
-                            self.publish_error(err);
-                        }
-                    }
-                }
+                Ok(command) => self.dispatch(command),
                 Err(mpsc::RecvTimeoutError::Timeout) => {}
                 Err(mpsc::RecvTimeoutError::Disconnected) => break,
             }
@@ -123,47 +302,237 @@ impl AudioRuntime {
             }
         }
 
+        self.device_watcher = None;
         self.stop_playback();
         let _ = self.transition_to(EngineState::Stopped);
         self.publish_snapshot();
     }
 
+    // User-initiated changes get a short fade instead of a hard cut. The
+    // command runs from the loop once the fade is over, so snapshots and
+    // events keep flowing in the meantime. Switching to another track while
+    // playing overlaps the two instead, through the decoder's crossfade.
+    fn dispatch(&mut self, command: AudioCommand) {
+        if let Some(fade) = self.fading.as_mut() {
+            fade.queued.push_back(command);
+            return;
+        }
+        if let Some(skip) = self.skipping.as_mut() {
+            skip.queued.push_back(command);
+            return;
+        }
+        if let Some(stream) = self.pending_stream.as_mut() {
+            stream.queued.push_back(command);
+            return;
+        }
+        if self.fades_out(&command)
+            && let Some(playback) = &self.playback
+        {
+            let fade_ms = self.config.manual_fade_ms;
+            if let AudioCommand::Open {
+                source,
+                start_ms,
+                autoplay: true,
+            } = &command
+                // A committed gapless transition or a finished decoder leaves
+                // nothing to mix into.
+                && !self.next_committed
+                && !playback.drain_state.is_decoder_finished()
+                && let Ok(mut slot) = playback.skip_slot.lock()
+            {
+                *slot = Some(SkipRequest {
+                    source: source.clone(),
+                    start_ms: *start_ms,
+                    fade_ms,
+                });
+                self.skipping = Some(PendingSkip {
+                    stream_id: playback.stream_id,
+                    source: source.clone(),
+                    start_ms: *start_ms,
+                    queued: VecDeque::new(),
+                });
+                return;
+            }
+            playback.fade_to(0.0, fade_ms);
+            self.fading = Some(PendingFade {
+                deadline: Instant::now() + Duration::from_millis(fade_ms),
+                command,
+                queued: VecDeque::new(),
+            });
+            return;
+        }
+        self.execute(command);
+    }
+
+    fn fades_out(&self, command: &AudioCommand) -> bool {
+        if self.config.manual_fade_ms == 0
+            || self.state != EngineState::Playing
+            || self
+                .playback
+                .as_ref()
+                .is_some_and(|playback| playback.bit_perfect)
+        {
+            return false;
+        }
+        match command {
+            AudioCommand::Open { .. }
+            | AudioCommand::Pause
+            | AudioCommand::Stop
+            | AudioCommand::Seek(_)
+            | AudioCommand::SetLoop { .. }
+            | AudioCommand::SwitchBackend(_)
+            | AudioCommand::SwitchDevice(_) => true,
+            AudioCommand::UpdateConfig(patch) => requires_rebuild(patch),
+            _ => false,
+        }
+    }
+
+    fn finish_fade(&mut self) {
+        if self
+            .fading
+            .as_ref()
+            .is_none_or(|fade| Instant::now() < fade.deadline)
+        {
+            return;
+        }
+        let Some(fade) = self.fading.take() else {
+            return;
+        };
+        self.execute(fade.command);
+        // A queued command may start another fade; the rest line up behind it.
+        for command in fade.queued {
+            self.dispatch(command);
+        }
+    }
+
+    fn execute(&mut self, command: AudioCommand) {
+        if let Err(err) = self.handle_command(command) {
+            if matches!(err, AudioError::InvalidStateTransition { .. }) {
+                self.event_hub.publish(AudioEvent::Error(err));
+            } else {
+                self.publish_error(err);
+            }
+        }
+    }
+
     fn handle_command(&mut self, command: AudioCommand) -> Result<()> {
         match command {
             AudioCommand::Open {
                 source,
                 start_ms,
                 autoplay,
-            } => self.start_source(source, start_ms, autoplay),
+            } => {
+                self.loop_region.set(None);
+                self.start_source(source, start_ms, autoplay)
+            }
+            AudioCommand::SetNext(source) => self.set_next_source(source),
             AudioCommand::Play => self.resume(),
             AudioCommand::Pause => self.pause(),
             AudioCommand::Stop => {
                 self.stop_playback();
+                self.loop_region.set(None);
                 self.current_source = None;
+                self.next_source = None;
+                self.pending_next = None;
+                self.source_factory.prefetch(None);
                 self.duration_ms = 0;
+                self.normalization_gain_db = 0.0;
+                self.format = None;
                 self.position_base_ms = 0;
                 self.playing_anchor = None;
                 self.transition_to(EngineState::Stopped)
             }
             AudioCommand::Seek(target) => self.seek(target),
+            AudioCommand::SetLoop { start_ms, end_ms } => self.set_loop(start_ms, end_ms),
+            AudioCommand::ClearLoop => {
+                self.clear_loop();
+                Ok(())
+            }
             AudioCommand::SetVolume(volume) => {
                 self.config.volume = volume.clamp(0.0, 1.0);
                 if let Some(playback) = &self.playback {
-                    playback.output.set_volume(self.config.volume);
+                    playback.set_volume(self.config.volume);
                 }
                 self.publish_snapshot();
                 Ok(())
             }
+            AudioCommand::SetPlaybackRate(rate) => {
+                self.set_playback_rate(rate);
+                Ok(())
+            }
+            AudioCommand::StartSleepTimer { after_ms, fade_ms } => {
+                self.start_sleep_timer(after_ms, fade_ms);
+                Ok(())
+            }
+            AudioCommand::CancelSleepTimer => {
+                self.cancel_sleep_timer();
+                Ok(())
+            }
             AudioCommand::SwitchBackend(kind) => self.switch_backend(kind),
             AudioCommand::SwitchDevice(device) => {
                 self.config.preferred_device = device;
+                self.preferred_device_missing = false;
                 self.restart_current_source()
             }
+            AudioCommand::SetEqualizer(settings) => {
+                self.equalizer.set(settings);
+                Ok(())
+            }
             AudioCommand::UpdateConfig(patch) => self.update_config(patch),
             AudioCommand::Shutdown => Ok(()),
         }
     }
 
+    // The decoder has started mixing in the pending skip, which now plays from
+    // its start position.
+    fn finish_skip(&mut self, duration_ms: Option<u64>, format: TrackFormat) {
+        let Some(skip) = self.skipping.take() else {
+            return;
+        };
+        self.loop_region.set(None);
+        self.current_source = Some(skip.source);
+        self.duration_ms = duration_ms.unwrap_or(0);
+        self.normalization_gain_db = 0.0;
+        self.format = Some(format);
+        self.position_base_ms = skip.start_ms;
+        self.playing_anchor = Some(Instant::now());
+        // The queued samples were dropped, so the current rate is audible now.
+        self.audible_rate = self.playback_rate();
+        if let Some(playback) = self.playback.as_mut() {
+            playback.rate_changes.clear();
+        }
+        self.publish_snapshot();
+        for command in skip.queued {
+            self.dispatch(command);
+        }
+    }
+
+    // Opens the pending skip on a fresh stream when the decoder could not mix
+    // it in, or the stream it was handed to has gone.
+    fn abandon_skip(&mut self) {
+        let Some(skip) = self.skipping.take() else {
+            return;
+        };
+        self.execute(AudioCommand::Open {
+            source: skip.source,
+            start_ms: skip.start_ms,
+            autoplay: true,
+        });
+        for command in skip.queued {
+            self.dispatch(command);
+        }
+    }
+
+    fn watch_skip(&mut self) {
+        let Some(skip) = &self.skipping else {
+            return;
+        };
+        let stream_id = self.playback.as_ref().map(|playback| playback.stream_id);
+        if stream_id != Some(skip.stream_id) {
+            self.abandon_skip();
+        }
+    }
+
     fn start_source(&mut self, source: SourceSpec, start_ms: u64, autoplay: bool) -> Result<()> {
         self.transition_to(EngineState::Loading)?;
         self.stop_playback();
@@ -171,58 +540,187 @@ impl AudioRuntime {
         let stream_id = self.next_stream_id;
         self.next_stream_id = self.next_stream_id.wrapping_add(1);
 
-        let backend = backend_for_kind(self.config.backend)?;
+        // A fresh decoder has nothing committed, so a deferred choice applies
+        // right away.
+        if let Some(next) = self.pending_next.take() {
+            self.next_source = next;
+        }
+        let next_slot = Arc::new(Mutex::new(self.next_source.clone()));
+        let skip_slot = SkipSlot::default();
+        self.next_duration_ms = None;
+        self.next_committed = false;
+        self.normalization_gain_db = 0.0;
+        self.next_normalization_gain_db = None;
+        let known_format = self.known_format(&source);
+        self.format = None;
+        self.next_format = None;
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        let drain_state = Arc::new(PlaybackDrainState::default());
+        let progress = Arc::new(StreamProgress::default());
+        let (output_tx, output_rx) = mpsc::channel();
+        let decoder = spawn_decoder(DecoderSpawnRequest {
+            source_factory: self.source_factory.clone(),
+            source_spec: source.clone(),
+            next_source: Arc::clone(&next_slot),
+            skip: Arc::clone(&skip_slot),
+            output: output_rx,
+            start_ms,
+            split_on_rate_change: self.config.output_mode == OutputMode::BitPerfect,
+            quality: self.config.resample_quality,
+            channel_mix: self.config.channel_mix,
+            normalization: self.config.normalization,
+            target_lufs: self.config.target_lufs,
+            crossfade_ms: Arc::clone(&self.crossfade_ms),
+            playback_rate: Arc::clone(&self.playback_rate),
+            equalizer: Arc::clone(&self.equalizer),
+            cancel: Arc::clone(&cancel),
+            notification_tx: self.decoder_notify_tx.clone(),
+            drain_state: Arc::clone(&drain_state),
+            progress: Arc::clone(&progress),
+            loop_region: Arc::clone(&self.loop_region),
+        });
+        let stream = PendingStream {
+            stream_id,
+            source,
+            start_ms,
+            autoplay,
+            cancel,
+            decoder_thread: decoder,
+            output_tx,
+            next_slot,
+            skip_slot,
+            drain_state,
+            progress,
+            queued: VecDeque::new(),
+        };
+
+        match self.config.output_mode {
+            OutputMode::Resampled => self.open_output(stream, None),
+            OutputMode::BitPerfect if known_format.is_some() => {
+                self.open_output(stream, known_format)
+            }
+            // The output waits for the decoder to report the track's format.
+            OutputMode::BitPerfect => {
+                self.pending_stream = Some(stream);
+                self.publish_snapshot();
+                Ok(())
+            }
+        }
+    }
+
+    // Opens the output for a stream whose decoder is already running and
+    // hands it the ring to fill.
+    fn open_output(
+        &mut self,
+        stream: PendingStream,
+        native_format: Option<TrackFormat>,
+    ) -> Result<()> {
+        let backend = match self.output_backend() {
+            Ok(backend) => backend,
+            Err(err) => {
+                stream.stop();
+                return Err(err);
+            }
+        };
         let ring_capacity = 48_000 * 2 * 2;
         let ring = SampleRing::new(ring_capacity);
         let (producer, consumer) = ring.split();
-        let drain_state = Arc::new(PlaybackDrainState::default());
+        let (analysis_producer, analysis_consumer) = analysis_ring();
 
-        let output = backend.open_stream(OpenStreamRequest {
-            stream_id,
-            preferred_device: self.config.preferred_device.clone(),
+        let opened = backend.open_stream(OpenStreamRequest {
+            stream_id: stream.stream_id,
+            preferred_device: self
+                .config
+                .preferred_device
+                .clone()
+                .filter(|_| !self.preferred_device_missing),
+            native_format: native_format.clone(),
             volume: self.config.volume,
+            fade_in_ms: if stream.autoplay {
+                self.config.manual_fade_ms
+            } else {
+                0
+            },
             consumer,
+            analysis: Some(analysis_producer),
             event_tx: self.backend_notify_tx.clone(),
-            drain_state: Arc::clone(&drain_state),
-        })?;
+            drain_state: Arc::clone(&stream.drain_state),
+        });
+        let output = match opened {
+            Ok(output) => output,
+            Err(err) => {
+                stream.stop();
+                return Err(err);
+            }
+        };
+        let bit_perfect = native_format.is_some_and(|format| {
+            format.sample_rate == output.sample_rate() && format.channels == output.channels()
+        });
+        if bit_perfect {
+            output.set_volume(1.0);
+            output.force_fade_level(1.0);
+        }
 
-        if autoplay {
-            output.play()?;
+        let started = if stream.autoplay {
+            output.play()
         } else {
-            output.pause()?;
+            output.pause()
+        };
+        if let Err(err) = started {
+            stream.stop();
+            return Err(err);
         }
 
         let sample_rate = output.sample_rate();
         let channels = output.channels() as usize;
-        let cancel = Arc::new(AtomicBool::new(false));
-
-        let decoder = spawn_decoder(DecoderSpawnRequest {
-            source_factory: Arc::clone(&self.source_factory),
-            source_spec: source.clone(),
-            target_sample_rate: sample_rate,
-            target_channels: channels,
-            start_ms,
-            quality: self.config.resample_quality,
-            cancel: Arc::clone(&cancel),
-            notification_tx: self.decoder_notify_tx.clone(),
+        let _ = stream.output_tx.send(DecoderOutput {
             producer,
-            drain_state,
+            sample_rate,
+            channels,
+            bit_perfect,
+        });
+
+        let analyzer = spawn_analyzer(AnalyzerSpawnRequest {
+            consumer: analysis_consumer,
+            sample_rate,
+            channels,
+            settings: Arc::clone(&self.analysis),
+            hub: self.analysis_hub.clone(),
+            cancel: Arc::clone(&stream.cancel),
         });
 
         let device_name = output.device_name();
         self.playback = Some(PlaybackPipeline {
-            cancel,
-            decoder_thread: Some(decoder),
+            cancel: stream.cancel,
+            decoder_thread: Some(stream.decoder_thread),
+            analyzer_thread: Some(analyzer),
+            next_slot: stream.next_slot,
+            skip_slot: stream.skip_slot,
             output,
             backend: self.config.backend,
             device_name: device_name.clone(),
-            stream_id,
+            bit_perfect,
+            stream_id: stream.stream_id,
+            drain_state: stream.drain_state,
+            progress: stream.progress,
+            samples_per_ms: sample_rate as u64 * channels as u64 / 1000,
+            ring_ms: (ring_capacity * 1000 / (sample_rate as usize * channels).max(1)) as u64,
+            underruns_seen: 0,
+            starved_since: None,
+            rate_changes: VecDeque::new(),
         });
-        self.current_source = Some(source);
-        self.position_base_ms = start_ms;
-        self.playing_anchor = if autoplay { Some(Instant::now()) } else { None };
+        self.buffer_health = None;
+        self.current_source = Some(stream.source);
+        self.position_base_ms = stream.start_ms;
+        self.playing_anchor = if stream.autoplay {
+            Some(Instant::now())
+        } else {
+            None
+        };
+        self.audible_rate = self.playback_rate();
 
-        self.transition_to(if autoplay {
+        self.transition_to(if stream.autoplay {
             EngineState::Playing
         } else {
             EngineState::Ready
@@ -237,9 +735,79 @@ impl AudioRuntime {
         Ok(())
     }
 
+    // The decoder of a bit-perfect stream has opened its track.
+    fn open_pending_output(&mut self, format: TrackFormat) {
+        let Some(mut stream) = self.pending_stream.take() else {
+            return;
+        };
+        let queued = std::mem::take(&mut stream.queued);
+        if let Err(err) = self.open_output(stream, Some(format)) {
+            self.publish_error(err);
+        }
+        for command in queued {
+            self.dispatch(command);
+        }
+    }
+
+    // Drops a stream whose decoder failed before the output was opened, and
+    // runs what was held back behind it.
+    fn abandon_pending_stream(&mut self) {
+        let Some(mut stream) = self.pending_stream.take() else {
+            return;
+        };
+        let queued = std::mem::take(&mut stream.queued);
+        stream.stop();
+        for command in queued {
+            self.dispatch(command);
+        }
+    }
+
+    // Format a bit-perfect output should match when it is already known, so
+    // restarts and seeks do not have to wait for the decoder to report it.
+    fn known_format(&mut self, source: &SourceSpec) -> Option<TrackFormat> {
+        if let Some((hinted, format)) = self.native_hint.take()
+            && &hinted == source
+        {
+            return Some(format);
+        }
+        if self.current_source.as_ref() == Some(source) {
+            return self.format.clone();
+        }
+        None
+    }
+
+    fn set_next_source(&mut self, source: Option<SourceSpec>) -> Result<()> {
+        if self.next_committed {
+            // The decoder is already feeding the previous choice into the ring;
+            // it plays out and the new choice follows it.
+            let pending = (self.next_source != source).then_some(source);
+            if pending != self.pending_next {
+                self.source_factory.prefetch(pending.clone().flatten());
+                self.pending_next = pending;
+            }
+            return Ok(());
+        }
+        if self.next_source == source {
+            return Ok(());
+        }
+
+        self.next_source = source;
+        self.source_factory.prefetch(self.next_source.clone());
+        self.update_next_slot();
+        Ok(())
+    }
+
+    fn update_next_slot(&self) {
+        if let Some(playback) = &self.playback
+            && let Ok(mut slot) = playback.next_slot.lock()
+        {
+            *slot = self.next_source.clone();
+        }
+    }
+
     fn pause(&mut self) -> Result<()> {
         match self.state {
-            EngineState::Playing => {
+            EngineState::Playing | EngineState::Buffering => {
                 if let Some(playback) = &self.playback {
                     playback.output.pause()?;
                 }
@@ -258,9 +826,18 @@ impl AudioRuntime {
 
     fn resume(&mut self) -> Result<()> {
         match self.state {
+            // Playback picks up by itself once enough is buffered.
+            EngineState::Buffering => Ok(()),
             EngineState::Ready | EngineState::Paused => {
                 if let Some(playback) = &self.playback {
+                    if self.config.manual_fade_ms > 0 {
+                        playback.force_fade_level(0.0);
+                    }
                     playback.output.play()?;
+                    playback.fade_to(1.0, self.config.manual_fade_ms);
+                }
+                if let Some(timer) = self.sleep_timer.as_mut() {
+                    timer.faded_stream = None;
                 }
                 self.playing_anchor = Some(Instant::now());
                 self.transition_to(EngineState::Playing)?;
@@ -282,8 +859,10 @@ impl AudioRuntime {
                 reason: "no source loaded".into(),
             })?;
 
-        let target_ms = target.to_millis();
-        let was_playing = self.state == EngineState::Playing;
+        let target_ms = target
+            .resolve(self.current_position_ms(), self.duration_ms)
+            .ok_or(AudioError::UnsupportedSeek)?;
+        let was_playing = matches!(self.state, EngineState::Playing | EngineState::Buffering);
         match self.start_source(source.clone(), target_ms, was_playing) {
             Ok(()) => {}
             Err(AudioError::UnsupportedSeek) if target_ms > 0 => {
@@ -300,30 +879,270 @@ impl AudioRuntime {
         Ok(())
     }
 
+    // The decoder reads the region live, so a playhead short of `end_ms`
+    // keeps playing untouched. The decoder runs ahead of the playhead by up to
+    // the length of the ring, though; once it is past `end_ms` the current
+    // position is decoded afresh with the loop in place. Starting at or beyond
+    // the end jumps straight to the loop start.
+    fn set_loop(&mut self, start_ms: u64, end_ms: u64) -> Result<()> {
+        if end_ms <= start_ms {
+            return Err(AudioError::ConfigInvalid {
+                reason: format!("loop end {end_ms} ms is not after its start {start_ms} ms"),
+            });
+        }
+        if self.duration_ms > 0 && end_ms > self.duration_ms {
+            return Err(AudioError::ConfigInvalid {
+                reason: format!(
+                    "loop end {end_ms} ms is past the end of the track at {} ms",
+                    self.duration_ms
+                ),
+            });
+        }
+        let position = self.current_position_ms();
+        self.loop_region.set(Some((start_ms, end_ms)));
+        let Some(playback) = self
+            .playback
+            .as_ref()
+            .filter(|_| self.current_source.is_some())
+        else {
+            self.publish_snapshot();
+            return Ok(());
+        };
+        // A packet decoded while the region was being set may still run past
+        // its end.
+        let decoded_ms = playback.progress.decoded_ms() + LOOP_DECODE_MARGIN_MS;
+        if position < end_ms && decoded_ms < end_ms {
+            self.publish_snapshot();
+            return Ok(());
+        }
+        let target_ms = if position < end_ms {
+            position
+        } else {
+            start_ms
+        };
+        self.seek(SeekTarget::ms(target_ms))
+    }
+
+    fn clear_loop(&mut self) {
+        if self.loop_region.get().is_none() {
+            return;
+        }
+        // Keep the wrapped position instead of jumping past the loop end.
+        self.position_base_ms = self.current_position_ms();
+        if self.playing_anchor.is_some() {
+            self.playing_anchor = Some(Instant::now());
+        }
+        self.loop_region.set(None);
+        self.publish_snapshot();
+    }
+
+    fn set_playback_rate(&mut self, rate: f32) {
+        let rate = if rate.is_finite() {
+            rate.clamp(MIN_PLAYBACK_RATE, MAX_PLAYBACK_RATE)
+        } else {
+            1.0
+        };
+        self.playback_rate.store(rate.to_bits(), Ordering::Relaxed);
+        // The decoder picks the new rate up straight away, but what is already
+        // in the ring plays out at the old one first.
+        match self.playback.as_mut() {
+            Some(playback) => {
+                let audible_from = playback.drain_state.queued_total();
+                playback.rate_changes.push_back((audible_from, rate));
+                self.watch_rate_changes();
+            }
+            None => self.audible_rate = rate,
+        }
+        self.publish_snapshot();
+    }
+
+    // Re-anchors the position once the output reaches the first sample of a
+    // rate change, so time already played keeps the old rate.
+    fn watch_rate_changes(&mut self) {
+        let Some(playback) = self.playback.as_mut() else {
+            return;
+        };
+        let played = playback.drain_state.played_total();
+        let mut reached = None;
+        while let Some(&(audible_from, rate)) = playback.rate_changes.front()
+            && audible_from <= played
+        {
+            playback.rate_changes.pop_front();
+            reached = Some(rate);
+        }
+        let Some(rate) = reached else {
+            return;
+        };
+        if self.playing_anchor.is_some() {
+            self.position_base_ms = self.current_position_ms();
+            self.playing_anchor = Some(Instant::now());
+        }
+        self.audible_rate = rate;
+    }
+
+    fn playback_rate(&self) -> f32 {
+        f32::from_bits(self.playback_rate.load(Ordering::Relaxed))
+    }
+
+    fn start_sleep_timer(&mut self, after_ms: u64, fade_ms: u64) {
+        self.cancel_sleep_timer();
+        self.sleep_timer = Some(SleepTimer {
+            deadline: Instant::now() + Duration::from_millis(after_ms),
+            fade_ms: fade_ms.min(MAX_SLEEP_FADE_MS),
+            faded_stream: None,
+        });
+        self.publish_snapshot();
+    }
+
+    fn cancel_sleep_timer(&mut self) {
+        let Some(timer) = self.sleep_timer.take() else {
+            return;
+        };
+        if let Some(playback) = &self.playback
+            && timer.faded_stream == Some(playback.stream_id)
+        {
+            playback.fade_to(1.0, self.config.manual_fade_ms);
+        }
+        self.publish_snapshot();
+    }
+
+    // Starts the fade once the deadline is within `fade_ms` and pauses when it
+    // passes. The fade is re-applied after anything that rebuilt the output.
+    fn watch_sleep_timer(&mut self) {
+        let Some(timer) = self.sleep_timer.as_mut() else {
+            return;
+        };
+        let remaining = timer.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            self.sleep_timer = None;
+            if matches!(self.state, EngineState::Playing | EngineState::Buffering)
+                && let Err(err) = self.pause()
+            {
+                self.publish_error(err);
+            }
+            self.event_hub.publish(AudioEvent::SleepTimerFired);
+            self.publish_snapshot();
+            return;
+        }
+
+        let remaining_ms = remaining.as_millis() as u64;
+        if self.state == EngineState::Playing
+            && remaining_ms <= timer.fade_ms
+            && let Some(playback) = &self.playback
+            && timer.faded_stream != Some(playback.stream_id)
+        {
+            playback.fade_to(0.0, remaining_ms);
+            timer.faded_stream = Some(playback.stream_id);
+        }
+    }
+
     fn switch_backend(&mut self, kind: OutputBackendKind) -> Result<()> {
         self.config.backend = kind;
+        self.restart_device_watcher();
         self.restart_current_source()
     }
 
+    fn output_backend(&self) -> Result<Box<dyn OutputBackend>> {
+        match self.config.backend {
+            OutputBackendKind::Null | OutputBackendKind::WavFile => {
+                Ok(Box::new(OfflineBackend::with_faults(
+                    self.config.backend,
+                    self.config.offline_output.clone(),
+                    self.offline_faults.clone(),
+                )))
+            }
+            kind => backend_for_kind(kind, &self.config.offline_output),
+        }
+    }
+
+    fn restart_device_watcher(&mut self) {
+        self.device_watcher = None;
+        match self.output_backend() {
+            Ok(backend) => {
+                self.device_watcher = Some(DeviceWatcher::spawn(
+                    backend,
+                    self.backend_notify_tx.clone(),
+                ));
+            }
+            Err(err) => warn!("failed to watch output devices: {err}"),
+        }
+    }
+
+    fn apply_device_list(
+        &mut self,
+        devices: &[AudioDevice],
+        default_device: Option<String>,
+    ) -> Result<()> {
+        if let Some(preferred) = self.config.preferred_device.as_deref() {
+            let present = devices.iter().any(|device| device.name == preferred);
+            match self.config.device_policy {
+                DevicePolicy::StickToDevice => {
+                    let returned = present && self.preferred_device_missing;
+                    self.preferred_device_missing = !present;
+                    return if returned && self.has_live_output() {
+                        self.restart_current_source()
+                    } else {
+                        Ok(())
+                    };
+                }
+                DevicePolicy::FollowSystemDefault if present => return Ok(()),
+                DevicePolicy::FollowSystemDefault => self.config.preferred_device = None,
+            }
+        }
+
+        if self.config.device_policy != DevicePolicy::FollowSystemDefault {
+            return Ok(());
+        }
+        let moved = match (&self.playback, default_device) {
+            (Some(playback), Some(default_device)) => playback.device_name != default_device,
+            _ => false,
+        };
+        if moved && self.has_live_output() {
+            self.restart_current_source()
+        } else {
+            Ok(())
+        }
+    }
+
+    // Whether an open output is worth moving to another device right away;
+    // otherwise the next stream simply opens on the right one.
+    fn has_live_output(&self) -> bool {
+        self.playback.is_some()
+            && matches!(
+                self.state,
+                EngineState::Playing | EngineState::Buffering | EngineState::Paused
+            )
+    }
+
     fn update_config(&mut self, patch: RuntimeConfigPatch) -> Result<()> {
-        let requires_rebuild = patch.backend.is_some()
-            || patch.preferred_device.is_some()
-            || patch.resample_quality.is_some()
-            || patch.network.is_some();
+        let requires_rebuild = requires_rebuild(&patch);
 
-        let rebuild_factory = patch.network.is_some();
+        let rebuild_factory = patch.network.is_some() || patch.stream_cache.is_some();
+        let rewatch_devices = patch.backend.is_some() || patch.offline_output.is_some();
+        if patch.preferred_device.is_some() {
+            self.preferred_device_missing = false;
+        }
         self.config.apply_patch(patch);
+        self.crossfade_ms
+            .store(self.config.crossfade_ms, Ordering::Relaxed);
+        self.analysis
+            .update(self.config.analysis_hz, self.config.spectrum_bins);
         self.snapshot_interval =
             Duration::from_secs_f32(1.0 / self.config.snapshot_hz.max(1) as f32);
 
+        if rewatch_devices {
+            self.restart_device_watcher();
+        }
+
         if rebuild_factory {
-            self.source_factory = Arc::new(DefaultSourceFactory::new(self.config.network.clone())?);
+            self.source_factory = build_source_factory(&self.config)?;
+            self.source_factory.prefetch(self.next_source.clone());
         }
 
         if requires_rebuild {
             self.restart_current_source()?;
         } else if let Some(playback) = &self.playback {
-            playback.output.set_volume(self.config.volume);
+            playback.set_volume(self.config.volume);
         }
 
         self.publish_snapshot();
@@ -335,7 +1154,7 @@ impl AudioRuntime {
             return Ok(());
         };
 
-        let playing = self.state == EngineState::Playing;
+        let playing = matches!(self.state, EngineState::Playing | EngineState::Buffering);
         let position = self.current_position_ms();
 
         self.start_source(source, position, playing)?;
@@ -350,6 +1169,9 @@ impl AudioRuntime {
         if let Some(playback) = self.playback.take() {
             playback.stop();
         }
+        if let Some(stream) = self.pending_stream.take() {
+            stream.stop();
+        }
     }
 
     fn drain_decoder_notifications(&mut self) {
@@ -358,7 +1180,87 @@ impl AudioRuntime {
                 Ok(DecoderNotification::Duration(duration_ms)) => {
                     self.duration_ms = duration_ms;
                 }
-                Ok(DecoderNotification::Error(err)) => self.publish_error(err),
+                Ok(DecoderNotification::Format { source, format }) => {
+                    self.format = Some(format.clone());
+                    if self
+                        .pending_stream
+                        .as_ref()
+                        .is_some_and(|stream| stream.source == source)
+                    {
+                        self.open_pending_output(format);
+                    }
+                }
+                // Seeks and restarts reopen the same file; its tags only need
+                // to go out once.
+                Ok(DecoderNotification::Metadata { source, metadata }) => {
+                    if self.metadata_source.as_ref() == Some(&source) {
+                        continue;
+                    }
+                    self.metadata_source = Some(source.clone());
+                    self.event_hub.publish(AudioEvent::MetadataReady {
+                        source,
+                        metadata: Arc::new(metadata),
+                    });
+                }
+                Ok(DecoderNotification::NormalizationGain { source, gain_db }) => {
+                    // Once the decoder has moved on to the queued track its gain
+                    // only applies after the boundary has been played out.
+                    if self.next_committed && self.next_source.as_ref() == Some(&source) {
+                        self.next_normalization_gain_db = Some(gain_db);
+                    } else if self.current_source.as_ref() == Some(&source) {
+                        self.normalization_gain_db = gain_db;
+                    }
+                }
+                Ok(DecoderNotification::NextTrackStarted {
+                    source,
+                    duration_ms,
+                    format,
+                }) => {
+                    if self.next_source.as_ref() == Some(&source) {
+                        self.next_duration_ms = duration_ms;
+                        self.next_format = Some(format);
+                        self.next_committed = true;
+                    }
+                }
+                Ok(DecoderNotification::Skipped {
+                    source,
+                    duration_ms,
+                    format,
+                }) => {
+                    if self
+                        .skipping
+                        .as_ref()
+                        .is_some_and(|skip| skip.source == source)
+                    {
+                        self.finish_skip(duration_ms, format);
+                    }
+                }
+                Ok(DecoderNotification::SkipFailed { source }) => {
+                    if self
+                        .skipping
+                        .as_ref()
+                        .is_some_and(|skip| skip.source == source)
+                    {
+                        self.abandon_skip();
+                    }
+                }
+                Ok(DecoderNotification::NextTrackFailed { source, error }) => {
+                    warn!("failed to pre-open {}: {error}", source.describe());
+                    if self.next_source.as_ref() == Some(&source) {
+                        self.next_source = None;
+                    }
+                    self.event_hub.publish(AudioEvent::Error(error));
+                }
+                Ok(DecoderNotification::NextTrackRateChanged { source, format }) => {
+                    if self.next_source.as_ref() == Some(&source) {
+                        self.native_hint = Some((source, format));
+                    }
+                }
+                Ok(DecoderNotification::Error(err)) => {
+                    self.publish_error(err);
+                    self.abandon_skip();
+                    self.abandon_pending_stream();
+                }
                 Err(mpsc::TryRecvError::Empty) => break,
                 Err(mpsc::TryRecvError::Disconnected) => break,
             }
@@ -376,7 +1278,7 @@ impl AudioRuntime {
                         continue;
                     }
 
-                    if self.state != EngineState::Playing {
+                    if !matches!(self.state, EngineState::Playing | EngineState::Buffering) {
                         warn!(
                             "ignore backend stream error while state={:?}: {}",
                             self.state, reason
@@ -402,14 +1304,75 @@ impl AudioRuntime {
                     if self.state != EngineState::Playing {
                         continue;
                     }
+                    if self.skipping.is_some() {
+                        self.abandon_skip();
+                        continue;
+                    }
+
+                    // The next source was queued too late for the decoder to pick
+                    // it up, so fall back to opening it on a fresh pipeline.
+                    if let Some(next) = self.next_source.take() {
+                        self.event_hub.publish(AudioEvent::TrackEnded {
+                            next: Some(next.clone()),
+                        });
+                        self.stop_playback();
+                        self.loop_region.set(None);
+                        if let Err(err) = self.start_source(next, 0, true) {
+                            self.publish_error(err);
+                        }
+                        continue;
+                    }
 
                     self.position_base_ms = self.duration_ms;
                     self.playing_anchor = None;
-                    self.event_hub.publish(AudioEvent::TrackEnded);
+                    self.event_hub
+                        .publish(AudioEvent::TrackEnded { next: None });
                     if let Err(err) = self.transition_to(EngineState::Ready) {
                         self.publish_error(err);
                     }
                 }
+                Ok(BackendNotification::TrackBoundary { stream_id }) => {
+                    let Some(playback) = self.playback.as_ref() else {
+                        continue;
+                    };
+                    if playback.stream_id != stream_id || !self.next_committed {
+                        continue;
+                    }
+
+                    self.current_source = self.next_source.take();
+                    self.duration_ms = self.next_duration_ms.take().unwrap_or(0);
+                    self.normalization_gain_db =
+                        self.next_normalization_gain_db.take().unwrap_or(0.0);
+                    self.format = self.next_format.take();
+                    self.next_committed = false;
+                    if let Some(next) = self.pending_next.take() {
+                        self.next_source = next;
+                        self.update_next_slot();
+                    }
+                    self.loop_region.set(None);
+                    self.position_base_ms = 0;
+                    self.playing_anchor = (self.state == EngineState::Playing).then(Instant::now);
+                    self.event_hub.publish(AudioEvent::TrackEnded {
+                        next: self.current_source.clone(),
+                    });
+                    self.publish_snapshot();
+                }
+                Ok(BackendNotification::DevicesChanged {
+                    backend,
+                    devices,
+                    default_device,
+                }) => {
+                    // Left over from a watcher of the previous backend.
+                    if backend != self.config.backend {
+                        continue;
+                    }
+
+                    self.event_hub
+                        .publish(AudioEvent::DevicesChanged(devices.clone()));
+                    if let Err(err) = self.apply_device_list(&devices, default_device) {
+                        self.publish_error(err);
+                    }
+                }
                 Err(mpsc::TryRecvError::Empty) => break,
                 Err(mpsc::TryRecvError::Disconnected) => break,
             }
@@ -425,7 +1388,15 @@ impl AudioRuntime {
             return Err(AudioError::DeviceLost { reason });
         }
 
-        self.config.preferred_device = None;
+        match self.config.device_policy {
+            DevicePolicy::StickToDevice => {
+                self.preferred_device_missing = self.config.preferred_device.is_some();
+            }
+            DevicePolicy::FollowSystemDefault => self.config.preferred_device = None,
+        }
+        if let Some(watcher) = &self.device_watcher {
+            watcher.refresh();
+        }
         match self.restart_current_source() {
             Ok(()) => Ok(()),
             Err(err) => {
@@ -475,19 +1446,131 @@ impl AudioRuntime {
         self.publish_snapshot();
     }
 
+    // Pauses the output once it has been starved for a while and resumes it
+    // when the decoder has refilled `rebuffer_ms` worth of audio.
+    fn watch_buffer(&mut self) {
+        let Some(playback) = self.playback.as_mut() else {
+            return;
+        };
+        let buffered_ms = playback.buffered_ms();
+        let finished = playback.drain_state.is_decoder_finished();
+        let underruns = playback.drain_state.underruns();
+        let playing = self.state == EngineState::Playing;
+        if playing && underruns != playback.underruns_seen {
+            playback.starved_since.get_or_insert_with(Instant::now);
+        } else if !playing || finished || buffered_ms >= STALL_RECOVERED_MS {
+            playback.starved_since = None;
+        }
+        playback.underruns_seen = underruns;
+        let stalled_at = playback
+            .starved_since
+            .filter(|since| since.elapsed() >= STALL_GRACE);
+        let rebuffer_ms = self.config.rebuffer_ms.min(playback.ring_ms * 3 / 4);
+        let (read_bytes, total_bytes) = playback.progress.get();
+
+        let result = match (self.state, stalled_at) {
+            (EngineState::Playing, Some(stalled_at)) if !finished => {
+                self.enter_buffering(stalled_at)
+            }
+            (EngineState::Buffering, _) if finished || buffered_ms >= rebuffer_ms => {
+                self.leave_buffering()
+            }
+            _ => Ok(()),
+        };
+        if let Err(err) = result {
+            self.publish_error(err);
+        }
+
+        let health = (buffered_ms, read_bytes, total_bytes);
+        if self
+            .current_source
+            .as_ref()
+            .and_then(SourceSpec::url)
+            .is_some()
+            && self.buffer_health != Some(health)
+            && self.buffer_health_at.elapsed() >= BUFFER_HEALTH_INTERVAL
+        {
+            self.buffer_health = Some(health);
+            self.buffer_health_at = Instant::now();
+            self.event_hub.publish(AudioEvent::BufferHealth {
+                buffered_ms,
+                downloaded_bytes: read_bytes,
+                total_bytes,
+            });
+        }
+    }
+
+    fn enter_buffering(&mut self, stalled_at: Instant) -> Result<()> {
+        if let Some(playback) = self.playback.as_mut() {
+            playback.output.pause()?;
+            playback.starved_since = None;
+        }
+        // Nothing has been audible since the output first ran dry.
+        self.position_base_ms = self.position_at(stalled_at);
+        self.playing_anchor = None;
+        self.transition_to(EngineState::Buffering)?;
+        self.publish_snapshot();
+        Ok(())
+    }
+
+    fn leave_buffering(&mut self) -> Result<()> {
+        if let Some(playback) = self.playback.as_mut() {
+            playback.output.play()?;
+            playback.underruns_seen = playback.drain_state.underruns();
+            playback.starved_since = None;
+        }
+        self.playing_anchor = Some(Instant::now());
+        self.transition_to(EngineState::Playing)?;
+        self.publish_snapshot();
+        Ok(())
+    }
+
+    // Processing the user asked for still runs on a bit-perfect output, which
+    // then no longer plays the track's samples unchanged.
+    fn alters_samples(&self) -> bool {
+        self.equalizer.is_enabled()
+            || self.config.normalization != NormalizationMode::Off
+            || self.playback_rate() != 1.0
+            || self.audible_rate != 1.0
+    }
+
     fn publish_snapshot(&self) {
         let snapshot = AudioSnapshot {
             state: self.state,
-            is_playing: self.state == EngineState::Playing,
+            is_playing: matches!(self.state, EngineState::Playing | EngineState::Buffering),
             position_ms: self.current_position_ms(),
             duration_ms: self.duration_ms,
             volume: self.config.volume,
+            playback_rate: self.playback_rate(),
             backend: self.config.backend,
             device: self
                 .playback
                 .as_ref()
                 .map(|playback| playback.device_name.clone()),
             source: self.current_source.clone(),
+            normalization_gain_db: self.normalization_gain_db,
+            format: self.format.clone(),
+            output_format: self.playback.as_ref().map(|playback| {
+                let sample_rate = playback.output.sample_rate();
+                let channels = playback.output.channels();
+                OutputFormat {
+                    sample_rate,
+                    channels,
+                    bit_depth: playback.output.bit_depth(),
+                    bit_perfect: playback.bit_perfect
+                        && !self.alters_samples()
+                        && self.format.as_ref().is_some_and(|format| {
+                            format.sample_rate == sample_rate && format.channels == channels
+                        }),
+                }
+            }),
+            loop_region: self.loop_region.get(),
+            sleep_timer_remaining_ms: self.sleep_timer.as_ref().map(|timer| {
+                timer
+                    .deadline
+                    .saturating_duration_since(Instant::now())
+                    .as_millis() as u64
+            }),
         };
 
         if let Ok(mut latest) = self.latest_snapshot.write() {
@@ -498,9 +1581,27 @@ impl AudioRuntime {
     }
 
     fn current_position_ms(&self) -> u64 {
+        self.position_at(Instant::now())
+    }
+
+    fn position_at(&self, at: Instant) -> u64 {
         if let Some(anchor) = self.playing_anchor {
-            let elapsed_ms = anchor.elapsed().as_millis() as u64;
-            let position = self.position_base_ms.saturating_add(elapsed_ms);
+            let elapsed_ms = (at.saturating_duration_since(anchor).as_secs_f64()
+                * 1000.0
+                * self.audible_rate as f64) as u64;
+            let mut position = self.position_base_ms.saturating_add(elapsed_ms);
+            // A loop reaching past the track wraps at the end of the track.
+            if let Some((start_ms, end_ms)) = self.loop_region.get()
+                && let end_ms = match self.duration_ms {
+                    0 => end_ms,
+                    duration_ms => end_ms.min(duration_ms),
+                }
+                && start_ms < end_ms
+                && self.position_base_ms < end_ms
+                && position >= end_ms
+            {
+                position = start_ms + (position - end_ms) % (end_ms - start_ms);
+            }
             if self.duration_ms > 0 {
                 return position.min(self.duration_ms);
             }
@@ -515,6 +1616,31 @@ impl AudioRuntime {
     }
 }
 
+fn requires_rebuild(patch: &RuntimeConfigPatch) -> bool {
+    patch.backend.is_some()
+        || patch.preferred_device.is_some()
+        || patch.offline_output.is_some()
+        || patch.resample_quality.is_some()
+        || patch.channel_mix.is_some()
+        || patch.output_mode.is_some()
+        || patch.normalization.is_some()
+        || patch.target_lufs.is_some()
+        || patch.network.is_some()
+}
+
+fn build_source_factory(config: &AudioConfig) -> Result<Arc<PrefetchingSourceFactory>> {
+    let network: Arc<dyn SourceFactory> =
+        Arc::new(DefaultSourceFactory::new(config.network.clone())?);
+    let inner: Arc<dyn SourceFactory> = match &config.stream_cache {
+        Some(cache) => Arc::new(CachingSourceFactory::new(network, cache.clone())?),
+        None => network,
+    };
+    Ok(Arc::new(PrefetchingSourceFactory::new(
+        inner,
+        config.network.prebuffer_bytes,
+    )))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -531,4 +1657,16 @@ mod tests {
         });
         assert_eq!(config.snapshot_hz, 1);
     }
+
+    #[test]
+    fn fade_durations_are_clamped() {
+        let mut config = AudioConfig::default();
+        config.apply_patch(RuntimeConfigPatch {
+            crossfade_ms: Some(60_000),
+            manual_fade_ms: Some(10_000),
+            ..RuntimeConfigPatch::default()
+        });
+        assert_eq!(config.crossfade_ms, MAX_CROSSFADE_MS);
+        assert_eq!(config.manual_fade_ms, crate::config::MAX_MANUAL_FADE_MS);
+    }
 }
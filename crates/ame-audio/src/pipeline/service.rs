@@ -1,22 +1,73 @@
-use std::sync::{Arc, Mutex, RwLock, mpsc};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, RwLock, Weak, mpsc};
+use std::task::{Context, Poll, Waker};
 use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
+use futures::Stream;
+use futures::channel::mpsc as stream_mpsc;
+
+use crate::analysis::AudioAnalysis;
 use crate::command::AudioCommand;
 use crate::config::AudioConfig;
 use crate::error::{AudioError, Result};
 use crate::event::AudioEvent;
+use crate::offline::OfflineFaults;
 use crate::runtime::AudioRuntime;
 use crate::snapshot::AudioSnapshot;
 
-#[derive(Clone)]
 pub struct SubscriptionHub<T: Clone> {
-    subscribers: Arc<Mutex<Vec<mpsc::Sender<T>>>>,
+    inner: Arc<HubInner<T>>,
+}
+
+struct HubInner<T> {
+    subscribers: Mutex<Vec<(u64, Subscriber<T>)>>,
+    next_id: AtomicU64,
+}
+
+enum Subscriber<T> {
+    Channel(mpsc::Sender<T>),
+    Stream(stream_mpsc::Sender<T>),
+    Latest(Arc<LatestSlot<T>>),
+}
+
+impl<T> Subscriber<T> {
+    // Hands over a value; `false` once the receiving side is gone.
+    fn deliver(&mut self, value: T) -> bool {
+        match self {
+            Self::Channel(tx) => tx.send(value).is_ok(),
+            // A full stream drops the value rather than growing without bound.
+            Self::Stream(tx) => match tx.try_send(value) {
+                Ok(()) => true,
+                Err(err) => !err.is_disconnected(),
+            },
+            Self::Latest(slot) => Arc::strong_count(slot) > 1 && slot.replace(value),
+        }
+    }
+
+    fn close(self) {
+        if let Self::Latest(slot) = self {
+            slot.close();
+        }
+    }
+}
+
+impl<T: Clone> Clone for SubscriptionHub<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
 }
 
 impl<T: Clone> Default for SubscriptionHub<T> {
     fn default() -> Self {
         Self {
-            subscribers: Arc::new(Mutex::new(Vec::new())),
+            inner: Arc::new(HubInner {
+                subscribers: Mutex::new(Vec::new()),
+                next_id: AtomicU64::new(0),
+            }),
         }
     }
 }
@@ -24,17 +75,216 @@ impl<T: Clone> Default for SubscriptionHub<T> {
 impl<T: Clone> SubscriptionHub<T> {
     pub fn subscribe(&self) -> mpsc::Receiver<T> {
         let (tx, rx) = mpsc::channel();
-        if let Ok(mut subscribers) = self.subscribers.lock() {
-            subscribers.push(tx);
-        }
+        self.attach(Subscriber::Channel(tx));
         rx
     }
 
+    pub fn has_subscribers(&self) -> bool {
+        self.inner
+            .subscribers
+            .lock()
+            .map(|subscribers| !subscribers.is_empty())
+            .unwrap_or(false)
+    }
+
     pub fn publish(&self, value: T) {
-        if let Ok(mut subscribers) = self.subscribers.lock() {
-            subscribers.retain(|subscriber| subscriber.send(value.clone()).is_ok());
+        if let Ok(mut subscribers) = self.inner.subscribers.lock() {
+            subscribers.retain_mut(|(_, subscriber)| subscriber.deliver(value.clone()));
+        }
+    }
+
+    fn attach(&self, subscriber: Subscriber<T>) -> u64 {
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut subscribers) = self.inner.subscribers.lock() {
+            subscribers.push((id, subscriber));
+        }
+        id
+    }
+}
+
+impl<T: Clone + Send + 'static> SubscriptionHub<T> {
+    // Buffers up to `capacity` values for a reader that falls behind; later
+    // values are dropped until it catches up.
+    pub fn subscribe_stream(&self, capacity: usize) -> SubscriptionStream<T> {
+        let (tx, receiver) = stream_mpsc::channel(capacity.max(1));
+        let id = self.attach(Subscriber::Stream(tx));
+        SubscriptionStream {
+            receiver,
+            handle: self.handle(id),
+        }
+    }
+
+    // Keeps only the newest value, starting with `initial` when given.
+    pub fn subscribe_latest(&self, initial: Option<T>) -> LatestReceiver<T> {
+        let slot = Arc::new(LatestSlot {
+            state: Mutex::new(LatestState {
+                value: initial,
+                closed: false,
+                waker: None,
+            }),
+            changed: Condvar::new(),
+        });
+        let id = self.attach(Subscriber::Latest(Arc::clone(&slot)));
+        LatestReceiver {
+            slot,
+            handle: self.handle(id),
+        }
+    }
+
+    fn handle(&self, id: u64) -> SubscriptionHandle {
+        let hub: Arc<dyn Detach> = self.inner.clone();
+        SubscriptionHandle {
+            id,
+            hub: Arc::downgrade(&hub),
+        }
+    }
+}
+
+trait Detach: Send + Sync {
+    fn detach(&self, id: u64);
+}
+
+impl<T: Send> Detach for HubInner<T> {
+    fn detach(&self, id: u64) {
+        let removed = self.subscribers.lock().ok().and_then(|mut subscribers| {
+            let index = subscribers.iter().position(|(other, _)| *other == id)?;
+            Some(subscribers.remove(index).1)
+        });
+        if let Some(subscriber) = removed {
+            subscriber.close();
+        }
+    }
+}
+
+// Ends a subscription from anywhere. The receiving side still gets what was
+// delivered before, then sees the end of the subscription.
+#[derive(Clone)]
+pub struct SubscriptionHandle {
+    id: u64,
+    hub: Weak<dyn Detach>,
+}
+
+impl SubscriptionHandle {
+    pub fn unsubscribe(&self) {
+        if let Some(hub) = self.hub.upgrade() {
+            hub.detach(self.id);
+        }
+    }
+}
+
+pub struct SubscriptionStream<T> {
+    receiver: stream_mpsc::Receiver<T>,
+    handle: SubscriptionHandle,
+}
+
+impl<T> SubscriptionStream<T> {
+    pub fn handle(&self) -> SubscriptionHandle {
+        self.handle.clone()
+    }
+
+    pub fn unsubscribe(self) {
+        self.handle.unsubscribe();
+    }
+}
+
+impl<T> Stream for SubscriptionStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+struct LatestSlot<T> {
+    state: Mutex<LatestState<T>>,
+    changed: Condvar,
+}
+
+struct LatestState<T> {
+    value: Option<T>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+impl<T> LatestSlot<T> {
+    fn replace(&self, value: T) -> bool {
+        let Ok(mut state) = self.state.lock() else {
+            return false;
+        };
+        state.value = Some(value);
+        Self::notify(&mut state, &self.changed);
+        true
+    }
+
+    fn close(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.closed = true;
+            Self::notify(&mut state, &self.changed);
+        }
+    }
+
+    fn notify(state: &mut LatestState<T>, changed: &Condvar) {
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        changed.notify_all();
+    }
+}
+
+// Watch-style subscription: a reader that falls behind skips straight to the
+// newest value instead of queueing the ones in between.
+pub struct LatestReceiver<T> {
+    slot: Arc<LatestSlot<T>>,
+    handle: SubscriptionHandle,
+}
+
+impl<T> LatestReceiver<T> {
+    // The newest value not taken yet.
+    pub fn try_take(&self) -> Option<T> {
+        self.slot.state.lock().ok()?.value.take()
+    }
+
+    // Waits for a value not taken yet; `None` on timeout or once unsubscribed.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<T> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.slot.state.lock().ok()?;
+        loop {
+            if let Some(value) = state.value.take() {
+                return Some(value);
+            }
+            let left = deadline.checked_duration_since(Instant::now())?;
+            if state.closed {
+                return None;
+            }
+            state = self.slot.changed.wait_timeout(state, left).ok()?.0;
         }
     }
+
+    pub fn handle(&self) -> SubscriptionHandle {
+        self.handle.clone()
+    }
+
+    pub fn unsubscribe(self) {
+        self.handle.unsubscribe();
+    }
+}
+
+impl<T> Stream for LatestReceiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let Ok(mut state) = self.slot.state.lock() else {
+            return Poll::Ready(None);
+        };
+        if let Some(value) = state.value.take() {
+            return Poll::Ready(Some(value));
+        }
+        if state.closed {
+            return Poll::Ready(None);
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
 }
 
 #[derive(Clone)]
@@ -42,7 +292,10 @@ pub struct AudioService {
     command_tx: mpsc::Sender<AudioCommand>,
     event_hub: SubscriptionHub<AudioEvent>,
     snapshot_hub: SubscriptionHub<AudioSnapshot>,
+    analysis_hub: SubscriptionHub<AudioAnalysis>,
     latest_snapshot: Arc<RwLock<AudioSnapshot>>,
+    #[cfg(any(test, feature = "test-support"))]
+    offline_faults: OfflineFaults,
 }
 
 impl AudioService {
@@ -50,18 +303,22 @@ impl AudioService {
         let (command_tx, command_rx) = mpsc::channel();
         let event_hub = SubscriptionHub::<AudioEvent>::default();
         let snapshot_hub = SubscriptionHub::<AudioSnapshot>::default();
+        let analysis_hub = SubscriptionHub::<AudioAnalysis>::default();
         let latest_snapshot = Arc::new(RwLock::new(AudioSnapshot {
             volume: config.volume,
             backend: config.backend,
             ..AudioSnapshot::default()
         }));
+        let offline_faults = OfflineFaults::default();
 
         let runtime = AudioRuntime::new(
             config,
             command_rx,
             event_hub.clone(),
             snapshot_hub.clone(),
+            analysis_hub.clone(),
             Arc::clone(&latest_snapshot),
+            offline_faults.clone(),
         )?;
 
         let thread = std::thread::Builder::new()
@@ -75,7 +332,10 @@ impl AudioService {
             command_tx: command_tx.clone(),
             event_hub,
             snapshot_hub,
+            analysis_hub,
             latest_snapshot,
+            #[cfg(any(test, feature = "test-support"))]
+            offline_faults,
         };
 
         let handle = AudioRuntimeHandle {
@@ -100,6 +360,27 @@ impl AudioService {
         self.snapshot_hub.subscribe()
     }
 
+    // Events for async readers. A reader more than `capacity` events behind
+    // misses the ones that arrive meanwhile.
+    pub fn event_stream(&self, capacity: usize) -> SubscriptionStream<AudioEvent> {
+        self.event_hub.subscribe_stream(capacity)
+    }
+
+    // Starts out with the current snapshot, so the first read never waits.
+    pub fn watch_snapshot(&self) -> LatestReceiver<AudioSnapshot> {
+        self.snapshot_hub.subscribe_latest(Some(self.snapshot()))
+    }
+
+    pub fn subscribe_analysis(&self) -> mpsc::Receiver<AudioAnalysis> {
+        self.analysis_hub.subscribe()
+    }
+
+    // Faults for the `Null` and `WavFile` outputs this service opens.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn offline_faults(&self) -> OfflineFaults {
+        self.offline_faults.clone()
+    }
+
     pub fn snapshot(&self) -> AudioSnapshot {
         self.latest_snapshot
             .read()
@@ -137,3 +418,70 @@ impl Drop for AudioRuntimeHandle {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+    use std::task::{Context, Poll, Waker};
+    use std::time::Duration;
+
+    use futures::Stream;
+
+    use super::SubscriptionHub;
+
+    fn poll<S: Stream + Unpin>(stream: &mut S) -> Poll<Option<S::Item>> {
+        Pin::new(stream).poll_next(&mut Context::from_waker(Waker::noop()))
+    }
+
+    #[test]
+    fn latest_receiver_skips_to_the_newest_value() {
+        let hub = SubscriptionHub::default();
+        let mut latest = hub.subscribe_latest(Some(0));
+        assert_eq!(latest.try_take(), Some(0));
+        assert_eq!(latest.try_take(), None);
+
+        for value in 1..=100 {
+            hub.publish(value);
+        }
+        assert_eq!(poll(&mut latest), Poll::Ready(Some(100)));
+        assert_eq!(poll(&mut latest), Poll::Pending);
+        assert_eq!(latest.recv_timeout(Duration::from_millis(10)), None);
+
+        latest.handle().unsubscribe();
+        assert!(!hub.has_subscribers());
+        assert_eq!(poll(&mut latest), Poll::Ready(None));
+    }
+
+    #[test]
+    fn stream_subscription_is_bounded_and_ends_on_unsubscribe() {
+        let hub = SubscriptionHub::default();
+        let mut stream = hub.subscribe_stream(4);
+        for value in 0..100 {
+            hub.publish(value);
+        }
+
+        let mut received = Vec::new();
+        while let Poll::Ready(Some(value)) = poll(&mut stream) {
+            received.push(value);
+        }
+        assert!(received.len() < 10, "kept {} values", received.len());
+        assert_eq!(received[..4], [0, 1, 2, 3]);
+
+        let handle = stream.handle();
+        hub.publish(100);
+        handle.unsubscribe();
+        hub.publish(101);
+        assert_eq!(poll(&mut stream), Poll::Ready(Some(100)));
+        assert_eq!(poll(&mut stream), Poll::Ready(None));
+    }
+
+    #[test]
+    fn dropped_receivers_are_pruned_on_publish() {
+        let hub = SubscriptionHub::default();
+        drop(hub.subscribe_latest(None));
+        drop(hub.subscribe_stream(1));
+        assert!(hub.has_subscribers());
+        hub.publish(1);
+        assert!(!hub.has_subscribers());
+    }
+}
@@ -10,11 +10,27 @@ use symphonia::core::io::MediaSource;
 
 use crate::config::NetworkConfig;
 use crate::error::{AudioError, Result};
+use crate::ncm::NcmFile;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SourceSpec {
     LocalFile(PathBuf),
+    NcmFile(PathBuf),
     NetworkUrl(String),
+    CachedStream { url: String, key: StreamCacheKey },
+}
+
+// Stream URLs expire, so cached audio is keyed by what the URL points at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StreamCacheKey {
+    pub track_id: i64,
+    pub bitrate: u32,
+}
+
+impl StreamCacheKey {
+    pub fn new(track_id: i64, bitrate: u32) -> Self {
+        Self { track_id, bitrate }
+    }
 }
 
 impl SourceSpec {
@@ -22,21 +38,43 @@ impl SourceSpec {
         Self::LocalFile(path.into())
     }
 
+    pub fn ncm(path: impl Into<PathBuf>) -> Self {
+        Self::NcmFile(path.into())
+    }
+
     pub fn network(url: impl Into<String>) -> Self {
         Self::NetworkUrl(url.into())
     }
 
+    pub fn cached_stream(url: impl Into<String>, key: StreamCacheKey) -> Self {
+        Self::CachedStream {
+            url: url.into(),
+            key,
+        }
+    }
+
+    pub fn url(&self) -> Option<&str> {
+        match self {
+            SourceSpec::LocalFile(_) | SourceSpec::NcmFile(_) => None,
+            SourceSpec::NetworkUrl(url) | SourceSpec::CachedStream { url, .. } => Some(url),
+        }
+    }
+
     pub fn describe(&self) -> String {
         match self {
-            SourceSpec::LocalFile(path) => path.display().to_string(),
-            SourceSpec::NetworkUrl(url) => url.clone(),
+            SourceSpec::LocalFile(path) | SourceSpec::NcmFile(path) => path.display().to_string(),
+            SourceSpec::NetworkUrl(url) | SourceSpec::CachedStream { url, .. } => url.clone(),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SeekTarget {
     Milliseconds(u64),
+    Forward(u64),
+    Backward(u64),
+    // Share of the track duration, `0.0..=1.0`.
+    Ratio(f32),
 }
 
 impl SeekTarget {
@@ -44,9 +82,28 @@ impl SeekTarget {
         Self::Milliseconds(ms)
     }
 
-    pub fn to_millis(self) -> u64 {
-        match self {
+    // Absolute position for this target given where playback is now. A
+    // duration of zero means it is unknown, so nothing is clamped and a ratio
+    // has nothing to resolve against.
+    pub fn resolve(self, position_ms: u64, duration_ms: u64) -> Option<u64> {
+        let target = match self {
             SeekTarget::Milliseconds(ms) => ms,
+            SeekTarget::Forward(ms) => position_ms.saturating_add(ms),
+            SeekTarget::Backward(ms) => position_ms.saturating_sub(ms),
+            SeekTarget::Ratio(_) if duration_ms == 0 => return None,
+            SeekTarget::Ratio(ratio) => {
+                let ratio = if ratio.is_finite() {
+                    ratio.clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                (duration_ms as f64 * ratio as f64) as u64
+            }
+        };
+        if duration_ms > 0 {
+            Some(target.min(duration_ms))
+        } else {
+            Some(target)
         }
     }
 }
@@ -91,7 +148,11 @@ impl SourceFactory for DefaultSourceFactory {
                     seekable: true,
                 })
             }
-            SourceSpec::NetworkUrl(url) => {
+            SourceSpec::NcmFile(path) => Ok(OpenedSource {
+                media_source: NcmFile::open(path)?.into_media_source(),
+                seekable: true,
+            }),
+            SourceSpec::NetworkUrl(url) | SourceSpec::CachedStream { url, .. } => {
                 let source = RangeHttpSource::new(self.client.clone(), url, self.network.clone())?;
                 let seekable = source.is_seekable();
                 Ok(OpenedSource {
@@ -367,3 +428,19 @@ impl Source for NetworkSource {
         SourceSpec::NetworkUrl(self.url)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::SeekTarget;
+
+    #[test]
+    fn ratio_needs_a_known_duration() {
+        assert_eq!(SeekTarget::Ratio(0.5).resolve(1_000, 10_000), Some(5_000));
+        assert_eq!(SeekTarget::Ratio(0.5).resolve(1_000, 0), None);
+        assert_eq!(SeekTarget::Forward(500).resolve(1_000, 0), Some(1_500));
+        assert_eq!(
+            SeekTarget::Forward(500).resolve(9_800, 10_000),
+            Some(10_000)
+        );
+    }
+}
@@ -0,0 +1,249 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::Sample;
+
+const OVERLAP_MS: u32 = 10;
+const SEARCH_MS: u32 = 8;
+// Correlation only looks at every few frames; the waveform similarity peak is
+// broad enough that this barely changes the chosen offset.
+const SEARCH_STRIDE: usize = 4;
+const TRIM_FRAMES: usize = 8192;
+const UNITY_EPSILON: f32 = 1e-3;
+
+// WSOLA time stretching: overlapping segments are taken from the input at the
+// playback rate and each one is nudged to where it best lines up with the
+// previous segment's tail, which keeps pitch intact. At 1x the input passes
+// through untouched.
+pub(crate) struct TimeStretch {
+    rate: Arc<AtomicU32>,
+    channels: usize,
+    overlap: usize,
+    search: usize,
+    fade_in: Vec<f32>,
+    active: bool,
+    input: Vec<Sample>,
+    position: f64,
+    natural: usize,
+    tail: Vec<Sample>,
+    output: Vec<Sample>,
+}
+
+impl TimeStretch {
+    pub fn new(sample_rate: u32, channels: usize, rate: Arc<AtomicU32>) -> Self {
+        let overlap = (sample_rate * OVERLAP_MS / 1000).max(1) as usize;
+        let fade_in = (0..overlap)
+            .map(|index| {
+                let phase = (index as f32 + 0.5) / overlap as f32;
+                0.5 - 0.5 * (std::f32::consts::PI * phase).cos()
+            })
+            .collect();
+        Self {
+            rate,
+            channels: channels.max(1),
+            overlap,
+            search: (sample_rate * SEARCH_MS / 1000) as usize,
+            fade_in,
+            active: false,
+            input: Vec::new(),
+            position: 0.0,
+            natural: 0,
+            tail: Vec::new(),
+            output: Vec::new(),
+        }
+    }
+
+    pub fn process<'a>(&'a mut self, samples: &'a mut [Sample]) -> &'a mut [Sample] {
+        let rate = f32::from_bits(self.rate.load(Ordering::Relaxed));
+        let unity = (rate - 1.0).abs() < UNITY_EPSILON;
+        if !self.active {
+            if unity {
+                return samples;
+            }
+            self.active = true;
+        }
+
+        self.input.extend_from_slice(samples);
+        self.output.clear();
+        if unity {
+            self.drain();
+        } else {
+            self.stretch(rate as f64);
+        }
+        &mut self.output
+    }
+
+    pub fn rate(&self) -> f32 {
+        f32::from_bits(self.rate.load(Ordering::Relaxed))
+    }
+
+    // Emits everything still buffered unstretched, e.g. before a track boundary
+    // or at the end of the stream.
+    pub fn flush(&mut self) -> &mut [Sample] {
+        self.output.clear();
+        if self.active {
+            self.drain();
+        }
+        &mut self.output
+    }
+
+    fn frames(&self) -> usize {
+        self.input.len() / self.channels
+    }
+
+    fn stretch(&mut self, rate: f64) {
+        let channels = self.channels;
+        let overlap = self.overlap;
+        let hop_in = overlap as f64 * rate;
+
+        loop {
+            let start = if self.tail.is_empty() {
+                let start = self.position.round() as usize;
+                if self.frames() < start + 2 * overlap {
+                    break;
+                }
+                self.output
+                    .extend_from_slice(&self.input[start * channels..(start + overlap) * channels]);
+                start
+            } else {
+                let nominal = self.position.round() as usize;
+                let low = nominal.saturating_sub(self.search);
+                let high = nominal + self.search;
+                if self.frames() < high + 2 * overlap {
+                    break;
+                }
+                let start = self.best_start(low, high);
+                for frame in 0..overlap {
+                    let fade_in = self.fade_in[frame];
+                    for channel in 0..channels {
+                        let index = frame * channels + channel;
+                        let incoming = self.input[start * channels + index];
+                        self.output
+                            .push(self.tail[index] * (1.0 - fade_in) + incoming * fade_in);
+                    }
+                }
+                start
+            };
+
+            self.tail.clear();
+            self.tail.extend_from_slice(
+                &self.input[(start + overlap) * channels..(start + 2 * overlap) * channels],
+            );
+            self.natural = start + 2 * overlap;
+            self.position += hop_in;
+        }
+
+        let keep_from = self
+            .natural
+            .min((self.position as usize).saturating_sub(self.search));
+        if keep_from >= TRIM_FRAMES {
+            self.input.drain(..keep_from * channels);
+            self.position -= keep_from as f64;
+            self.natural -= keep_from;
+        }
+    }
+
+    // Offset in `low..=high` whose opening best continues the current tail,
+    // by normalized cross-correlation of the channel mix.
+    fn best_start(&self, low: usize, high: usize) -> usize {
+        let channels = self.channels;
+        let mix = |samples: &[Sample], frame: usize| -> f32 {
+            samples[frame * channels..(frame + 1) * channels]
+                .iter()
+                .sum()
+        };
+
+        let mut best = (low, f32::MIN);
+        for start in low..=high {
+            let mut correlation = 0.0;
+            let mut energy = 1e-9;
+            for frame in (0..self.overlap).step_by(SEARCH_STRIDE) {
+                let candidate = mix(&self.input, start + frame);
+                correlation += mix(&self.tail, frame) * candidate;
+                energy += candidate * candidate;
+            }
+            let score = correlation / energy.sqrt();
+            if score > best.1 {
+                best = (start, score);
+            }
+        }
+        best.0
+    }
+
+    fn drain(&mut self) {
+        self.output.append(&mut self.tail);
+        let natural = (self.natural * self.channels).min(self.input.len());
+        self.output.extend_from_slice(&self.input[natural..]);
+        self.input.clear();
+        self.position = 0.0;
+        self.natural = 0;
+        self.active = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicU32;
+
+    use super::TimeStretch;
+
+    const SAMPLE_RATE: u32 = 48_000;
+
+    fn sine(frequency: f32, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|frame| {
+                let value = (2.0 * std::f32::consts::PI * frequency * frame as f32
+                    / SAMPLE_RATE as f32)
+                    .sin();
+                [value, value]
+            })
+            .collect()
+    }
+
+    fn stretch_all(rate: f32, input: &[f32]) -> Vec<f32> {
+        let mut stretch =
+            TimeStretch::new(SAMPLE_RATE, 2, Arc::new(AtomicU32::new(rate.to_bits())));
+        let mut output = Vec::new();
+        for chunk in input.chunks(1024) {
+            let mut chunk = chunk.to_vec();
+            output.extend_from_slice(stretch.process(&mut chunk));
+        }
+        output.extend_from_slice(stretch.flush());
+        output
+    }
+
+    fn rising_crossings(samples: &[f32]) -> usize {
+        samples
+            .chunks_exact(2)
+            .map(|frame| frame[0])
+            .collect::<Vec<_>>()
+            .windows(2)
+            .filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0)
+            .count()
+    }
+
+    #[test]
+    fn unity_rate_passes_samples_through() {
+        let input = sine(440.0, 4800);
+        assert_eq!(stretch_all(1.0, &input), input);
+    }
+
+    #[test]
+    fn stretching_changes_length_but_not_pitch() {
+        let frames = SAMPLE_RATE as usize * 2;
+        let input = sine(440.0, frames);
+        for rate in [0.5f32, 1.5, 3.0] {
+            let output = stretch_all(rate, &input);
+            let output_frames = output.len() / 2;
+            let expected = frames as f32 / rate;
+            assert!(
+                (output_frames as f32 - expected).abs() < SAMPLE_RATE as f32 * 0.05,
+                "rate {rate}: {output_frames} frames, expected {expected}"
+            );
+
+            let hz = rising_crossings(&output) as f32 * SAMPLE_RATE as f32 / output_frames as f32;
+            assert!((hz - 440.0).abs() < 10.0, "rate {rate}: {hz} Hz");
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use symphonia::core::meta::{MetadataRevision, StandardTagKey, StandardVisualKey, Value};
+
+use crate::metadata::{Artwork, TrackMetadata};
+
+// Collects what a player shows about a track from the file's own tags. The
+// first revision carrying a field wins, so tags found while probing the file
+// take precedence over the container's.
+pub(crate) fn read_metadata(revisions: &[&MetadataRevision]) -> TrackMetadata {
+    let mut metadata = TrackMetadata::default();
+    for tag in revisions.iter().flat_map(|revision| revision.tags()) {
+        let slot = match tag.std_key {
+            Some(StandardTagKey::TrackTitle) => &mut metadata.title,
+            Some(StandardTagKey::Artist) => &mut metadata.artist,
+            Some(StandardTagKey::Album) => &mut metadata.album,
+            Some(StandardTagKey::Lyrics) => &mut metadata.lyrics,
+            Some(StandardTagKey::TrackNumber) => {
+                if metadata.track_number.is_none() {
+                    metadata.track_number = track_number(&tag.value);
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        if slot.is_none() {
+            *slot = text(&tag.value);
+        }
+    }
+
+    let visuals = || revisions.iter().flat_map(|revision| revision.visuals());
+    metadata.artwork = visuals()
+        .find(|visual| visual.usage == Some(StandardVisualKey::FrontCover))
+        .or_else(|| visuals().next())
+        .map(|visual| Artwork {
+            media_type: visual.media_type.clone(),
+            data: visual.data.to_vec(),
+        });
+    metadata
+}
+
+// RIFF INFO strings keep their NUL terminator.
+fn trim(text: &str) -> &str {
+    text.trim_matches(|c: char| c.is_whitespace() || c == '\0')
+}
+
+fn text(value: &Value) -> Option<String> {
+    let text = value.to_string();
+    let text = trim(&text);
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+// Either a plain number or "track/total".
+fn track_number(value: &Value) -> Option<u32> {
+    let number = match value {
+        Value::UnsignedInt(number) => u32::try_from(*number).ok(),
+        Value::SignedInt(number) => u32::try_from(*number).ok(),
+        Value::String(text) => trim(text.split('/').next()?).parse().ok(),
+        _ => None,
+    };
+    number.filter(|number| *number > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use symphonia::core::meta::{
+        MetadataBuilder, MetadataRevision, StandardTagKey, StandardVisualKey, Tag, Value, Visual,
+    };
+
+    use super::read_metadata;
+
+    fn visual(usage: StandardVisualKey, data: &[u8]) -> Visual {
+        Visual {
+            media_type: "image/png".into(),
+            dimensions: None,
+            bits_per_pixel: None,
+            color_mode: None,
+            usage: Some(usage),
+            tags: Vec::new(),
+            data: data.into(),
+        }
+    }
+
+    fn revision(tags: &[(StandardTagKey, Value)], visuals: Vec<Visual>) -> MetadataRevision {
+        let mut builder = MetadataBuilder::new();
+        for (key, value) in tags {
+            builder.add_tag(Tag::new(Some(*key), "", value.clone()));
+        }
+        for visual in visuals {
+            builder.add_visual(visual);
+        }
+        builder.metadata()
+    }
+
+    #[test]
+    fn reads_standard_tags_and_prefers_the_front_cover() {
+        let probed = revision(
+            &[
+                (StandardTagKey::TrackTitle, Value::from("  Blue  ")),
+                (StandardTagKey::Artist, Value::from("")),
+                (StandardTagKey::TrackNumber, Value::from("3/12")),
+                (StandardTagKey::Lyrics, Value::from("la la la")),
+            ],
+            vec![
+                visual(StandardVisualKey::BackCover, b"back"),
+                visual(StandardVisualKey::FrontCover, b"front"),
+            ],
+        );
+        let container = revision(
+            &[
+                (StandardTagKey::TrackTitle, Value::from("Other")),
+                (StandardTagKey::Artist, Value::from("Someone")),
+                (StandardTagKey::TrackNumber, Value::UnsignedInt(7)),
+            ],
+            Vec::new(),
+        );
+
+        let metadata = read_metadata(&[&probed, &container]);
+        assert_eq!(metadata.title.as_deref(), Some("Blue"));
+        assert_eq!(metadata.artist.as_deref(), Some("Someone"));
+        assert_eq!(metadata.album, None);
+        assert_eq!(metadata.track_number, Some(3));
+        assert_eq!(metadata.lyrics.as_deref(), Some("la la la"));
+        assert_eq!(metadata.artwork.unwrap().data, b"front");
+    }
+
+    #[test]
+    fn untagged_files_yield_empty_metadata() {
+        let empty = revision(&[(StandardTagKey::Comment, Value::from("x"))], Vec::new());
+        assert!(read_metadata(&[&empty]).is_empty());
+        assert!(read_metadata(&[]).is_empty());
+    }
+}
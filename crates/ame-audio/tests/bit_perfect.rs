@@ -0,0 +1,150 @@
+mod common;
+
+use std::time::Instant;
+
+use ame_audio::{
+    AudioCommand, AudioConfig, AudioEvent, EngineState, EqualizerPreset, EqualizerSettings,
+    OfflineClock, OutputBackendKind, OutputMode,
+};
+
+use common::{Harness, TIMEOUT, fixtures};
+
+fn render_two_tracks(config: AudioConfig) -> Vec<f32> {
+    let mut harness = Harness::recording(OfflineClock::Freewheel, config);
+    let first = harness.source("first.wav", fixtures::wav(48_000, 2, 16, 24_000));
+    let second = harness.source("second.wav", fixtures::wav(48_000, 2, 16, 24_000));
+    harness.send(AudioCommand::SetNext(Some(second)));
+    harness.open(&first, true);
+    harness.wait_for(|event| matches!(event, AudioEvent::TrackEnded { next: None }));
+    harness.rendered()
+}
+
+#[test]
+fn bit_perfect_output_reopens_only_when_the_rate_changes() {
+    let mut harness = Harness::spawn(AudioConfig {
+        backend: OutputBackendKind::Null,
+        output_mode: OutputMode::BitPerfect,
+        ..AudioConfig::default()
+    });
+    let first = harness.source("first.wav", fixtures::wav(44_100, 2, 16, 44_100 * 3));
+    let second = harness.source("second.wav", fixtures::wav(44_100, 2, 16, 44_100 * 3));
+    let third = harness.source("third.wav", fixtures::wav(96_000, 2, 16, 48_000));
+    let snapshots = harness.service.subscribe_snapshot();
+    harness.open(&first, true);
+    harness.send(AudioCommand::SetNext(Some(second.clone())));
+
+    let wait_for_rate = |sample_rate: u32| {
+        let deadline = Instant::now() + TIMEOUT;
+        while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+            let snapshot = snapshots.recv_timeout(left).expect("no snapshot");
+            if let Some(output) = snapshot.output_format
+                && output.sample_rate == sample_rate
+                && output.bit_perfect
+            {
+                return;
+            }
+        }
+        panic!("output never ran bit-perfect at {sample_rate} Hz");
+    };
+    wait_for_rate(44_100);
+
+    // Same rate: the decoder moves on without a new stream.
+    let mut streams = 0;
+    let deadline = Instant::now() + TIMEOUT;
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        match harness
+            .events
+            .recv_timeout(left)
+            .expect("second track never started")
+        {
+            AudioEvent::DeviceChanged { .. } => streams += 1,
+            AudioEvent::TrackEnded { next: Some(next) } => {
+                assert_eq!(next, second);
+                break;
+            }
+            _ => {}
+        }
+    }
+    assert_eq!(streams, 1);
+
+    harness.send(AudioCommand::SetNext(Some(third.clone())));
+    harness.wait_for(
+        |event| matches!(event, AudioEvent::TrackEnded { next: Some(next) } if *next == third),
+    );
+    harness.wait_for(|event| matches!(event, AudioEvent::DeviceChanged { .. }));
+    wait_for_rate(96_000);
+}
+
+#[test]
+fn commands_wait_for_the_decoder_to_report_the_format() {
+    let mut harness = Harness::spawn(AudioConfig {
+        backend: OutputBackendKind::Null,
+        output_mode: OutputMode::BitPerfect,
+        ..AudioConfig::default()
+    });
+    let track = harness.source("track.wav", fixtures::wav(96_000, 2, 16, 96_000 * 2));
+    harness.open(&track, true);
+    harness.send(AudioCommand::Pause);
+    harness.wait_for_state(EngineState::Playing);
+    harness.wait_for_state(EngineState::Paused);
+
+    let snapshot = harness.service.snapshot();
+    let output = snapshot.output_format.expect("no output format");
+    assert_eq!(output.sample_rate, 96_000);
+    assert!(output.bit_perfect);
+}
+
+#[test]
+fn bit_perfect_output_plays_the_samples_unchanged() {
+    let reference = render_two_tracks(AudioConfig {
+        volume: 1.0,
+        ..AudioConfig::default()
+    });
+    // Volume, the fade-in and the crossfade would all scale samples.
+    let rendered = render_two_tracks(AudioConfig {
+        output_mode: OutputMode::BitPerfect,
+        volume: 0.5,
+        manual_fade_ms: 200,
+        crossfade_ms: 200,
+        ..AudioConfig::default()
+    });
+    assert_eq!(rendered.len(), reference.len());
+    assert!(
+        rendered == reference,
+        "bit-perfect output altered the samples"
+    );
+}
+
+#[test]
+fn equalizer_clears_the_bit_perfect_flag() {
+    let mut harness = Harness::spawn(AudioConfig {
+        backend: OutputBackendKind::Null,
+        output_mode: OutputMode::BitPerfect,
+        ..AudioConfig::default()
+    });
+    let track = harness.source("track.wav", fixtures::wav(44_100, 2, 16, 44_100 * 3));
+    let snapshots = harness.service.subscribe_snapshot();
+    harness.open(&track, true);
+
+    let wait_for_flag = |bit_perfect: bool| {
+        let deadline = Instant::now() + TIMEOUT;
+        while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+            let snapshot = snapshots.recv_timeout(left).expect("no snapshot");
+            if snapshot
+                .output_format
+                .is_some_and(|output| output.bit_perfect == bit_perfect)
+            {
+                return;
+            }
+        }
+        panic!("output never reported bit_perfect = {bit_perfect}");
+    };
+    wait_for_flag(true);
+    harness.send(AudioCommand::SetEqualizer(EqualizerSettings::from_preset(
+        EqualizerPreset::Rock,
+    )));
+    wait_for_flag(false);
+    harness.send(AudioCommand::SetEqualizer(EqualizerSettings::default()));
+    wait_for_flag(true);
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use ame_audio::{AudioEvent, EngineState, SourceSpec};
+
+use common::{Harness, TIMEOUT, fixtures};
+
+#[test]
+fn stalled_network_source_buffers_and_resumes() {
+    let bytes = fixtures::wav(48_000, 2, 16, 48_000 * 3);
+    let total = bytes.len() as u64;
+    let url = fixtures::serve_stalling(bytes, 48_000 * 4, Duration::from_millis(2_500));
+
+    let harness = Harness::null();
+    let snapshots = harness.service.subscribe_snapshot();
+    harness.open(&SourceSpec::network(url), true);
+
+    let deadline = Instant::now() + TIMEOUT;
+    let buffering = loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        let snapshot = snapshots
+            .recv_timeout(left)
+            .expect("never started buffering");
+        if snapshot.state == EngineState::Buffering {
+            break snapshot;
+        }
+    };
+    assert!(
+        buffering.position_ms <= 1_100,
+        "position kept running: {}",
+        buffering.position_ms
+    );
+    assert!(buffering.is_playing);
+    harness.wait_for(|event| {
+        matches!(
+            event,
+            AudioEvent::StateChanged {
+                from: EngineState::Buffering,
+                to: EngineState::Playing,
+            }
+        )
+    });
+    harness.wait_for(|event| {
+        matches!(
+            event,
+            AudioEvent::BufferHealth { downloaded_bytes, total_bytes: Some(length), .. }
+                if *length == total && *downloaded_bytes == total
+        )
+    });
+}
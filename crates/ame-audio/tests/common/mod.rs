@@ -0,0 +1,151 @@
+// Shared by the integration tests; each test binary only uses part of it.
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
+
+use ame_audio::{
+    AudioCommand, AudioConfig, AudioEvent, AudioRuntimeHandle, AudioService, EngineState,
+    OfflineClock, OfflineOutputConfig, OutputBackendKind, SourceSpec,
+};
+
+pub use ame_audio::fixtures;
+
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+pub fn temp_path(name: &str) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let index = NEXT.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "ame-audio-test-{}-{index}-{name}",
+        std::process::id()
+    ))
+}
+
+// A running audio service on an offline output, along with the temporary files
+// it plays, which are removed once the test is done.
+pub struct Harness {
+    pub service: AudioService,
+    pub events: Receiver<AudioEvent>,
+    handle: Option<AudioRuntimeHandle>,
+    output: Option<PathBuf>,
+    files: Vec<PathBuf>,
+}
+
+impl Harness {
+    pub fn spawn(config: AudioConfig) -> Self {
+        Self::start(config, None)
+    }
+
+    pub fn null() -> Self {
+        Self::spawn(AudioConfig {
+            backend: OutputBackendKind::Null,
+            ..AudioConfig::default()
+        })
+    }
+
+    // Writes everything rendered to a WAV file, read back by `rendered`.
+    pub fn recording(clock: OfflineClock, config: AudioConfig) -> Self {
+        let output = temp_path("output.wav");
+        Self::start(
+            AudioConfig {
+                backend: OutputBackendKind::WavFile,
+                offline_output: OfflineOutputConfig {
+                    clock,
+                    wav_path: Some(output.clone()),
+                    ..config.offline_output.clone()
+                },
+                ..config
+            },
+            Some(output),
+        )
+    }
+
+    fn start(config: AudioConfig, output: Option<PathBuf>) -> Self {
+        let (service, handle) = AudioService::spawn(config).unwrap();
+        let events = service.subscribe_events();
+        Self {
+            service,
+            events,
+            handle: Some(handle),
+            output,
+            files: Vec::new(),
+        }
+    }
+
+    // A local source holding `bytes`.
+    pub fn source(&mut self, name: &str, bytes: Vec<u8>) -> SourceSpec {
+        let path = temp_path(name);
+        std::fs::write(&path, bytes).unwrap();
+        let source = SourceSpec::local(&path);
+        self.files.push(path);
+        source
+    }
+
+    pub fn send(&self, command: AudioCommand) {
+        self.service.send(command).unwrap();
+    }
+
+    pub fn open(&self, source: &SourceSpec, autoplay: bool) {
+        self.send(AudioCommand::Open {
+            source: source.clone(),
+            start_ms: 0,
+            autoplay,
+        });
+    }
+
+    pub fn wait_for(&self, matches: impl Fn(&AudioEvent) -> bool) {
+        let deadline = Instant::now() + TIMEOUT;
+        while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+            match self.events.recv_timeout(left) {
+                Ok(event) if matches(&event) => return,
+                Ok(_) => {}
+                Err(_) => break,
+            }
+        }
+        panic!("timed out waiting for audio event");
+    }
+
+    pub fn wait_for_state(&self, state: EngineState) {
+        self.wait_for(|event| matches!(event, AudioEvent::StateChanged { to, .. } if *to == state));
+    }
+
+    // Shuts the runtime down so the WAV output is complete, then reads it.
+    pub fn rendered(&mut self) -> Vec<f32> {
+        if let Some(handle) = self.handle.take() {
+            handle.shutdown().unwrap();
+        }
+        read_wav_samples(self.output.as_deref().expect("not recording"))
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.shutdown();
+        }
+        for path in self.files.iter().chain(&self.output) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+pub fn read_wav_samples(path: &Path) -> Vec<f32> {
+    let bytes = std::fs::read(path).unwrap();
+    assert_eq!(&bytes[..4], b"RIFF");
+    assert_eq!(u16::from_le_bytes([bytes[20], bytes[21]]), 3);
+    let data_len = u32::from_le_bytes(bytes[40..44].try_into().unwrap()) as usize;
+    assert_eq!(bytes.len(), 44 + data_len);
+    bytes[44..]
+        .chunks_exact(4)
+        .map(|sample| f32::from_le_bytes(sample.try_into().unwrap()))
+        .collect()
+}
+
+pub fn peak(samples: &[f32]) -> f32 {
+    samples
+        .iter()
+        .fold(0.0f32, |peak, sample| peak.max(sample.abs()))
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use ame_audio::{AudioConfig, AudioEvent, EngineState, OutputBackendKind};
+
+use common::{Harness, fixtures};
+
+#[test]
+fn device_loss_recovers_onto_a_new_stream() {
+    let mut harness = Harness::null();
+    let source = harness.source("source.wav", fixtures::wav(48_000, 2, 16, 48_000 * 5));
+    harness.open(&source, true);
+    harness.wait_for_state(EngineState::Playing);
+
+    harness.service.offline_faults().disconnect();
+    harness.wait_for(|event| matches!(event, AudioEvent::DeviceLost { .. }));
+    // The stream is rebuilt on the fallback device but stays paused, like
+    // unplugged headphones.
+    harness.wait_for_state(EngineState::Paused);
+    let snapshot = harness.service.snapshot();
+    assert_eq!(snapshot.device.as_deref(), Some("null"));
+    assert_eq!(snapshot.source, Some(source));
+}
+
+#[test]
+fn preferred_device_is_picked_up_again_when_it_returns() {
+    let mut harness = Harness::spawn(AudioConfig {
+        backend: OutputBackendKind::Null,
+        preferred_device: Some("usb-headphones".into()),
+        ..AudioConfig::default()
+    });
+    let faults = harness.service.offline_faults();
+    faults.plug("usb-headphones");
+    let source = harness.source("source.wav", fixtures::wav(48_000, 2, 16, 48_000 * 5));
+    harness.open(&source, true);
+    let changed_to = |name: &'static str| move |event: &AudioEvent| matches!(event, AudioEvent::DeviceChanged { device: Some(device), .. } if device == name);
+    harness.wait_for(changed_to("usb-headphones"));
+
+    faults.unplug("usb-headphones");
+    harness.wait_for(|event| matches!(event, AudioEvent::DeviceLost { .. }));
+    harness.wait_for(changed_to("null"));
+
+    faults.plug("usb-headphones");
+    harness.wait_for(|event| {
+        matches!(
+            event,
+            AudioEvent::DevicesChanged(devices)
+                if devices.iter().any(|device| device.name == "usb-headphones")
+        )
+    });
+    harness.wait_for(changed_to("usb-headphones"));
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use ame_audio::{
+    AudioCommand, AudioConfig, AudioEvent, EngineState, OfflineClock, OutputBackendKind,
+};
+
+use common::{Harness, TIMEOUT, fixtures};
+
+#[test]
+fn manual_fade_pauses_without_holding_up_the_runtime() {
+    let mut harness = Harness::spawn(AudioConfig {
+        backend: OutputBackendKind::Null,
+        manual_fade_ms: 600,
+        ..AudioConfig::default()
+    });
+    let source = harness.source("source.wav", fixtures::wav(48_000, 2, 16, 48_000 * 5));
+    harness.open(&source, true);
+    harness.wait_for_state(EngineState::Playing);
+
+    let snapshots = harness.service.subscribe_snapshot();
+    let paused_at = Instant::now();
+    harness.send(AudioCommand::Pause);
+    harness.send(AudioCommand::SetVolume(0.25));
+    // Snapshots keep coming while the fade runs out.
+    let snapshot = snapshots.recv_timeout(Duration::from_millis(200)).unwrap();
+    assert_eq!(snapshot.state, EngineState::Playing);
+
+    harness.wait_for_state(EngineState::Paused);
+    assert!(paused_at.elapsed() >= Duration::from_millis(600));
+    // Commands sent during the fade run after it, in order.
+    let deadline = Instant::now() + TIMEOUT;
+    while harness.service.snapshot().volume != 0.25 {
+        assert!(Instant::now() < deadline, "volume change was lost");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(harness.service.snapshot().state, EngineState::Paused);
+}
+
+#[test]
+fn manual_skip_crossfades_from_the_playhead() {
+    let mut harness = Harness::recording(
+        OfflineClock::Realtime,
+        AudioConfig {
+            manual_fade_ms: 600,
+            ..AudioConfig::default()
+        },
+    );
+    let first = harness.source("first.wav", fixtures::wav(48_000, 2, 16, 48_000 * 5));
+    let second = harness.source("second.wav", fixtures::wav(48_000, 2, 16, 48_000));
+    harness.open(&first, true);
+    harness.wait_for_state(EngineState::Playing);
+    std::thread::sleep(Duration::from_millis(500));
+
+    harness.open(&second, true);
+    let mut restarted = false;
+    loop {
+        match harness
+            .events
+            .recv_timeout(TIMEOUT)
+            .expect("track never ended")
+        {
+            AudioEvent::StateChanged {
+                to: EngineState::Loading,
+                ..
+            } => restarted = true,
+            AudioEvent::TrackEnded { next: None } => break,
+            _ => {}
+        }
+    }
+    assert!(!restarted, "the skip opened a new stream");
+    assert_eq!(harness.service.snapshot().source, Some(second));
+
+    // The second track starts under the fade-out instead of after it, and
+    // without waiting for the queued part of the first one.
+    let rendered_ms = harness.rendered().len() as u64 / 96;
+    assert!(
+        (1_300..1_850).contains(&rendered_ms),
+        "rendered {rendered_ms} ms"
+    );
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use ame_audio::{AudioCommand, AudioConfig, AudioEvent, OfflineClock, SeekTarget};
+
+use common::{Harness, TIMEOUT, fixtures, peak};
+
+#[test]
+fn service_plays_a_track_to_the_end_without_hardware() {
+    let frames = 24_000;
+    let mut harness = Harness::recording(
+        OfflineClock::Freewheel,
+        AudioConfig {
+            volume: 1.0,
+            ..AudioConfig::default()
+        },
+    );
+    let source = harness.source("source.wav", fixtures::wav(48_000, 2, 16, frames));
+    harness.open(&source, true);
+    harness.wait_for(|event| matches!(event, AudioEvent::TrackEnded { next: None }));
+
+    let rendered = harness.rendered();
+    assert_eq!(rendered.len(), frames * 2);
+    let peak = peak(&rendered);
+    assert!((peak - 0.5).abs() < 0.01, "peak = {peak}");
+}
+
+#[test]
+fn tagged_file_reports_its_metadata_once() {
+    let mut harness = Harness::null();
+    let tags: [(&[u8; 4], &str); 4] = [
+        (b"INAM", "Blue"),
+        (b"IART", "Someone"),
+        (b"IPRD", "Colours"),
+        (b"IPRT", "3"),
+    ];
+    let source = harness.source(
+        "tagged.wav",
+        fixtures::wav_with_info(48_000, 2, 16, 48_000, &tags),
+    );
+    harness.open(&source, false);
+
+    let deadline = Instant::now() + TIMEOUT;
+    let metadata = loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if let AudioEvent::MetadataReady {
+            source: tagged,
+            metadata,
+        } = harness
+            .events
+            .recv_timeout(left)
+            .expect("no metadata reported")
+        {
+            assert_eq!(tagged, source);
+            break metadata;
+        }
+    };
+    assert_eq!(metadata.title.as_deref(), Some("Blue"));
+    assert_eq!(metadata.artist.as_deref(), Some("Someone"));
+    assert_eq!(metadata.album.as_deref(), Some("Colours"));
+    assert_eq!(metadata.track_number, Some(3));
+    assert_eq!(metadata.artwork, None);
+
+    // Seeking reopens the file without announcing the same tags again.
+    harness.send(AudioCommand::Seek(SeekTarget::Milliseconds(500)));
+    std::thread::sleep(Duration::from_millis(300));
+    assert!(
+        harness
+            .events
+            .try_iter()
+            .all(|event| !matches!(event, AudioEvent::MetadataReady { .. }))
+    );
+}
+
+#[test]
+fn changing_a_committed_next_track_defers_to_the_following_boundary() {
+    let mut harness = Harness::null();
+    let first = harness.source("first.wav", fixtures::wav(48_000, 2, 16, 48_000));
+    let second = harness.source("second.wav", fixtures::wav(48_000, 2, 16, 48_000 * 3));
+    let third = harness.source("third.wav", fixtures::wav(48_000, 2, 16, 24_000));
+    harness.send(AudioCommand::SetNext(Some(second.clone())));
+    harness.open(&first, true);
+    // The first track fits in the ring, so the decoder commits the second
+    // one long before the boundary is heard.
+    std::thread::sleep(Duration::from_millis(300));
+    harness.send(AudioCommand::SetNext(Some(third.clone())));
+
+    let mut ended = Vec::new();
+    let mut streams = 0;
+    let deadline = Instant::now() + TIMEOUT;
+    while ended.last() != Some(&None) {
+        let left = deadline.saturating_duration_since(Instant::now());
+        match harness
+            .events
+            .recv_timeout(left)
+            .expect("playback never finished")
+        {
+            AudioEvent::DeviceChanged { .. } => streams += 1,
+            AudioEvent::TrackEnded { next } => ended.push(next),
+            _ => {}
+        }
+    }
+    assert_eq!(ended, [Some(second), Some(third), None]);
+    assert_eq!(streams, 1);
+}
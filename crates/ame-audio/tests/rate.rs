@@ -0,0 +1,39 @@
+mod common;
+
+use std::time::Duration;
+
+use ame_audio::{AudioCommand, AudioConfig, AudioEvent, EngineState, OfflineClock};
+
+use common::{Harness, fixtures};
+
+#[test]
+fn playback_rate_shortens_the_rendered_track() {
+    let frames = 48_000;
+    let mut harness = Harness::recording(OfflineClock::Freewheel, AudioConfig::default());
+    let source = harness.source("source.wav", fixtures::wav(48_000, 2, 16, frames));
+    harness.send(AudioCommand::SetPlaybackRate(2.0));
+    harness.open(&source, true);
+    harness.wait_for(|event| matches!(event, AudioEvent::TrackEnded { next: None }));
+    assert_eq!(harness.service.snapshot().playback_rate, 2.0);
+
+    let rendered_frames = harness.rendered().len() / 2;
+    assert!(
+        rendered_frames.abs_diff(frames / 2) < frames / 20,
+        "rendered {rendered_frames} frames"
+    );
+}
+
+#[test]
+fn rate_change_moves_the_position_once_the_buffer_plays_out() {
+    let mut harness = Harness::null();
+    let source = harness.source("source.wav", fixtures::wav(48_000, 2, 16, 48_000 * 10));
+    harness.open(&source, true);
+    harness.wait_for_state(EngineState::Playing);
+    std::thread::sleep(Duration::from_millis(500));
+
+    // A full ring at the old rate is still ahead of the output.
+    harness.send(AudioCommand::SetPlaybackRate(2.0));
+    std::thread::sleep(Duration::from_millis(1_000));
+    let position = harness.service.snapshot().position_ms;
+    assert!((1_200..2_000).contains(&position), "position {position} ms");
+}
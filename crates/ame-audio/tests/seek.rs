@@ -0,0 +1,76 @@
+mod common;
+
+use std::time::Duration;
+
+use ame_audio::{AudioCommand, AudioConfig, AudioError, AudioEvent, EngineState, OfflineClock};
+
+use common::{Harness, fixtures};
+
+#[test]
+fn loop_region_repeats_with_sample_accuracy() {
+    let frames = 48_000;
+    let loop_frames = 48_000 / 5;
+    let mut harness = Harness::recording(OfflineClock::Freewheel, AudioConfig::default());
+    let source = harness.source("source.wav", fixtures::wav(48_000, 2, 16, frames));
+    harness.open(&source, false);
+    harness.send(AudioCommand::SetLoop {
+        start_ms: 200,
+        end_ms: 400,
+    });
+    harness.send(AudioCommand::Play);
+    std::thread::sleep(Duration::from_millis(300));
+    assert_eq!(harness.service.snapshot().loop_region, Some((200, 400)));
+    harness.send(AudioCommand::ClearLoop);
+    harness.wait_for(|event| matches!(event, AudioEvent::TrackEnded { next: None }));
+
+    let rendered_frames = harness.rendered().len() / 2;
+    let extra = rendered_frames - frames;
+    assert!(
+        extra > 0 && extra.is_multiple_of(loop_frames),
+        "rendered {rendered_frames} frames"
+    );
+}
+
+#[test]
+fn loop_around_the_playhead_keeps_the_stream_running() {
+    let mut harness = Harness::null();
+    let source = harness.source("source.wav", fixtures::wav(48_000, 2, 16, 48_000 * 10));
+    harness.open(&source, true);
+    harness.wait_for_state(EngineState::Playing);
+    std::thread::sleep(Duration::from_millis(300));
+
+    harness.send(AudioCommand::SetLoop {
+        start_ms: 0,
+        end_ms: 8_000,
+    });
+    std::thread::sleep(Duration::from_millis(300));
+    let restarted = harness
+        .events
+        .try_iter()
+        .any(|event| matches!(event, AudioEvent::StateChanged { .. }));
+    assert!(!restarted, "setting the loop restarted the stream");
+    let snapshot = harness.service.snapshot();
+    assert_eq!(snapshot.loop_region, Some((0, 8_000)));
+    assert!(
+        snapshot.position_ms >= 500,
+        "position {} ms",
+        snapshot.position_ms
+    );
+}
+
+#[test]
+fn loop_past_the_end_of_the_track_is_rejected() {
+    let mut harness = Harness::null();
+    let source = harness.source("source.wav", fixtures::wav(48_000, 2, 16, 48_000));
+    harness.open(&source, false);
+    while harness.service.snapshot().duration_ms == 0 {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    harness.send(AudioCommand::SetLoop {
+        start_ms: 500,
+        end_ms: 2_000,
+    });
+    harness.wait_for(|event| matches!(event, AudioEvent::Error(AudioError::ConfigInvalid { .. })));
+    assert_eq!(harness.service.snapshot().loop_region, None);
+}
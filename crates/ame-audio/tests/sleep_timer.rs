@@ -0,0 +1,39 @@
+mod common;
+
+use ame_audio::{AudioCommand, AudioConfig, AudioEvent, EngineState, OfflineClock};
+
+use common::{Harness, fixtures, peak};
+
+#[test]
+fn sleep_timer_fades_out_and_pauses() {
+    let mut harness = Harness::recording(OfflineClock::Realtime, AudioConfig::default());
+    let source = harness.source("source.wav", fixtures::wav(48_000, 2, 16, 48_000 * 5));
+    harness.send(AudioCommand::StartSleepTimer {
+        after_ms: 800,
+        fade_ms: 400,
+    });
+    harness.open(&source, true);
+    harness.wait_for_state(EngineState::Playing);
+    assert!(
+        harness
+            .service
+            .snapshot()
+            .sleep_timer_remaining_ms
+            .is_some()
+    );
+    harness.wait_for(|event| matches!(event, AudioEvent::SleepTimerFired));
+
+    let snapshot = harness.service.snapshot();
+    assert_eq!(snapshot.state, EngineState::Paused);
+    assert_eq!(snapshot.sleep_timer_remaining_ms, None);
+    assert!(snapshot.position_ms < 1_500, "{}", snapshot.position_ms);
+
+    let rendered = harness.rendered();
+    assert!(
+        rendered.len() > 48_000,
+        "rendered {} samples",
+        rendered.len()
+    );
+    assert!(peak(&rendered[..48_000]) > 0.3);
+    assert!(peak(&rendered[rendered.len() - 960..]) < 0.15);
+}
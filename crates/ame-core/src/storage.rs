@@ -30,6 +30,7 @@ pub struct AppStorage {
     weather: NetworkCacheBucketStorage,
     geological: NetworkCacheBucketStorage,
     response_dir: PathBuf,
+    audio_cache_dir: PathBuf,
 }
 
 impl AppStorage {
@@ -80,6 +81,7 @@ impl AppStorage {
                 "geological",
             ),
             response_dir: paths.response_dir,
+            audio_cache_dir: paths.audio_cache_dir,
         })
     }
 
@@ -116,6 +118,10 @@ impl AppStorage {
     pub fn response_dir(&self) -> &Path {
         &self.response_dir
     }
+
+    pub fn audio_cache_dir(&self) -> &Path {
+        &self.audio_cache_dir
+    }
 }
 
 #[derive(Clone)]
@@ -290,6 +296,7 @@ struct StoragePaths {
     weather_db: PathBuf,
     geological_db: PathBuf,
     response_dir: PathBuf,
+    audio_cache_dir: PathBuf,
 }
 
 impl StoragePaths {
@@ -303,6 +310,7 @@ impl StoragePaths {
             weather_db: cache_dir.join(WEATHER_DB_FILE_NAME),
             geological_db: cache_dir.join(GEOLOGICAL_DB_FILE_NAME),
             response_dir: cache_dir.join("response"),
+            audio_cache_dir: cache_dir.join("audio"),
         }
     }
 
@@ -311,6 +319,7 @@ impl StoragePaths {
             self.settings_db.parent(),
             self.firework_db.parent(),
             Some(self.response_dir.as_path()),
+            Some(self.audio_cache_dir.as_path()),
         ]
         .into_iter()
         .flatten()
@@ -508,5 +517,6 @@ mod tests {
     fn temporary_storage_has_cache_dirs() {
         let storage = AppStorage::temporary().expect("temp storage");
         assert!(storage.response_dir().exists());
+        assert!(storage.audio_cache_dir().exists());
     }
 }
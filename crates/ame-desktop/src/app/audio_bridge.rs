@@ -1,20 +1,20 @@
 use std::sync::mpsc::Receiver;
 
-use ame_audio::{AudioCommand, AudioEvent, AudioService, AudioSnapshot};
+use ame_audio::{AudioCommand, AudioEvent, AudioService, AudioSnapshot, LatestReceiver};
 
 use crate::domain::player::PlayerEntity;
 
 pub struct AudioBridgeEntity {
     service: AudioService,
     event_rx: Receiver<AudioEvent>,
-    snapshot_rx: Receiver<AudioSnapshot>,
+    snapshot_rx: LatestReceiver<AudioSnapshot>,
     pub last_error: Option<String>,
 }
 
 impl AudioBridgeEntity {
     pub fn new(service: AudioService) -> Self {
         let event_rx = service.subscribe_events();
-        let snapshot_rx = service.subscribe_snapshot();
+        let snapshot_rx = service.watch_snapshot();
         Self {
             service,
             event_rx,
@@ -32,15 +32,15 @@ impl AudioBridgeEntity {
     }
 
     pub fn drain(&mut self, player: &mut PlayerEntity) -> Vec<AudioEvent> {
-        while let Ok(snapshot) = self.snapshot_rx.try_recv() {
+        // Only the newest snapshot matters between two frames.
+        if let Some(snapshot) = self.snapshot_rx.try_take() {
             let is_idle_snapshot = snapshot.source.is_none()
                 && !snapshot.is_playing
                 && snapshot.position_ms == 0
                 && snapshot.duration_ms == 0;
-            if is_idle_snapshot {
-                continue;
+            if !is_idle_snapshot {
+                player.apply_audio_snapshot(&snapshot);
             }
-            player.apply_audio_snapshot(&snapshot);
         }
 
         let mut events = Vec::new();
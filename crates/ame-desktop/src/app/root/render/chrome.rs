@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use nekowg::{AnyElement, App, Context, Window, div, prelude::*, px};
+use nekowg::{AnyElement, App, Context, SharedString, Window, div, prelude::*, px};
 
 use crate::app::route::AppRoute;
 use crate::app::router;
@@ -9,6 +9,7 @@ use crate::component::{
     nav_bar::{self, NavBarActions, NavBarModel},
     title_bar::{self, TitleBarActions, TitleBarModel},
 };
+use crate::domain::player::PlayerEntity;
 use crate::domain::{favorites, player};
 
 use super::super::RootView;
@@ -132,11 +133,20 @@ impl RootView {
                 current_song: current_song.into(),
                 current_artist: current_artist.into(),
                 current_cover_url: current_cover_url.map(Into::into),
+                current_quality: current_item.as_ref().and_then(|item| {
+                    let label = item.quality?.label();
+                    Some(if item.trial {
+                        format!("{label} · 试听").into()
+                    } else {
+                        label.into()
+                    })
+                }),
                 favorite_liked,
                 favorite_enabled: favorite_ready,
                 favorite_pending,
                 is_playing: player.is_playing,
                 mode: player.mode,
+                sleep_timer_label: sleep_timer_label(&player),
                 volume: player.volume,
                 progress_slider: self.player_progress_slider.clone(),
                 volume_slider: self.player_volume_slider.clone(),
@@ -175,6 +185,30 @@ impl RootView {
                         });
                     })
                 },
+                on_start_sleep_timer: {
+                    let root_entity = root_entity.clone();
+                    Arc::new(move |after_ms, cx| {
+                        root_entity.update(cx, |this, cx| {
+                            player::start_sleep_timer(&this.runtime, after_ms, cx);
+                        });
+                    })
+                },
+                on_stop_after_tracks: {
+                    let root_entity = root_entity.clone();
+                    Arc::new(move |tracks, cx| {
+                        root_entity.update(cx, |this, cx| {
+                            player::stop_after_tracks(&this.runtime, tracks, cx);
+                        });
+                    })
+                },
+                on_cancel_sleep_timer: {
+                    let root_entity = root_entity.clone();
+                    Arc::new(move |cx| {
+                        root_entity.update(cx, |this, cx| {
+                            player::cancel_sleep_timer(&this.runtime, cx);
+                        });
+                    })
+                },
             },
         );
 
@@ -188,3 +222,21 @@ impl RootView {
             .into_any_element()
     }
 }
+
+fn sleep_timer_label(player: &PlayerEntity) -> Option<SharedString> {
+    if let Some(remaining_ms) = player.sleep_timer_remaining_ms {
+        let total_seconds = remaining_ms.div_ceil(1000);
+        let hours = total_seconds / 3600;
+        let minutes = total_seconds / 60 % 60;
+        let seconds = total_seconds % 60;
+        let countdown = if hours > 0 {
+            format!("{hours}:{minutes:02}:{seconds:02}")
+        } else {
+            format!("{minutes}:{seconds:02}")
+        };
+        return Some(countdown.into());
+    }
+    player
+        .sleep_after_tracks
+        .map(|tracks| format!("剩 {tracks} 首").into())
+}
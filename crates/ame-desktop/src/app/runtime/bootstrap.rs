@@ -1,6 +1,9 @@
 use std::sync::{Arc, Mutex};
 
-use ame_audio::{AudioCommand, AudioConfig, AudioService};
+use ame_audio::{
+    AudioCommand, AudioConfig, AudioService, EqualizerSettings, RuntimeConfigPatch,
+    StreamCacheConfig,
+};
 use ame_core::storage::AppStorage;
 use nekowg::{AppContext, Context};
 
@@ -11,13 +14,14 @@ use crate::domain::cache::CacheService;
 use crate::domain::favorites::FavoritesState;
 use crate::domain::player::{PlaybackMode, PlayerEntity, QueueItem};
 use crate::domain::session::{PersistedSessionIdentity, SessionState};
-use crate::domain::settings::{CloseBehavior, HomeArtistLanguage};
+use crate::domain::settings::{AudioQuality, CloseBehavior, HomeArtistLanguage};
 use crate::domain::shell::ShellState;
 
 use super::keys::{
-    KEY_HOME_ARTIST_LANGUAGE, KEY_PLAYER_CURRENT_INDEX, KEY_PLAYER_DURATION_MS, KEY_PLAYER_MODE,
-    KEY_PLAYER_POSITION_MS, KEY_PLAYER_QUEUE, KEY_PLAYER_VOLUME, KEY_PLAYER_WAS_PLAYING,
-    KEY_SESSION_IDENTITY, KEY_WINDOW_CLOSE_BEHAVIOR,
+    KEY_HOME_ARTIST_LANGUAGE, KEY_PLAYER_AUDIO_QUALITY, KEY_PLAYER_CURRENT_INDEX,
+    KEY_PLAYER_DURATION_MS, KEY_PLAYER_EQUALIZER, KEY_PLAYER_MODE, KEY_PLAYER_POSITION_MS,
+    KEY_PLAYER_QUEUE, KEY_PLAYER_VOLUME, KEY_PLAYER_WAS_PLAYING, KEY_SESSION_IDENTITY,
+    KEY_WINDOW_CLOSE_BEHAVIOR,
 };
 use super::{AppRuntime, AppServices, PersistedQueueItem, RuntimeBootstrap};
 
@@ -29,6 +33,8 @@ pub(super) fn bootstrap_runtime<T>(cx: &mut Context<T>) -> RuntimeBootstrap {
     let mut services = AppServices::default();
     let mut close_behavior = CloseBehavior::default();
     let mut home_artist_language = HomeArtistLanguage::default();
+    let mut audio_quality = AudioQuality::default();
+    let mut stream_cache = None;
     let (audio_bridge, audio_runtime, audio_error) =
         match AudioService::spawn(AudioConfig::default()) {
             Ok((service, runtime)) => (Some(AudioBridgeEntity::new(service)), Some(runtime), None),
@@ -49,6 +55,7 @@ pub(super) fn bootstrap_runtime<T>(cx: &mut Context<T>) -> RuntimeBootstrap {
                 Ok(storage) => {
                     services.settings_store = Some(storage.settings());
                     services.state_store = Some(storage.state());
+                    stream_cache = Some(StreamCacheConfig::new(storage.audio_cache_dir()));
                     services.network_cache = Some(Arc::new(CacheService::new(
                         storage.firework(),
                         storage.weather(),
@@ -95,6 +102,14 @@ pub(super) fn bootstrap_runtime<T>(cx: &mut Context<T>) -> RuntimeBootstrap {
                 format!("Failed to read playback mode: {err}"),
             ),
         }
+        match settings.get::<EqualizerSettings>(KEY_PLAYER_EQUALIZER) {
+            Ok(Some(equalizer)) => player_state.equalizer = equalizer,
+            Ok(None) => {}
+            Err(err) => push_message(
+                &mut startup_error,
+                format!("Failed to read equalizer settings: {err}"),
+            ),
+        }
         match settings.get::<CloseBehavior>(KEY_WINDOW_CLOSE_BEHAVIOR) {
             Ok(Some(value)) => close_behavior = value,
             Ok(None) => {}
@@ -111,6 +126,14 @@ pub(super) fn bootstrap_runtime<T>(cx: &mut Context<T>) -> RuntimeBootstrap {
                 format!("Failed to read home artist language: {err}"),
             ),
         }
+        match settings.get::<AudioQuality>(KEY_PLAYER_AUDIO_QUALITY) {
+            Ok(Some(value)) => audio_quality = value,
+            Ok(None) => {}
+            Err(err) => push_message(
+                &mut startup_error,
+                format!("Failed to read audio quality: {err}"),
+            ),
+        }
     }
 
     if let Some(audio_bridge) = services.audio_bridge.as_ref() {
@@ -119,6 +142,25 @@ pub(super) fn bootstrap_runtime<T>(cx: &mut Context<T>) -> RuntimeBootstrap {
                 if let Err(err) = bridge.send(AudioCommand::SetVolume(player_state.volume)) {
                     push_message(&mut startup_error, format!("Failed to set volume: {err}"));
                 }
+                if let Err(err) =
+                    bridge.send(AudioCommand::SetEqualizer(player_state.equalizer.clone()))
+                {
+                    push_message(
+                        &mut startup_error,
+                        format!("Failed to apply equalizer: {err}"),
+                    );
+                }
+                if let Some(stream_cache) = stream_cache
+                    && let Err(err) = bridge.send(AudioCommand::UpdateConfig(RuntimeConfigPatch {
+                        stream_cache: Some(Some(stream_cache)),
+                        ..RuntimeConfigPatch::default()
+                    }))
+                {
+                    push_message(
+                        &mut startup_error,
+                        format!("Failed to enable stream cache: {err}"),
+                    );
+                }
             }
             Err(err) => {
                 push_message(
@@ -144,6 +186,8 @@ pub(super) fn bootstrap_runtime<T>(cx: &mut Context<T>) -> RuntimeBootstrap {
                             duration_ms: item.duration_ms,
                             cover_url: item.cover_url,
                             source_url: None,
+                            quality: None,
+                            trial: false,
                         })
                         .collect(),
                 );
@@ -225,6 +269,7 @@ pub(super) fn bootstrap_runtime<T>(cx: &mut Context<T>) -> RuntimeBootstrap {
         app: cx.new(move |_| AppEntity {
             search_query: String::new(),
             home_artist_language,
+            audio_quality,
         }),
         favorites: cx.new(|_| FavoritesState::default()),
         player: cx.new(move |_| player_state.clone()),
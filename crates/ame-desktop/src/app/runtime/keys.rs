@@ -5,6 +5,8 @@ pub const KEY_PLAYER_CURRENT_INDEX: &str = "player.current_index";
 pub const KEY_PLAYER_POSITION_MS: &str = "player.position_ms";
 pub const KEY_PLAYER_DURATION_MS: &str = "player.duration_ms";
 pub const KEY_PLAYER_WAS_PLAYING: &str = "player.was_playing";
+pub const KEY_PLAYER_EQUALIZER: &str = "player.equalizer";
+pub const KEY_PLAYER_AUDIO_QUALITY: &str = "player.audio_quality";
 pub const KEY_WINDOW_CLOSE_BEHAVIOR: &str = "window.close_behavior";
 pub const KEY_HOME_ARTIST_LANGUAGE: &str = "home.artist_language";
 pub const KEY_SESSION_IDENTITY: &str = "session.identity";
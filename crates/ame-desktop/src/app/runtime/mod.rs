@@ -19,9 +19,10 @@ use crate::domain::session::SessionState;
 use crate::domain::shell::ShellState;
 
 pub use keys::{
-    KEY_HOME_ARTIST_LANGUAGE, KEY_PLAYER_CURRENT_INDEX, KEY_PLAYER_DURATION_MS, KEY_PLAYER_MODE,
-    KEY_PLAYER_POSITION_MS, KEY_PLAYER_QUEUE, KEY_PLAYER_VOLUME, KEY_PLAYER_WAS_PLAYING,
-    KEY_SESSION_IDENTITY, KEY_WINDOW_CLOSE_BEHAVIOR,
+    KEY_HOME_ARTIST_LANGUAGE, KEY_PLAYER_AUDIO_QUALITY, KEY_PLAYER_CURRENT_INDEX,
+    KEY_PLAYER_DURATION_MS, KEY_PLAYER_EQUALIZER, KEY_PLAYER_MODE, KEY_PLAYER_POSITION_MS,
+    KEY_PLAYER_QUEUE, KEY_PLAYER_VOLUME, KEY_PLAYER_WAS_PLAYING, KEY_SESSION_IDENTITY,
+    KEY_WINDOW_CLOSE_BEHAVIOR,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -2,6 +2,7 @@
 pub struct AppEntity {
     pub search_query: String,
     pub home_artist_language: crate::domain::settings::HomeArtistLanguage,
+    pub audio_quality: crate::domain::settings::AudioQuality,
 }
 
 impl AppEntity {
@@ -15,4 +16,8 @@ impl AppEntity {
     ) {
         self.home_artist_language = language;
     }
+
+    pub fn set_audio_quality(&mut self, quality: crate::domain::settings::AudioQuality) {
+        self.audio_quality = quality;
+    }
 }
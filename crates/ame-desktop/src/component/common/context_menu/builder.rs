@@ -177,7 +177,6 @@ impl ContextMenuBuilder {
         self
     }
 
-    #[allow(dead_code)]
     pub fn separator(mut self) -> Self {
         self.items.push(ContextMenuItem::Separator);
         self
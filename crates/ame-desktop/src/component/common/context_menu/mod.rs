@@ -67,6 +67,7 @@ pub struct ContextMenu<E: ParentElement + Styled + Sized> {
     element: Option<E>,
     menu: Option<ContextMenuBuilderFn>,
     anchor: Corner,
+    trigger: MouseButton,
     menu_style: ContextMenuStyle,
     _ignore_style: StyleRefinement,
 }
@@ -78,6 +79,7 @@ impl<E: ParentElement + Styled> ContextMenu<E> {
             element: Some(element),
             menu: None,
             anchor: Corner::TopLeft,
+            trigger: MouseButton::Right,
             menu_style: ContextMenuStyle::default(),
             _ignore_style: StyleRefinement::default(),
         }
@@ -91,12 +93,17 @@ impl<E: ParentElement + Styled> ContextMenu<E> {
         self
     }
 
-    #[allow(dead_code)]
     pub fn anchor(mut self, anchor: Corner) -> Self {
         self.anchor = anchor;
         self
     }
 
+    // Mouse button that opens the menu; right-click unless set otherwise.
+    pub fn trigger(mut self, button: MouseButton) -> Self {
+        self.trigger = button;
+        self
+    }
+
     #[allow(dead_code)]
     pub fn menu_style(mut self, style: ContextMenuStyle) -> Self {
         self.menu_style = style;
@@ -317,6 +324,7 @@ impl<E: ParentElement + Styled + IntoElement + 'static> Element for ContextMenu<
 
         let builder = self.menu.clone();
         let menu_style = self.menu_style;
+        let trigger = self.trigger;
 
         self.with_element_state(
             id.expect("context_menu requires global id"),
@@ -329,10 +337,7 @@ impl<E: ParentElement + Styled + IntoElement + 'static> Element for ContextMenu<
                 let hitbox = hitbox.clone();
 
                 window.on_mouse_event(move |event: &MouseDownEvent, phase, window, cx| {
-                    if !phase.bubble()
-                        || event.button != MouseButton::Right
-                        || !hitbox.is_hovered(window)
-                    {
+                    if !phase.bubble() || event.button != trigger || !hitbox.is_hovered(window) {
                         return;
                     }
 
@@ -1,10 +1,11 @@
 use std::sync::Arc;
 
 use nekowg::{
-    AnyElement, App, Div, Entity, FontWeight, MouseButton, SharedString, div, img, prelude::*, px,
-    relative, rgb, rgba,
+    AnyElement, App, Corner, Div, Entity, FontWeight, MouseButton, SharedString, div, img,
+    prelude::*, px, relative, rgb, rgba,
 };
 
+use crate::component::context_menu::ContextMenuExt;
 use crate::component::theme;
 use crate::component::{
     button,
@@ -15,6 +16,11 @@ use crate::domain::player::PlaybackMode;
 use crate::util::url::image_resize_url;
 
 pub type BottomBarAction = Arc<dyn Fn(&mut App)>;
+pub type SleepTimerAction = Arc<dyn Fn(u64, &mut App)>;
+pub type StopAfterTracksAction = Arc<dyn Fn(u32, &mut App)>;
+
+const SLEEP_TIMER_MINUTES: [u64; 4] = [15, 30, 60, 90];
+const STOP_AFTER_TRACKS: [u32; 3] = [1, 3, 5];
 
 #[derive(Debug, Clone)]
 pub struct BottomBarModel {
@@ -22,11 +28,15 @@ pub struct BottomBarModel {
     pub current_song: SharedString,
     pub current_artist: SharedString,
     pub current_cover_url: Option<SharedString>,
+    // Granted quality of the stream that is playing.
+    pub current_quality: Option<SharedString>,
     pub favorite_liked: bool,
     pub favorite_enabled: bool,
     pub favorite_pending: bool,
     pub is_playing: bool,
     pub mode: PlaybackMode,
+    // Countdown of the sleep timer, or the tracks left before playback stops.
+    pub sleep_timer_label: Option<SharedString>,
     pub volume: f32,
     pub progress_slider: Entity<slider::SliderState>,
     pub volume_slider: Entity<slider::SliderState>,
@@ -40,6 +50,9 @@ pub struct BottomBarActions {
     pub on_toggle_favorite: Option<BottomBarAction>,
     pub on_open_queue: BottomBarAction,
     pub on_cycle_mode: BottomBarAction,
+    pub on_start_sleep_timer: SleepTimerAction,
+    pub on_stop_after_tracks: StopAfterTracksAction,
+    pub on_cancel_sleep_timer: BottomBarAction,
 }
 
 pub fn left_section(content: AnyElement) -> Div {
@@ -170,6 +183,18 @@ pub fn render(model: &BottomBarModel, actions: &BottomBarActions) -> AnyElement
                                 .child(model.current_artist.clone()),
                         ),
                 )
+                .children(model.current_quality.clone().map(|quality| {
+                    div()
+                        .flex_none()
+                        .px(px(4.))
+                        .rounded(px(3.))
+                        .border(px(1.))
+                        .border_color(rgb(theme::COLOR_SECONDARY))
+                        .text_size(px(10.))
+                        .text_color(rgb(theme::COLOR_SECONDARY))
+                        .child(quality)
+                        .into_any_element()
+                }))
                 .children(model.has_current_song.then(|| {
                     let favorite_color = if model.favorite_liked {
                         theme::COLOR_PRIMARY
@@ -263,6 +288,7 @@ pub fn render(model: &BottomBarModel, actions: &BottomBarActions) -> AnyElement
         .flex()
         .items_center()
         .gap_1()
+        .child(sleep_timer_button(model, actions))
         .child(button::icon_interactive(
             "player-queue",
             button::icon_base(button::ButtonStyle::default())
@@ -316,6 +342,61 @@ pub fn render(model: &BottomBarModel, actions: &BottomBarActions) -> AnyElement
     .into_any_element()
 }
 
+fn sleep_timer_button(model: &BottomBarModel, actions: &BottomBarActions) -> AnyElement {
+    let active = model.sleep_timer_label.is_some();
+    let color = if active {
+        theme::COLOR_PRIMARY
+    } else {
+        theme::COLOR_TEXT_DARK
+    };
+    let label = model
+        .sleep_timer_label
+        .clone()
+        .unwrap_or_else(|| "定时".into());
+    let actions = actions.clone();
+
+    div()
+        .child(button::icon_interactive(
+            "player-sleep-timer",
+            button::icon_base(button::ButtonStyle::default())
+                .h(px(36.))
+                .px(px(8.))
+                .text_size(px(12.))
+                .text_color(rgb(color))
+                .child(label),
+            button::ButtonStyle::default(),
+        ))
+        .context_menu_with_id("player-sleep-timer-menu", move |menu, _window, _cx| {
+            let menu = SLEEP_TIMER_MINUTES
+                .into_iter()
+                .fold(menu, |menu, minutes| {
+                    let on_start = actions.on_start_sleep_timer.clone();
+                    menu.item(format!("{minutes} 分钟后停止"), move |_window, cx| {
+                        on_start(minutes * 60_000, cx)
+                    })
+                })
+                .separator();
+            let menu = STOP_AFTER_TRACKS.into_iter().fold(menu, |menu, tracks| {
+                let on_stop_after = actions.on_stop_after_tracks.clone();
+                let label = if tracks == 1 {
+                    "播放完当前歌曲后停止".to_string()
+                } else {
+                    format!("播放完 {tracks} 首后停止")
+                };
+                menu.item(label, move |_window, cx| on_stop_after(tracks, cx))
+            });
+            if !active {
+                return menu;
+            }
+            let on_cancel = actions.on_cancel_sleep_timer.clone();
+            menu.separator()
+                .item("取消定时", move |_window, cx| on_cancel(cx))
+        })
+        .trigger(MouseButton::Left)
+        .anchor(Corner::BottomRight)
+        .into_any_element()
+}
+
 fn mode_icon(mode: PlaybackMode) -> IconName {
     match mode {
         PlaybackMode::Shuffle => IconName::Shuffle,
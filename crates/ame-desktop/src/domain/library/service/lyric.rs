@@ -1,5 +1,5 @@
 use ame_netease::NeteaseClient;
-use ame_netease::api::track::lyric::TrackLyricRequest;
+use ame_netease::api::track::lyric::TrackLyricV1Request;
 use anyhow::Result;
 use rand::RngExt;
 
@@ -9,9 +9,8 @@ use super::parse;
 
 pub fn fetch_track_lyric_preview_blocking(track_id: i64, cookie: &str) -> Result<Vec<String>> {
     let client = NeteaseClient::with_cookie(cookie);
-    let response = block_on(client.weapi_request(TrackLyricRequest::new(track_id)))?;
-    let raw = response.main_lyric().unwrap_or_default();
-    let mut lines = parse::parse_lyric_lines(raw);
+    let response = block_on(client.eapi_request(TrackLyricV1Request::new(track_id)))?;
+    let mut lines = parse::parse_lyric_lines(&response.lyrics());
     if lines.is_empty() {
         return Ok(Vec::new());
     }
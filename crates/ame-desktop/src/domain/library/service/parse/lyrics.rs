@@ -1,7 +1,10 @@
-pub(in crate::domain::library::service) fn parse_lyric_lines(raw: &str) -> Vec<String> {
-    raw.lines()
-        .filter_map(|line| line.split(']').next_back())
-        .map(|line| line.trim())
+use ame_netease::Lyrics;
+
+pub(in crate::domain::library::service) fn parse_lyric_lines(lyrics: &Lyrics) -> Vec<String> {
+    lyrics
+        .lines
+        .iter()
+        .map(|line| line.text.trim())
         .filter(|line| !line.is_empty())
         .filter(|line| {
             !line.contains("作词")
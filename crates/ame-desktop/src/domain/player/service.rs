@@ -1,6 +1,6 @@
 use ame_netease::api::track::detail::TrackDetailRequest;
-use ame_netease::api::track::url::TrackUrlRequest;
-use anyhow::{Context as _, Result};
+use ame_netease::api::track::url::{AudioQuality, TrackUrlRequest};
+use anyhow::{Context as _, Result, bail};
 
 use crate::domain::runtime::{block_on, netease_client};
 
@@ -16,21 +16,57 @@ fn compact_cover_url(raw: Option<&str>, size: u32) -> Option<String> {
     Some(format!("{raw}{separator}param={size}y{size}"))
 }
 
-pub fn fetch_track_url_blocking(track_id: i64, cookie: Option<&str>) -> Result<String> {
-    let client = netease_client(cookie);
-    let response = block_on(client.eapi_request(TrackUrlRequest::with_level(
-        vec![track_id],
-        // TODO: 音质切换
-        "jymaster".to_string(),
-    )))?;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackStream {
+    pub url: String,
+    pub bitrate: u32,
+    pub quality: AudioQuality,
+    // Only a preview clip was granted.
+    pub trial: bool,
+}
 
-    response
-        .data
-        .first()
-        .and_then(|item| item.url.as_deref())
-        .filter(|url| !url.is_empty())
-        .map(ToString::to_string)
-        .context("track url missing in response")
+// Asks for `preferred` first and steps down one level at a time while the
+// account gets no URL for it. The server already downgrades to what it
+// grants, so this only matters for levels it refuses outright. A preview clip
+// is kept as a last resort in case a lower level is granted in full.
+pub fn fetch_track_url_blocking(
+    track_id: i64,
+    preferred: AudioQuality,
+    cookie: Option<&str>,
+) -> Result<TrackStream> {
+    let client = netease_client(cookie);
+    let mut level = Some(preferred);
+    let mut trial = None;
+    while let Some(requested) = level {
+        let response =
+            block_on(client.eapi_request(TrackUrlRequest::with_level(vec![track_id], requested)))?;
+        let item = response
+            .data
+            .first()
+            .context("track url missing in response")?;
+        if let Some(url) = item.playable_url() {
+            let stream = TrackStream {
+                url: url.to_string(),
+                bitrate: item
+                    .br
+                    .and_then(|br| u32::try_from(br).ok())
+                    .unwrap_or_default(),
+                quality: item.granted_quality().unwrap_or(requested),
+                trial: item.is_trial(),
+            };
+            if !stream.trial {
+                return Ok(stream);
+            }
+            trial.get_or_insert(stream);
+            if item.requires_purchase() {
+                break;
+            }
+        } else if item.requires_purchase() {
+            return trial.context("track requires a subscription or purchase");
+        }
+        level = requested.fallback();
+    }
+    trial.context("track url missing in response")
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
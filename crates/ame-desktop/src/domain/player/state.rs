@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use ame_audio::{EngineState, EqualizerSettings, SourceSpec};
+use ame_netease::api::track::url::AudioQuality;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -19,6 +21,20 @@ pub struct QueueItem {
     pub duration_ms: Option<u64>,
     pub cover_url: Option<String>,
     pub source_url: Option<String>,
+    // Level the account was granted for `source_url`.
+    pub quality: Option<AudioQuality>,
+    // `source_url` is only a preview clip.
+    pub trial: bool,
+}
+
+// The queue entry whose source has been handed to the audio runtime ahead of
+// time. `source` is `None` while its URL is still being resolved or when that
+// failed, so it is not retried on every tick.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefetchedTrack {
+    pub index: usize,
+    pub track_id: i64,
+    pub source: Option<SourceSpec>,
 }
 
 #[derive(Debug, Clone)]
@@ -28,8 +44,17 @@ pub struct PlayerEntity {
     pub current_index: Option<usize>,
     pub is_playing: bool,
     pub volume: f32,
+    pub equalizer: EqualizerSettings,
     pub position_ms: u64,
     pub duration_ms: u64,
+    pub is_buffering: bool,
+    // Share of the current network stream fetched so far, for the slider.
+    pub downloaded_ratio: Option<f32>,
+    pub prefetched: Option<PrefetchedTrack>,
+    // Countdown of the audio runtime's sleep timer, for the bottom bar.
+    pub sleep_timer_remaining_ms: Option<u64>,
+    // Tracks left to finish before playback stops, counting the current one.
+    pub sleep_after_tracks: Option<u32>,
     queue_index_by_id: HashMap<i64, usize>,
     shuffle_seed: u64,
 }
@@ -42,8 +67,14 @@ impl Default for PlayerEntity {
             current_index: None,
             is_playing: false,
             volume: 0.7,
+            equalizer: EqualizerSettings::default(),
             position_ms: 0,
             duration_ms: 180_000,
+            is_buffering: false,
+            downloaded_ratio: None,
+            prefetched: None,
+            sleep_timer_remaining_ms: None,
+            sleep_after_tracks: None,
             queue_index_by_id: HashMap::new(),
             shuffle_seed: 0x9E37_79B9_7F4A_7C15,
         }
@@ -56,6 +87,39 @@ impl PlayerEntity {
         self.volume = snapshot.volume.clamp(0.0, 1.0);
         self.position_ms = snapshot.position_ms;
         self.duration_ms = snapshot.duration_ms;
+        self.is_buffering = snapshot.state == EngineState::Buffering;
+        self.sleep_timer_remaining_ms = snapshot.sleep_timer_remaining_ms;
+        if snapshot.source.as_ref().and_then(SourceSpec::url).is_none() {
+            self.downloaded_ratio = None;
+        }
+    }
+
+    pub fn apply_buffer_health(&mut self, downloaded_bytes: u64, total_bytes: Option<u64>) {
+        self.downloaded_ratio = total_bytes
+            .filter(|total| *total > 0)
+            .map(|total| (downloaded_bytes as f32 / total as f32).clamp(0.0, 1.0));
+    }
+
+    // Whether playback should stop once the current track is over, in which
+    // case nothing is queued behind it.
+    pub fn stops_after_current(&self) -> bool {
+        self.sleep_after_tracks == Some(1)
+    }
+
+    // Counts a finished track against the track sleep timer. Returns `true`
+    // when that was the last one and playback should stop.
+    pub fn finish_track_for_sleep(&mut self) -> bool {
+        match self.sleep_after_tracks {
+            Some(left) if left <= 1 => {
+                self.sleep_after_tracks = None;
+                true
+            }
+            Some(left) => {
+                self.sleep_after_tracks = Some(left - 1);
+                false
+            }
+            None => false,
+        }
     }
 
     pub fn enqueue(&mut self, item: QueueItem) {
@@ -74,6 +138,12 @@ impl PlayerEntity {
             .and_then(|item| (item.id == id).then_some(index))
     }
 
+    pub fn index_of_source_url(&self, url: &str) -> Option<usize> {
+        self.queue
+            .iter()
+            .position(|item| item.source_url.as_deref() == Some(url))
+    }
+
     pub fn remove_at(&mut self, index: usize) {
         if index >= self.queue.len() {
             return;
@@ -111,6 +181,7 @@ impl PlayerEntity {
     pub fn clear(&mut self) {
         self.queue.clear();
         self.current_index = None;
+        self.prefetched = None;
         self.queue_index_by_id.clear();
     }
 
@@ -138,6 +209,30 @@ impl PlayerEntity {
     }
 
     pub fn next_index(&mut self) -> Option<usize> {
+        let (next, seed) = self.upcoming()?;
+        self.shuffle_seed = seed;
+        self.current_index = Some(next);
+        Some(next)
+    }
+
+    // Same pick as `next_index` without advancing, so the next track can be
+    // prepared while the current one is still playing.
+    pub fn peek_next_index(&self) -> Option<usize> {
+        self.upcoming().map(|(index, _)| index)
+    }
+
+    // Moves to a track the audio runtime already continued into. Shuffle state
+    // only advances when it was the predicted pick.
+    pub fn advance_to(&mut self, index: usize) {
+        if let Some((next, seed)) = self.upcoming()
+            && next == index
+        {
+            self.shuffle_seed = seed;
+        }
+        self.current_index = Some(index);
+    }
+
+    fn upcoming(&self) -> Option<(usize, u64)> {
         let len = self.queue.len();
         if len == 0 {
             return None;
@@ -153,11 +248,9 @@ impl PlayerEntity {
                 }
             }
             PlaybackMode::SingleRepeat => current.min(len - 1),
-            PlaybackMode::Shuffle => self.pseudo_random_index(len, current),
+            PlaybackMode::Shuffle => return Some(self.pseudo_random_index(len, current)),
         };
-
-        self.current_index = Some(next);
-        Some(next)
+        Some((next, self.shuffle_seed))
     }
 
     pub fn prev_index(&mut self) -> Option<usize> {
@@ -181,20 +274,20 @@ impl PlayerEntity {
         Some(prev)
     }
 
-    fn pseudo_random_index(&mut self, len: usize, current: usize) -> usize {
+    fn pseudo_random_index(&self, len: usize, current: usize) -> (usize, u64) {
         if len == 1 {
-            return 0;
+            return (0, self.shuffle_seed);
         }
 
-        self.shuffle_seed = self
+        let seed = self
             .shuffle_seed
             .wrapping_mul(6364136223846793005)
             .wrapping_add(1);
-        let mut idx = (self.shuffle_seed as usize) % len;
+        let mut idx = (seed as usize) % len;
         if idx == current {
             idx = (idx + 1) % len;
         }
-        idx
+        (idx, seed)
     }
 }
 
@@ -213,6 +306,8 @@ mod tests {
             duration_ms: None,
             cover_url: None,
             source_url: None,
+            quality: None,
+            trial: false,
         });
         p.enqueue(QueueItem {
             id: 2,
@@ -223,6 +318,8 @@ mod tests {
             duration_ms: None,
             cover_url: None,
             source_url: None,
+            quality: None,
+            trial: false,
         });
         p.enqueue(QueueItem {
             id: 3,
@@ -233,6 +330,8 @@ mod tests {
             duration_ms: None,
             cover_url: None,
             source_url: None,
+            quality: None,
+            trial: false,
         });
         p
     }
@@ -262,6 +361,38 @@ mod tests {
         assert_ne!(next, 1);
     }
 
+    #[test]
+    fn peek_matches_the_next_pick() {
+        let mut p = build_player();
+        p.current_index = Some(1);
+        p.mode = PlaybackMode::Shuffle;
+        for _ in 0..8 {
+            let peeked = p.peek_next_index();
+            assert_eq!(p.peek_next_index(), peeked);
+            assert_eq!(p.next_index(), peeked);
+        }
+
+        let predicted = p.peek_next_index().expect("next");
+        let mut expected = p.clone();
+        expected.next_index();
+        p.advance_to(predicted);
+        assert_eq!(p.current_index, Some(predicted));
+        assert_eq!(p.peek_next_index(), expected.peek_next_index());
+    }
+
+    #[test]
+    fn sleep_after_tracks_counts_down() {
+        let mut p = build_player();
+        assert!(!p.finish_track_for_sleep());
+
+        p.sleep_after_tracks = Some(2);
+        assert!(!p.stops_after_current());
+        assert!(!p.finish_track_for_sleep());
+        assert!(p.stops_after_current());
+        assert!(p.finish_track_for_sleep());
+        assert_eq!(p.sleep_after_tracks, None);
+    }
+
     #[test]
     fn cycle_mode_rotates() {
         let mut p = PlayerEntity::default();
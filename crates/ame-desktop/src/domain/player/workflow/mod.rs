@@ -6,8 +6,9 @@ mod types;
 
 pub use persist::persist_progress_by_interval;
 pub use playback::{
-    commit_seek_ratio, cycle_play_mode, play_next, play_previous, prepare_app_exit,
-    preview_seek_ratio, set_volume_absolute, sync_audio_bridge, toggle_playback,
+    cancel_sleep_timer, commit_seek_ratio, cycle_play_mode, play_next, play_previous,
+    prepare_app_exit, preview_seek_ratio, set_equalizer, set_volume_absolute, start_sleep_timer,
+    stop_after_tracks, sync_audio_bridge, toggle_playback,
 };
 pub use queue::{clear_queue, enqueue_track, play_queue_item, remove_queue_item, replace_queue};
 pub use types::QueueTrackInput;
@@ -3,9 +3,9 @@ use std::time::{Duration, Instant};
 use nekowg::Context;
 
 use crate::app::runtime::{
-    AppRuntime, KEY_PLAYER_CURRENT_INDEX, KEY_PLAYER_DURATION_MS, KEY_PLAYER_MODE,
-    KEY_PLAYER_POSITION_MS, KEY_PLAYER_QUEUE, KEY_PLAYER_VOLUME, KEY_PLAYER_WAS_PLAYING,
-    KEY_WINDOW_CLOSE_BEHAVIOR, PersistedQueueItem,
+    AppRuntime, KEY_PLAYER_CURRENT_INDEX, KEY_PLAYER_DURATION_MS, KEY_PLAYER_EQUALIZER,
+    KEY_PLAYER_MODE, KEY_PLAYER_POSITION_MS, KEY_PLAYER_QUEUE, KEY_PLAYER_VOLUME,
+    KEY_PLAYER_WAS_PLAYING, KEY_WINDOW_CLOSE_BEHAVIOR, PersistedQueueItem,
 };
 use crate::domain::session as auth;
 
@@ -34,6 +34,9 @@ pub(super) fn persist_player_settings<T>(runtime: &AppRuntime, cx: &mut Context<
     if let Err(err) = settings.set(KEY_PLAYER_VOLUME, &player.volume) {
         errors.push(format!("Failed to persist volume: {err}"));
     }
+    if let Err(err) = settings.set(KEY_PLAYER_EQUALIZER, &player.equalizer) {
+        errors.push(format!("Failed to persist equalizer settings: {err}"));
+    }
     if let Err(err) = settings.set(KEY_PLAYER_MODE, &player.mode) {
         errors.push(format!("Failed to persist playback mode: {err}"));
     }
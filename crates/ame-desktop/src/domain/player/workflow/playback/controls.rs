@@ -1,4 +1,4 @@
-use ame_audio::{AudioCommand, AudioError, SeekTarget};
+use ame_audio::{AudioCommand, AudioError, EqualizerSettings, SeekTarget, SourceSpec};
 use nekowg::Context;
 
 use crate::app::runtime::AppRuntime;
@@ -10,7 +10,10 @@ use super::super::bridge::{
 use super::super::persist::{
     persist_player_progress, persist_player_runtime, persist_player_settings,
 };
-use super::{refresh_current_track_url_and_resume, start_playback_at};
+use super::{prefetch_next_track, refresh_current_track_url_and_resume, start_playback_at};
+
+// Fade applied by the audio runtime before a timed sleep pauses playback.
+const SLEEP_TIMER_FADE_MS: u64 = 10_000;
 
 pub fn set_volume_absolute<T>(runtime: &AppRuntime, volume: f32, cx: &mut Context<T>) {
     let volume = volume.clamp(0.0, 1.0);
@@ -26,6 +29,71 @@ pub fn set_volume_absolute<T>(runtime: &AppRuntime, volume: f32, cx: &mut Contex
     persist_player_settings(runtime, cx);
 }
 
+pub fn set_equalizer<T>(runtime: &AppRuntime, equalizer: EqualizerSettings, cx: &mut Context<T>) {
+    runtime.player.update(cx, |player, cx| {
+        player.equalizer = equalizer.clone();
+        cx.notify();
+    });
+    match with_audio_bridge(runtime, |audio| {
+        audio.send(AudioCommand::SetEqualizer(equalizer))
+    }) {
+        Ok(Ok(_)) => {}
+        Ok(Err(err)) => {
+            auth::push_shell_error(runtime, format!("Failed to apply equalizer: {err}"), cx)
+        }
+        Err(err) => {
+            auth::push_shell_error(runtime, format!("Failed to apply equalizer: {err}"), cx)
+        }
+    }
+    persist_player_settings(runtime, cx);
+}
+
+pub fn start_sleep_timer<T>(runtime: &AppRuntime, after_ms: u64, cx: &mut Context<T>) {
+    runtime.player.update(cx, |player, cx| {
+        player.sleep_after_tracks = None;
+        cx.notify();
+    });
+    match with_audio_bridge(runtime, |audio| {
+        audio.send(AudioCommand::StartSleepTimer {
+            after_ms,
+            fade_ms: SLEEP_TIMER_FADE_MS.min(after_ms),
+        })
+    }) {
+        Ok(Ok(_)) => {}
+        Ok(Err(err)) => {
+            auth::push_shell_error(runtime, format!("Failed to set sleep timer: {err}"), cx)
+        }
+        Err(err) => {
+            auth::push_shell_error(runtime, format!("Failed to set sleep timer: {err}"), cx)
+        }
+    }
+}
+
+pub fn stop_after_tracks<T: 'static>(runtime: &AppRuntime, tracks: u32, cx: &mut Context<T>) {
+    cancel_sleep_timer(runtime, cx);
+    runtime.player.update(cx, |player, cx| {
+        player.sleep_after_tracks = (tracks > 0).then_some(tracks);
+        cx.notify();
+    });
+    prefetch_next_track(runtime, cx);
+}
+
+pub fn cancel_sleep_timer<T>(runtime: &AppRuntime, cx: &mut Context<T>) {
+    runtime.player.update(cx, |player, cx| {
+        player.sleep_after_tracks = None;
+        cx.notify();
+    });
+    match with_audio_bridge(runtime, |audio| audio.send(AudioCommand::CancelSleepTimer)) {
+        Ok(Ok(_)) => {}
+        Ok(Err(err)) => {
+            auth::push_shell_error(runtime, format!("Failed to cancel sleep timer: {err}"), cx)
+        }
+        Err(err) => {
+            auth::push_shell_error(runtime, format!("Failed to cancel sleep timer: {err}"), cx)
+        }
+    }
+}
+
 pub fn preview_seek_ratio<T>(runtime: &AppRuntime, ratio: f32, cx: &mut Context<T>) {
     let ratio = ratio.clamp(0.0, 1.0);
     runtime.player.update(cx, |player, cx| {
@@ -40,7 +108,7 @@ pub fn commit_seek_ratio<T>(runtime: &AppRuntime, ratio: f32, cx: &mut Context<T
     let duration_ms = runtime.player.read(cx).duration_ms.max(1);
     let target_ms = ((duration_ms as f32) * ratio) as u64;
     match with_audio_bridge(runtime, |audio| {
-        audio.send(AudioCommand::Seek(SeekTarget::ms(target_ms)))
+        audio.send(AudioCommand::Seek(SeekTarget::Ratio(ratio)))
     }) {
         Ok(Ok(_)) => {}
         Ok(Err(err)) => {
@@ -163,8 +231,9 @@ pub(super) fn play_current<T>(runtime: &AppRuntime, cx: &mut Context<T>) {
     start_playback_at(runtime, current_index, player.position_ms, true, cx);
 }
 
-pub fn sync_audio_bridge<T>(runtime: &AppRuntime, cx: &mut Context<T>) {
+pub fn sync_audio_bridge<T: 'static>(runtime: &AppRuntime, cx: &mut Context<T>) {
     let mut ended = false;
+    let mut continued: Option<SourceSpec> = None;
     let mut forbidden = false;
     let mut last_error: Option<String> = None;
 
@@ -172,7 +241,15 @@ pub fn sync_audio_bridge<T>(runtime: &AppRuntime, cx: &mut Context<T>) {
         runtime.player.update(cx, |player, _| {
             for event in bridge.drain(player) {
                 match event {
-                    ame_audio::AudioEvent::TrackEnded => ended = true,
+                    ame_audio::AudioEvent::TrackEnded { next: None } => ended = true,
+                    ame_audio::AudioEvent::TrackEnded { next: Some(source) } => {
+                        continued = Some(source)
+                    }
+                    ame_audio::AudioEvent::BufferHealth {
+                        downloaded_bytes,
+                        total_bytes,
+                        ..
+                    } => player.apply_buffer_health(downloaded_bytes, total_bytes),
                     ame_audio::AudioEvent::Error(err) => {
                         if matches!(err, AudioError::HttpStatus { code: 403, .. }) {
                             forbidden = true;
@@ -198,17 +275,62 @@ pub fn sync_audio_bridge<T>(runtime: &AppRuntime, cx: &mut Context<T>) {
         auth::set_shell_error(runtime, Some(err), cx);
     }
 
+    if let Some(source) = continued {
+        let mut sleep = false;
+        runtime.player.update(cx, |player, cx| {
+            sleep = player.finish_track_for_sleep();
+            let prefetched = player
+                .prefetched
+                .take()
+                .filter(|prefetched| prefetched.source.as_ref() == Some(&source))
+                .map(|prefetched| prefetched.index);
+            if let Some(index) =
+                prefetched.or_else(|| source.url().and_then(|url| player.index_of_source_url(url)))
+            {
+                player.advance_to(index);
+            }
+            player.position_ms = 0;
+            player.duration_ms = 0;
+            player.downloaded_ratio = None;
+            if sleep {
+                player.is_playing = false;
+            }
+            cx.notify();
+        });
+        // Only reached when the next track was already queued before the track
+        // sleep timer was set.
+        if sleep {
+            match with_audio_bridge(runtime, |audio| audio.send(AudioCommand::Pause)) {
+                Ok(Ok(_)) => {}
+                Ok(Err(err)) => {
+                    auth::push_shell_error(runtime, format!("Failed to pause playback: {err}"), cx)
+                }
+                Err(err) => {
+                    auth::push_shell_error(runtime, format!("Failed to pause playback: {err}"), cx)
+                }
+            }
+        }
+        persist_player_runtime(runtime, cx);
+    }
+
     if ended {
         let mut target = None;
+        let mut sleep = false;
         runtime.player.update(cx, |player, _| {
+            sleep = player.finish_track_for_sleep();
             target = player.next_index();
             player.position_ms = 0;
             player.duration_ms = 0;
         });
+        // The track sleep timer ran out: line up the next track but leave it
+        // paused.
         if let Some(index) = target {
-            start_playback_at(runtime, index, 0, true, cx);
+            start_playback_at(runtime, index, 0, !sleep, cx);
         }
+        return;
     }
+
+    prefetch_next_track(runtime, cx);
 }
 
 pub fn prepare_app_exit<T>(runtime: &AppRuntime, cx: &mut Context<T>) {
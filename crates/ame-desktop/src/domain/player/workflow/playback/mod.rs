@@ -2,9 +2,10 @@ mod controls;
 mod source;
 
 pub use controls::{
-    commit_seek_ratio, cycle_play_mode, play_next, play_previous, prepare_app_exit,
-    preview_seek_ratio, set_volume_absolute, sync_audio_bridge, toggle_playback,
+    cancel_sleep_timer, commit_seek_ratio, cycle_play_mode, play_next, play_previous,
+    prepare_app_exit, preview_seek_ratio, set_equalizer, set_volume_absolute, start_sleep_timer,
+    stop_after_tracks, sync_audio_bridge, toggle_playback,
 };
 pub(in crate::domain::player::workflow) use source::{
-    refresh_current_track_url_and_resume, start_playback_at,
+    prefetch_next_track, refresh_current_track_url_and_resume, start_playback_at,
 };
@@ -1,23 +1,30 @@
-use ame_audio::{AudioCommand, SourceSpec};
-use nekowg::Context;
+use ame_audio::{AudioCommand, SourceSpec, StreamCacheKey};
+use nekowg::{AppContext, Context};
+use tracing::warn;
 
 use crate::app::runtime::AppRuntime;
 use crate::domain::player;
+use crate::domain::player::{PlayerEntity, PrefetchedTrack, TrackStream};
 use crate::domain::session as auth;
 use crate::domain::session::AuthLevel;
 
-use super::super::bridge::with_audio_bridge_or_error;
+use super::super::bridge::{with_audio_bridge, with_audio_bridge_or_error};
 use super::super::persist::persist_player_runtime;
 
+// How long before the end of the current track the next one is resolved and
+// handed to the audio runtime. Leaves room for the longest crossfade.
+const NEXT_TRACK_PREFETCH_LEAD_MS: u64 = 20_000;
+
 fn prepare_track_source<T>(
     runtime: &AppRuntime,
     track_id: i64,
     queue_index: usize,
     cx: &mut Context<T>,
-) -> Option<String> {
+) -> Option<SourceSpec> {
     let cookie = auth::ensure_auth_cookie(runtime, AuthLevel::Guest, cx)?;
-    let source_url = match player::fetch_track_url_blocking(track_id, Some(cookie.as_str())) {
-        Ok(url) => url,
+    let quality = runtime.app.read(cx).audio_quality;
+    let stream = match player::fetch_track_url_blocking(track_id, quality, Some(cookie.as_str())) {
+        Ok(stream) => stream,
         Err(err) => {
             auth::set_shell_error(
                 runtime,
@@ -28,12 +35,23 @@ fn prepare_track_source<T>(
         }
     };
 
-    runtime.player.update(cx, |player, _| {
-        if let Some(item) = player.queue.get_mut(queue_index) {
-            item.source_url = Some(source_url.clone());
-        }
-    });
-    Some(source_url)
+    Some(runtime.player.update(cx, |player, _| {
+        apply_track_stream(player, queue_index, track_id, stream)
+    }))
+}
+
+fn apply_track_stream(
+    player: &mut PlayerEntity,
+    queue_index: usize,
+    track_id: i64,
+    stream: TrackStream,
+) -> SourceSpec {
+    if let Some(item) = player.queue.get_mut(queue_index) {
+        item.source_url = Some(stream.url.clone());
+        item.quality = Some(stream.quality);
+        item.trial = stream.trial;
+    }
+    SourceSpec::cached_stream(stream.url, StreamCacheKey::new(track_id, stream.bitrate))
 }
 
 pub(in crate::domain::player::workflow) fn start_playback_at<T>(
@@ -48,14 +66,30 @@ pub(in crate::domain::player::workflow) fn start_playback_at<T>(
         return false;
     };
 
-    let Some(source_url) = prepare_track_source(runtime, item.id, queue_index, cx) else {
-        persist_player_runtime(runtime, cx);
-        return false;
+    let prefetched = snapshot
+        .prefetched
+        .as_ref()
+        .filter(|prefetched| prefetched.index == queue_index && prefetched.track_id == item.id)
+        .and_then(|prefetched| prefetched.source.clone());
+    let source = match prefetched {
+        Some(source) => source,
+        None => match prepare_track_source(runtime, item.id, queue_index, cx) {
+            Some(source) => source,
+            None => {
+                persist_player_runtime(runtime, cx);
+                return false;
+            }
+        },
     };
 
     let opened = with_audio_bridge_or_error(runtime, cx, "Playback failed", |audio| {
+        // The runtime carries the queued next source over to newly opened
+        // tracks, so drop it before it could follow the wrong one.
+        if snapshot.prefetched.is_some() {
+            audio.send(AudioCommand::SetNext(None))?;
+        }
         audio.send(AudioCommand::Open {
-            source: SourceSpec::network(source_url),
+            source,
             start_ms,
             autoplay,
         })
@@ -74,12 +108,125 @@ pub(in crate::domain::player::workflow) fn start_playback_at<T>(
     runtime.player.update(cx, |player, cx| {
         player.current_index = Some(queue_index);
         player.is_playing = autoplay;
+        player.prefetched = None;
         cx.notify();
     });
     persist_player_runtime(runtime, cx);
     true
 }
 
+// Runs from the UI tick, so the URL is resolved on the background executor and
+// only handed to the audio runtime once it arrives. Failures are only logged;
+// the track is resolved again when playback actually reaches it.
+pub(in crate::domain::player::workflow) fn prefetch_next_track<T: 'static>(
+    runtime: &AppRuntime,
+    cx: &mut Context<T>,
+) {
+    let (target, prefetched, due) = {
+        let player = runtime.player.read(cx);
+        let target = player
+            .peek_next_index()
+            .filter(|_| !player.stops_after_current())
+            .and_then(|index| Some((index, player.queue.get(index)?.id)));
+        let remaining_ms = player.duration_ms.saturating_sub(player.position_ms);
+        let due = player.is_playing
+            && player.duration_ms > 0
+            && remaining_ms <= NEXT_TRACK_PREFETCH_LEAD_MS;
+        (target, player.prefetched.clone(), due)
+    };
+    let was_sent = prefetched
+        .as_ref()
+        .is_some_and(|prefetched| prefetched.source.is_some());
+
+    let Some((index, track_id)) = target else {
+        if was_sent {
+            send_next_source(runtime, None, cx);
+        }
+        if prefetched.is_some() {
+            runtime
+                .player
+                .update(cx, |player, _| player.prefetched = None);
+        }
+        return;
+    };
+    if !due
+        || prefetched
+            .is_some_and(|prefetched| prefetched.index == index && prefetched.track_id == track_id)
+    {
+        return;
+    }
+
+    // The previous choice must not stay queued while the new one resolves.
+    if was_sent {
+        send_next_source(runtime, None, cx);
+    }
+    // An entry without a source marks the lookup as in flight, or as failed,
+    // so the tick does not start it again.
+    let pending = PrefetchedTrack {
+        index,
+        track_id,
+        source: None,
+    };
+    runtime.player.update(cx, |player, _| {
+        player.prefetched = Some(pending.clone());
+    });
+    let Some(cookie) = auth::build_cookie_header(&auth::auth_bundle(runtime, cx)) else {
+        return;
+    };
+    let quality = runtime.app.read(cx).audio_quality;
+
+    let runtime = runtime.clone();
+    cx.spawn(async move |_, cx| {
+        let result = cx
+            .background_executor()
+            .spawn(async move {
+                player::fetch_track_url_blocking(track_id, quality, Some(cookie.as_str()))
+            })
+            .await;
+        let stream = match result {
+            Ok(stream) => stream,
+            Err(err) => {
+                warn!(error = %err, track_id, "failed to resolve next track URL");
+                return;
+            }
+        };
+
+        // The queue or the playing track may have moved on in the meantime.
+        let source = runtime.player.update(cx, |player, _| {
+            let still_next = player.prefetched.as_ref() == Some(&pending)
+                && player
+                    .queue
+                    .get(index)
+                    .is_some_and(|item| item.id == track_id);
+            if !still_next {
+                return None;
+            }
+            let source = apply_track_stream(player, index, track_id, stream);
+            player.prefetched = Some(PrefetchedTrack {
+                source: Some(source.clone()),
+                ..pending
+            });
+            Some(source)
+        });
+        if let Some(source) = source {
+            send_next_source(&runtime, Some(source), cx);
+        }
+    })
+    .detach();
+}
+
+fn send_next_source<C: AppContext>(runtime: &AppRuntime, source: Option<SourceSpec>, cx: &mut C) {
+    match with_audio_bridge(runtime, |audio| audio.send(AudioCommand::SetNext(source))) {
+        Ok(Ok(_)) => {}
+        Ok(Err(err)) => {
+            auth::push_shell_error(runtime, format!("Failed to queue next track: {err}"), cx)
+        }
+        Err(err) => {
+            auth::push_shell_error(runtime, format!("Failed to queue next track: {err}"), cx)
+        }
+    }
+}
+
 pub(in crate::domain::player::workflow) fn refresh_current_track_url_and_resume<T>(
     runtime: &AppRuntime,
     cx: &mut Context<T>,
@@ -92,7 +239,7 @@ pub(in crate::domain::player::workflow) fn refresh_current_track_url_and_resume<
         return false;
     };
 
-    let Some(url) = prepare_track_source(runtime, current_item.id, current_index, cx) else {
+    let Some(source) = prepare_track_source(runtime, current_item.id, current_index, cx) else {
         auth::set_shell_error(
             runtime,
             Some("Failed to refresh playback URL".to_string()),
@@ -104,7 +251,7 @@ pub(in crate::domain::player::workflow) fn refresh_current_track_url_and_resume<
     let reopened = with_audio_bridge_or_error(runtime, cx, "Failed to refresh playback", |audio| {
         let position = audio.service().snapshot().position_ms;
         audio.send(AudioCommand::Open {
-            source: SourceSpec::network(url),
+            source,
             start_ms: position,
             autoplay: true,
         })
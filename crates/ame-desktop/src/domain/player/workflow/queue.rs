@@ -68,6 +68,8 @@ pub fn enqueue_track<T>(
             duration_ms,
             cover_url,
             source_url: None,
+            quality: None,
+            trial: false,
         });
         inserted_index = player.queue.len().checked_sub(1);
         if autoplay {
@@ -114,6 +116,8 @@ pub fn replace_queue<T>(
                     duration_ms: track.duration_ms,
                     cover_url: track.cover_url.clone(),
                     source_url: None,
+                    quality: None,
+                    trial: false,
                 })
                 .collect(),
         );
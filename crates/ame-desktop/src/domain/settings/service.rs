@@ -1,8 +1,8 @@
 use nekowg::AppContext;
 
-use crate::app::runtime::{AppRuntime, KEY_HOME_ARTIST_LANGUAGE};
+use crate::app::runtime::{AppRuntime, KEY_HOME_ARTIST_LANGUAGE, KEY_PLAYER_AUDIO_QUALITY};
 use crate::domain::session::push_shell_error;
-use crate::domain::settings::HomeArtistLanguage;
+use crate::domain::settings::{AudioQuality, HomeArtistLanguage};
 
 pub fn set_home_artist_language<C: AppContext>(
     runtime: &AppRuntime,
@@ -32,3 +32,26 @@ pub fn set_home_artist_language<C: AppContext>(
         );
     }
 }
+
+// Applies from the next track whose URL is resolved; the one playing keeps the
+// stream it already has.
+pub fn set_audio_quality<C: AppContext>(runtime: &AppRuntime, value: AudioQuality, cx: &mut C) {
+    let changed = runtime.app.update(cx, |app, cx| {
+        if app.audio_quality == value {
+            return false;
+        }
+        app.set_audio_quality(value);
+        cx.notify();
+        true
+    });
+
+    if !changed {
+        return;
+    }
+
+    if let Some(settings) = runtime.services.settings_store.as_ref()
+        && let Err(err) = settings.set(KEY_PLAYER_AUDIO_QUALITY, &value)
+    {
+        push_shell_error(runtime, format!("Failed to save audio quality: {err}"), cx);
+    }
+}
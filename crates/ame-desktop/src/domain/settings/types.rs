@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 
+pub use ame_audio::EqualizerPreset;
+pub use ame_netease::api::track::url::AudioQuality;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum CloseBehavior {
     #[default]
@@ -51,6 +54,20 @@ impl HomeArtistLanguage {
     }
 }
 
+pub const fn equalizer_preset_label(preset: EqualizerPreset) -> &'static str {
+    match preset {
+        EqualizerPreset::Flat => "平直",
+        EqualizerPreset::BassBoost => "低音增强",
+        EqualizerPreset::TrebleBoost => "高音增强",
+        EqualizerPreset::Vocal => "人声",
+        EqualizerPreset::Rock => "摇滚",
+        EqualizerPreset::Pop => "流行",
+        EqualizerPreset::Jazz => "爵士",
+        EqualizerPreset::Classical => "古典",
+        EqualizerPreset::Electronic => "电子",
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::HomeArtistLanguage;
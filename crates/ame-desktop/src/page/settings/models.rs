@@ -1,7 +1,10 @@
+use ame_audio::EqualizerSettings;
 use nekowg::SharedString;
 
 #[derive(Debug, Clone)]
 pub struct SettingsViewModel {
     pub close_behavior_label: SharedString,
     pub home_artist_language_label: SharedString,
+    pub audio_quality_label: SharedString,
+    pub equalizer: EqualizerSettings,
 }
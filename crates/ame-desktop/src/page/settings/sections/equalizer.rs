@@ -0,0 +1,117 @@
+use std::rc::Rc;
+
+use ame_audio::{EQ_BAND_COUNT, EqualizerSettings};
+use nekowg::{AnyElement, App, div, prelude::*};
+
+use crate::domain::settings::{EqualizerPreset, equalizer_preset_label};
+
+use super::EqualizerHandler;
+use super::option_row::{OptionAction, setting_option_row};
+
+const GAIN_STEP_DB: f32 = 1.0;
+
+pub(super) fn equalizer_rows(
+    settings: &EqualizerSettings,
+    on_change: EqualizerHandler,
+) -> AnyElement {
+    let summary = if settings.enabled {
+        settings.preset.map_or("自定义", equalizer_preset_label)
+    } else {
+        "关闭"
+    };
+    let mut presets = vec![(
+        "关闭",
+        change(settings, &on_change, |settings| settings.enabled = false),
+    )];
+    presets.extend(EqualizerPreset::variants().into_iter().map(|preset| {
+        let on_change = on_change.clone();
+        (
+            equalizer_preset_label(preset),
+            Rc::new(move |cx: &mut App| {
+                on_change(
+                    EqualizerSettings {
+                        enabled: true,
+                        ..EqualizerSettings::from_preset(preset)
+                    },
+                    cx,
+                )
+            }) as OptionAction,
+        )
+    }));
+
+    let rows = div()
+        .w_full()
+        .flex()
+        .flex_col()
+        .gap_2()
+        .child(setting_option_row(format!("均衡器: {summary}"), presets))
+        .child(setting_option_row(
+            format!("前级增益: {}", format_gain(settings.preamp_db)),
+            gain_steps(settings, &on_change, |settings, step| {
+                settings.preamp_db += step;
+            }),
+        ));
+
+    (0..EQ_BAND_COUNT)
+        .fold(rows, |rows, index| {
+            let band = settings.bands[index];
+            rows.child(setting_option_row(
+                format!(
+                    "{}: {}",
+                    format_frequency(band.frequency_hz),
+                    format_gain(band.gain_db)
+                ),
+                gain_steps(settings, &on_change, move |settings, step| {
+                    settings.set_band_gain(index, settings.bands[index].gain_db + step);
+                }),
+            ))
+        })
+        .into_any_element()
+}
+
+fn change(
+    settings: &EqualizerSettings,
+    on_change: &EqualizerHandler,
+    apply: impl Fn(&mut EqualizerSettings) + 'static,
+) -> OptionAction {
+    let settings = settings.clone();
+    let on_change = on_change.clone();
+    Rc::new(move |cx: &mut App| {
+        let mut next = settings.clone();
+        apply(&mut next);
+        on_change(next.clamped(), cx);
+    })
+}
+
+// Nudging a gain also switches the equalizer on, so the change is audible.
+fn gain_steps(
+    settings: &EqualizerSettings,
+    on_change: &EqualizerHandler,
+    apply: impl Fn(&mut EqualizerSettings, f32) + Clone + 'static,
+) -> Vec<(&'static str, OptionAction)> {
+    [("-", -GAIN_STEP_DB), ("+", GAIN_STEP_DB)]
+        .into_iter()
+        .map(|(label, step)| {
+            let apply = apply.clone();
+            (
+                label,
+                change(settings, on_change, move |settings| {
+                    settings.enabled = true;
+                    apply(settings, step);
+                }),
+            )
+        })
+        .collect()
+}
+
+fn format_gain(gain_db: f32) -> String {
+    format!("{gain_db:+.1} dB")
+}
+
+fn format_frequency(frequency_hz: f32) -> String {
+    if frequency_hz >= 1_000.0 {
+        format!("{} kHz", frequency_hz / 1_000.0)
+    } else {
+        format!("{frequency_hz} Hz")
+    }
+}
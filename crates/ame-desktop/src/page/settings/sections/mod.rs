@@ -1,22 +1,29 @@
+mod equalizer;
 mod option_row;
 
 use std::rc::Rc;
 
+use ame_audio::EqualizerSettings;
 use nekowg::{AnyElement, App, FontWeight, div, prelude::*, px, rgb};
 
 use crate::component::theme;
-use crate::domain::settings::{CloseBehavior, HomeArtistLanguage};
+use crate::domain::settings::{AudioQuality, CloseBehavior, HomeArtistLanguage};
 use crate::page::settings::models::SettingsViewModel;
 
+use self::equalizer::equalizer_rows;
 use self::option_row::setting_option_row;
 
 pub(crate) type CloseBehaviorHandler = Rc<dyn Fn(CloseBehavior, &mut App)>;
 pub(crate) type HomeArtistLanguageHandler = Rc<dyn Fn(HomeArtistLanguage, &mut App)>;
+pub(crate) type AudioQualityHandler = Rc<dyn Fn(AudioQuality, &mut App)>;
+pub(crate) type EqualizerHandler = Rc<dyn Fn(EqualizerSettings, &mut App)>;
 
 pub(crate) fn render_settings_page(
     model: SettingsViewModel,
     on_set_close_behavior: CloseBehaviorHandler,
     on_set_home_artist_language: HomeArtistLanguageHandler,
+    on_set_audio_quality: AudioQualityHandler,
+    on_set_equalizer: EqualizerHandler,
 ) -> AnyElement {
     div()
         .w_full()
@@ -65,5 +72,21 @@ pub(crate) fn render_settings_page(
                 })
                 .collect(),
         ))
+        .child(setting_option_row(
+            format!("音质: {}", model.audio_quality_label),
+            AudioQuality::variants()
+                .into_iter()
+                .map(|quality| {
+                    let label = quality.label();
+                    let on_set_audio_quality = on_set_audio_quality.clone();
+                    (
+                        label,
+                        Rc::new(move |cx: &mut App| on_set_audio_quality(quality, cx))
+                            as Rc<dyn Fn(&mut App)>,
+                    )
+                })
+                .collect(),
+        ))
+        .child(equalizer_rows(&model.equalizer, on_set_equalizer))
         .into_any_element()
 }
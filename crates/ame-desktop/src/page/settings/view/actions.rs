@@ -1,7 +1,8 @@
+use ame_audio::EqualizerSettings;
 use nekowg::Context;
 
-use crate::domain::settings::{CloseBehavior, HomeArtistLanguage};
-use crate::domain::{settings, shell};
+use crate::domain::settings::{AudioQuality, CloseBehavior, HomeArtistLanguage};
+use crate::domain::{player, settings, shell};
 
 use super::SettingsPageView;
 
@@ -17,4 +18,12 @@ impl SettingsPageView {
     ) {
         settings::set_home_artist_language(&self.runtime, value, cx);
     }
+
+    pub(super) fn set_audio_quality(&mut self, value: AudioQuality, cx: &mut Context<Self>) {
+        settings::set_audio_quality(&self.runtime, value, cx);
+    }
+
+    pub(super) fn set_equalizer(&mut self, value: EqualizerSettings, cx: &mut Context<Self>) {
+        player::set_equalizer(&self.runtime, value, cx);
+    }
 }
@@ -8,7 +8,8 @@ use crate::app::page::{PageLifecycle, PageRetentionPolicy};
 use crate::app::runtime::AppRuntime;
 use crate::page::settings::models::SettingsViewModel;
 use crate::page::settings::sections::{
-    CloseBehaviorHandler, HomeArtistLanguageHandler, render_settings_page,
+    AudioQualityHandler, CloseBehaviorHandler, EqualizerHandler, HomeArtistLanguageHandler,
+    render_settings_page,
 };
 
 pub struct SettingsPageView {
@@ -17,10 +18,13 @@ pub struct SettingsPageView {
 }
 
 impl SettingsPageView {
-    pub fn new(runtime: AppRuntime, _cx: &mut Context<Self>) -> Self {
+    pub fn new(runtime: AppRuntime, cx: &mut Context<Self>) -> Self {
+        let subscriptions = vec![cx.observe(&runtime.player, |_, _, cx| {
+            cx.notify();
+        })];
         Self {
             runtime,
-            _subscriptions: Vec::new(),
+            _subscriptions: subscriptions,
         }
     }
 }
@@ -36,6 +40,8 @@ impl Render for SettingsPageView {
                 .home_artist_language
                 .label()
                 .into(),
+            audio_quality_label: self.runtime.app.read(cx).audio_quality.label().into(),
+            equalizer: self.runtime.player.read(cx).equalizer.clone(),
         };
         let page = cx.entity();
         let on_set_close_behavior: CloseBehaviorHandler = Rc::new(move |value, cx| {
@@ -46,7 +52,23 @@ impl Render for SettingsPageView {
             page.update(cx, |this, cx| this.set_home_artist_language(value, cx));
         });
 
-        render_settings_page(model, on_set_close_behavior, on_set_home_artist_language)
+        let page = cx.entity();
+        let on_set_audio_quality: AudioQualityHandler = Rc::new(move |value, cx| {
+            page.update(cx, |this, cx| this.set_audio_quality(value, cx));
+        });
+
+        let page = cx.entity();
+        let on_set_equalizer: EqualizerHandler = Rc::new(move |value, cx| {
+            page.update(cx, |this, cx| this.set_equalizer(value, cx));
+        });
+
+        render_settings_page(
+            model,
+            on_set_close_behavior,
+            on_set_home_artist_language,
+            on_set_audio_quality,
+            on_set_equalizer,
+        )
     }
 }
 
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::api::common::models::{AlbumDto, TrackDto};
+use crate::api::request::ApiRequest;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct AlbumDetailResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub album: AlbumDto,
+    #[serde(default)]
+    pub songs: Vec<TrackDto>,
+}
+
+pub struct AlbumDetailRequest {
+    id: i64,
+}
+
+impl AlbumDetailRequest {
+    pub fn new(id: i64) -> Self {
+        Self { id }
+    }
+}
+
+impl ApiRequest for AlbumDetailRequest {
+    type Response = AlbumDetailResponse;
+
+    fn endpoint(&self) -> &'static str {
+        "/api/v1/album"
+    }
+
+    fn payload(&self) -> Value {
+        json!({})
+    }
+
+    fn path_param(&self) -> Option<String> {
+        Some(self.id.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{AlbumDetailRequest, AlbumDetailResponse};
+    use crate::api::request::{ApiRequest, request_path};
+
+    #[test]
+    fn album_detail_puts_id_in_path() {
+        let req = AlbumDetailRequest::new(32311);
+        assert_eq!(request_path(&req), "/api/v1/album/32311");
+        assert_eq!(req.payload(), json!({}));
+    }
+
+    #[test]
+    fn album_detail_response_reads_tracks_and_privileges() {
+        let response: AlbumDetailResponse = serde_json::from_value(json!({
+            "code": 200,
+            "album": {
+                "id": 32311,
+                "name": "Album",
+                "description": "desc",
+                "publishTime": 1_104_508_800_000_u64,
+                "company": "Label",
+                "size": 1
+            },
+            "songs": [{
+                "id": 326904,
+                "name": "Song",
+                "ar": [{ "id": 6452, "name": "Artist" }],
+                "al": { "id": 32311, "name": "Album" },
+                "dt": 240000,
+                "fee": 8,
+                "privilege": { "id": 326904, "fee": 8, "st": 0, "pl": 320000, "maxbr": 999000 }
+            }]
+        }))
+        .unwrap();
+        assert_eq!(response.album.company.as_deref(), Some("Label"));
+        assert_eq!(response.album.publish_time, Some(1_104_508_800_000));
+        let song = &response.songs[0];
+        assert_eq!(song.album.id, 32311);
+        assert_eq!(song.duration_ms, Some(240000));
+        let privilege = song.privilege.as_ref().unwrap();
+        assert_eq!(privilege.maxbr, Some(999000));
+        assert!(privilege.is_playable());
+    }
+
+    #[tokio::test]
+    async fn live_album_detail_request() {
+        let client = crate::NeteaseClient::new();
+        let response = client
+            .weapi_request(AlbumDetailRequest::new(32311))
+            .await
+            .expect("album detail request failed");
+
+        assert_eq!(response.code, 200);
+        assert_eq!(response.album.id, 32311);
+        assert!(!response.songs.is_empty());
+    }
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::api::request::ApiRequest;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct AlbumDynamicResponse {
+    pub code: i64,
+    #[serde(default, rename = "isSub")]
+    pub is_sub: bool,
+    #[serde(default, rename = "subTime")]
+    pub sub_time: Option<u64>,
+    #[serde(default, rename = "subCount")]
+    pub sub_count: Option<u64>,
+    #[serde(default, rename = "commentCount")]
+    pub comment_count: Option<u64>,
+    #[serde(default, rename = "shareCount")]
+    pub share_count: Option<u64>,
+    #[serde(default, rename = "likedCount")]
+    pub liked_count: Option<u64>,
+    #[serde(default, rename = "onSale")]
+    pub on_sale: Option<bool>,
+}
+
+pub struct AlbumDynamicRequest {
+    id: i64,
+}
+
+impl AlbumDynamicRequest {
+    pub fn new(id: i64) -> Self {
+        Self { id }
+    }
+}
+
+impl ApiRequest for AlbumDynamicRequest {
+    type Response = AlbumDynamicResponse;
+
+    fn endpoint(&self) -> &'static str {
+        "/api/album/detail/dynamic"
+    }
+
+    fn payload(&self) -> Value {
+        json!({ "id": self.id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{AlbumDynamicRequest, AlbumDynamicResponse};
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn album_dynamic_payload_contains_id() {
+        let req = AlbumDynamicRequest::new(32311);
+        assert_eq!(req.endpoint(), "/api/album/detail/dynamic");
+        assert_eq!(req.payload(), json!({ "id": 32311 }));
+    }
+
+    #[test]
+    fn album_dynamic_response_reads_counts() {
+        let response: AlbumDynamicResponse = serde_json::from_value(json!({
+            "code": 200,
+            "isSub": true,
+            "commentCount": 12,
+            "shareCount": 3,
+            "subCount": 40
+        }))
+        .unwrap();
+        assert!(response.is_sub);
+        assert_eq!(response.comment_count, Some(12));
+        assert_eq!(response.share_count, Some(3));
+        assert_eq!(response.liked_count, None);
+    }
+}
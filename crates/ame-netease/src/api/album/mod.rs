@@ -1 +1,5 @@
+pub mod detail;
+pub mod dynamic;
 pub mod new;
+pub mod sublist;
+pub mod subscribe;
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::api::common::models::AlbumDto;
+use crate::api::request::ApiRequest;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct AlbumSublistResponse {
+    pub code: i64,
+    #[serde(default, rename = "data")]
+    pub albums: Vec<AlbumDto>,
+    #[serde(default)]
+    pub count: Option<u64>,
+    #[serde(default, rename = "hasMore")]
+    pub has_more: bool,
+}
+
+// Albums the signed-in user has subscribed to.
+pub struct AlbumSublistRequest {
+    limit: u32,
+    offset: u32,
+}
+
+impl AlbumSublistRequest {
+    pub fn new(limit: u32, offset: u32) -> Self {
+        Self { limit, offset }
+    }
+}
+
+impl Default for AlbumSublistRequest {
+    fn default() -> Self {
+        Self::new(25, 0)
+    }
+}
+
+impl ApiRequest for AlbumSublistRequest {
+    type Response = AlbumSublistResponse;
+
+    fn endpoint(&self) -> &'static str {
+        "/api/album/sublist"
+    }
+
+    fn payload(&self) -> Value {
+        json!({
+            "limit": self.limit,
+            "offset": self.offset,
+            "total": true,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{AlbumSublistRequest, AlbumSublistResponse};
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn album_sublist_payload_defaults() {
+        let req = AlbumSublistRequest::default();
+        assert_eq!(req.endpoint(), "/api/album/sublist");
+        assert_eq!(
+            req.payload(),
+            json!({
+                "limit": 25,
+                "offset": 0,
+                "total": true,
+            })
+        );
+    }
+
+    #[test]
+    fn album_sublist_response_reads_data_page() {
+        let response: AlbumSublistResponse = serde_json::from_value(json!({
+            "code": 200,
+            "count": 1,
+            "hasMore": false,
+            "data": [{ "id": 32311, "name": "Album", "size": 10, "picUrl": "https://p1.music.126.net/a.jpg" }]
+        }))
+        .unwrap();
+        assert_eq!(response.albums.len(), 1);
+        assert_eq!(response.albums[0].size, Some(10));
+        assert_eq!(response.count, Some(1));
+    }
+}
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::api::request::ApiRequest;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct AlbumSubscribeResponse {
+    pub code: i64,
+}
+
+pub struct AlbumSubscribeRequest {
+    id: i64,
+    subscribe: bool,
+}
+
+impl AlbumSubscribeRequest {
+    pub fn new(id: i64, subscribe: bool) -> Self {
+        Self { id, subscribe }
+    }
+}
+
+impl ApiRequest for AlbumSubscribeRequest {
+    type Response = AlbumSubscribeResponse;
+
+    fn endpoint(&self) -> &'static str {
+        if self.subscribe {
+            "/api/album/sub"
+        } else {
+            "/api/album/unsub"
+        }
+    }
+
+    fn payload(&self) -> Value {
+        json!({ "id": self.id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::AlbumSubscribeRequest;
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn subscribe_and_unsubscribe_pick_endpoint() {
+        let sub = AlbumSubscribeRequest::new(32311, true);
+        assert_eq!(sub.endpoint(), "/api/album/sub");
+        assert_eq!(sub.payload(), json!({ "id": 32311 }));
+        assert_eq!(
+            AlbumSubscribeRequest::new(32311, false).endpoint(),
+            "/api/album/unsub"
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::api::common::models::{AlbumDto, ArtistDto};
+use crate::api::request::ApiRequest;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ArtistAlbumsResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub artist: Option<ArtistDto>,
+    #[serde(default, rename = "hotAlbums")]
+    pub albums: Vec<AlbumDto>,
+    #[serde(default)]
+    pub more: bool,
+}
+
+pub struct ArtistAlbumsRequest {
+    id: i64,
+    limit: u32,
+    offset: u32,
+}
+
+impl ArtistAlbumsRequest {
+    pub fn new(id: i64, limit: u32, offset: u32) -> Self {
+        Self { id, limit, offset }
+    }
+}
+
+impl ApiRequest for ArtistAlbumsRequest {
+    type Response = ArtistAlbumsResponse;
+
+    fn endpoint(&self) -> &'static str {
+        "/api/artist/albums"
+    }
+
+    fn payload(&self) -> Value {
+        json!({
+            "limit": self.limit,
+            "offset": self.offset,
+            "total": true,
+        })
+    }
+
+    fn path_param(&self) -> Option<String> {
+        Some(self.id.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::ArtistAlbumsRequest;
+    use crate::api::request::{ApiRequest, request_path};
+
+    #[test]
+    fn artist_albums_puts_id_in_path() {
+        let req = ArtistAlbumsRequest::new(6452, 30, 60);
+        assert_eq!(request_path(&req), "/api/artist/albums/6452");
+        assert_eq!(
+            req.payload(),
+            json!({
+                "limit": 30,
+                "offset": 60,
+                "total": true,
+            })
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::api::request::ApiRequest;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ArtistIntroductionDto {
+    #[serde(default, rename = "ti")]
+    pub title: Option<String>,
+    #[serde(default, rename = "txt")]
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ArtistDescResponse {
+    pub code: i64,
+    #[serde(default, rename = "briefDesc")]
+    pub brief_desc: Option<String>,
+    #[serde(default)]
+    pub introduction: Vec<ArtistIntroductionDto>,
+}
+
+pub struct ArtistDescRequest {
+    id: i64,
+}
+
+impl ArtistDescRequest {
+    pub fn new(id: i64) -> Self {
+        Self { id }
+    }
+}
+
+impl ApiRequest for ArtistDescRequest {
+    type Response = ArtistDescResponse;
+
+    fn endpoint(&self) -> &'static str {
+        "/api/artist/introduction"
+    }
+
+    fn payload(&self) -> Value {
+        json!({ "id": self.id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{ArtistDescRequest, ArtistDescResponse};
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn artist_desc_payload_contains_id() {
+        let req = ArtistDescRequest::new(6452);
+        assert_eq!(req.endpoint(), "/api/artist/introduction");
+        assert_eq!(req.payload(), json!({ "id": 6452 }));
+    }
+
+    #[test]
+    fn artist_desc_response_maps_sections() {
+        let response: ArtistDescResponse = serde_json::from_value(json!({
+            "code": 200,
+            "briefDesc": "brief",
+            "introduction": [{ "ti": "经历", "txt": "text" }]
+        }))
+        .unwrap();
+        assert_eq!(response.introduction.len(), 1);
+        assert_eq!(response.introduction[0].title.as_deref(), Some("经历"));
+        assert_eq!(response.introduction[0].text.as_deref(), Some("text"));
+    }
+}
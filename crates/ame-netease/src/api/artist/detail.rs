@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::api::request::ApiRequest;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ArtistProfileDto {
+    pub id: i64,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub cover: Option<String>,
+    #[serde(default)]
+    pub avatar: Option<String>,
+    #[serde(default, rename = "briefDesc")]
+    pub brief_desc: Option<String>,
+    #[serde(default)]
+    pub alias: Vec<String>,
+    #[serde(default, rename = "transNames")]
+    pub trans_names: Vec<String>,
+    #[serde(default, rename = "musicSize")]
+    pub music_size: Option<u64>,
+    #[serde(default, rename = "albumSize")]
+    pub album_size: Option<u64>,
+    #[serde(default, rename = "mvSize")]
+    pub mv_size: Option<u64>,
+    #[serde(default)]
+    pub followed: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ArtistDetailData {
+    #[serde(default)]
+    pub artist: ArtistProfileDto,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ArtistDetailResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub data: ArtistDetailData,
+}
+
+pub struct ArtistDetailRequest {
+    id: i64,
+}
+
+impl ArtistDetailRequest {
+    pub fn new(id: i64) -> Self {
+        Self { id }
+    }
+}
+
+impl ApiRequest for ArtistDetailRequest {
+    type Response = ArtistDetailResponse;
+
+    fn endpoint(&self) -> &'static str {
+        "/api/artist/head/info/get"
+    }
+
+    fn payload(&self) -> Value {
+        json!({ "id": self.id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{ArtistDetailRequest, ArtistDetailResponse};
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn artist_detail_payload_contains_id() {
+        let req = ArtistDetailRequest::new(12138269);
+        assert_eq!(req.endpoint(), "/api/artist/head/info/get");
+        assert_eq!(req.payload(), json!({ "id": 12138269 }));
+    }
+
+    #[test]
+    fn artist_detail_response_reads_nested_artist() {
+        let response: ArtistDetailResponse = serde_json::from_value(json!({
+            "code": 200,
+            "data": {
+                "artist": {
+                    "id": 12138269,
+                    "name": "Artist",
+                    "briefDesc": "brief",
+                    "musicSize": 120,
+                    "albumSize": 9,
+                    "transNames": ["Trans"]
+                }
+            }
+        }))
+        .unwrap();
+        let artist = response.data.artist;
+        assert_eq!(artist.id, 12138269);
+        assert_eq!(artist.brief_desc.as_deref(), Some("brief"));
+        assert_eq!(artist.music_size, Some(120));
+        assert_eq!(artist.trans_names, ["Trans"]);
+        assert_eq!(artist.followed, None);
+    }
+
+    #[tokio::test]
+    async fn live_artist_detail_request() {
+        let client = crate::NeteaseClient::new();
+        let response = client
+            .weapi_request(ArtistDetailRequest::new(6452))
+            .await
+            .expect("artist detail request failed");
+
+        assert_eq!(response.code, 200);
+        assert_eq!(response.data.artist.id, 6452);
+    }
+}
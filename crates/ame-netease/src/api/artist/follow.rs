@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::api::request::ApiRequest;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct FollowArtistResponse {
+    pub code: i64,
+}
+
+pub struct FollowArtistRequest {
+    id: i64,
+    follow: bool,
+}
+
+impl FollowArtistRequest {
+    pub fn new(id: i64, follow: bool) -> Self {
+        Self { id, follow }
+    }
+}
+
+impl ApiRequest for FollowArtistRequest {
+    type Response = FollowArtistResponse;
+
+    fn endpoint(&self) -> &'static str {
+        if self.follow {
+            "/api/artist/sub"
+        } else {
+            "/api/artist/unsub"
+        }
+    }
+
+    fn payload(&self) -> Value {
+        json!({
+            "artistId": self.id,
+            "artistIds": format!("[{}]", self.id),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::FollowArtistRequest;
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn follow_and_unfollow_pick_endpoint() {
+        let follow = FollowArtistRequest::new(6452, true);
+        assert_eq!(follow.endpoint(), "/api/artist/sub");
+        assert_eq!(
+            follow.payload(),
+            json!({
+                "artistId": 6452,
+                "artistIds": "[6452]",
+            })
+        );
+        assert_eq!(
+            FollowArtistRequest::new(6452, false).endpoint(),
+            "/api/artist/unsub"
+        );
+    }
+}
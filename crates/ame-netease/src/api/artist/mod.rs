@@ -1 +1,9 @@
+pub mod albums;
+pub mod desc;
+pub mod detail;
+pub mod follow;
+pub mod mvs;
+pub mod similar;
+pub mod songs;
+pub mod top_song;
 pub mod toplist;
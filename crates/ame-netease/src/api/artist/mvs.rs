@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::api::common::models::MvDto;
+use crate::api::request::ApiRequest;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ArtistMvsResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub mvs: Vec<MvDto>,
+    #[serde(default, rename = "hasMore")]
+    pub has_more: bool,
+}
+
+pub struct ArtistMvsRequest {
+    id: i64,
+    limit: u32,
+    offset: u32,
+}
+
+impl ArtistMvsRequest {
+    pub fn new(id: i64, limit: u32, offset: u32) -> Self {
+        Self { id, limit, offset }
+    }
+}
+
+impl ApiRequest for ArtistMvsRequest {
+    type Response = ArtistMvsResponse;
+
+    fn endpoint(&self) -> &'static str {
+        "/api/artist/mvs"
+    }
+
+    fn payload(&self) -> Value {
+        json!({
+            "artistId": self.id,
+            "limit": self.limit,
+            "offset": self.offset,
+            "total": true,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{ArtistMvsRequest, ArtistMvsResponse};
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn artist_mvs_payload_uses_artist_id() {
+        let req = ArtistMvsRequest::new(6452, 30, 0);
+        assert_eq!(req.endpoint(), "/api/artist/mvs");
+        assert_eq!(
+            req.payload(),
+            json!({
+                "artistId": 6452,
+                "limit": 30,
+                "offset": 0,
+                "total": true,
+            })
+        );
+    }
+
+    #[test]
+    fn artist_mvs_response_reads_cover_aliases() {
+        let response: ArtistMvsResponse = serde_json::from_value(json!({
+            "code": 200,
+            "hasMore": true,
+            "mvs": [{ "id": 1, "name": "MV", "imgurl": "https://p1.music.126.net/a.jpg" }]
+        }))
+        .unwrap();
+        assert!(response.has_more);
+        assert_eq!(
+            response.mvs[0].cover_url.as_deref(),
+            Some("https://p1.music.126.net/a.jpg")
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::api::common::models::ArtistDto;
+use crate::api::request::ApiRequest;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SimilarArtistResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub artists: Vec<ArtistDto>,
+}
+
+pub struct SimilarArtistRequest {
+    id: i64,
+}
+
+impl SimilarArtistRequest {
+    pub fn new(id: i64) -> Self {
+        Self { id }
+    }
+}
+
+impl ApiRequest for SimilarArtistRequest {
+    type Response = SimilarArtistResponse;
+
+    fn endpoint(&self) -> &'static str {
+        "/api/discovery/simiArtist"
+    }
+
+    fn payload(&self) -> Value {
+        json!({ "artistid": self.id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::SimilarArtistRequest;
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn similar_artist_payload_uses_lowercase_key() {
+        let req = SimilarArtistRequest::new(6452);
+        assert_eq!(req.endpoint(), "/api/discovery/simiArtist");
+        assert_eq!(req.payload(), json!({ "artistid": 6452 }));
+    }
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::api::common::models::TrackDto;
+use crate::api::request::ApiRequest;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArtistSongOrder {
+    #[default]
+    Hot,
+    Time,
+}
+
+impl ArtistSongOrder {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Hot => "hot",
+            Self::Time => "time",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ArtistSongsResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub songs: Vec<TrackDto>,
+    #[serde(default)]
+    pub more: bool,
+    #[serde(default)]
+    pub total: Option<u64>,
+}
+
+pub struct ArtistSongsRequest {
+    id: i64,
+    order: ArtistSongOrder,
+    limit: u32,
+    offset: u32,
+}
+
+impl ArtistSongsRequest {
+    pub fn new(id: i64, limit: u32, offset: u32) -> Self {
+        Self {
+            id,
+            order: ArtistSongOrder::default(),
+            limit,
+            offset,
+        }
+    }
+
+    pub fn with_order(mut self, order: ArtistSongOrder) -> Self {
+        self.order = order;
+        self
+    }
+}
+
+impl ApiRequest for ArtistSongsRequest {
+    type Response = ArtistSongsResponse;
+
+    fn endpoint(&self) -> &'static str {
+        "/api/v1/artist/songs"
+    }
+
+    fn payload(&self) -> Value {
+        json!({
+            "id": self.id,
+            "private_cloud": "true",
+            "work_type": 1,
+            "order": self.order.as_str(),
+            "offset": self.offset,
+            "limit": self.limit,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{ArtistSongOrder, ArtistSongsRequest};
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn artist_songs_payload_defaults_to_hot() {
+        let req = ArtistSongsRequest::new(6452, 100, 0);
+        assert_eq!(req.endpoint(), "/api/v1/artist/songs");
+        assert_eq!(
+            req.payload(),
+            json!({
+                "id": 6452,
+                "private_cloud": "true",
+                "work_type": 1,
+                "order": "hot",
+                "offset": 0,
+                "limit": 100,
+            })
+        );
+    }
+
+    #[test]
+    fn artist_songs_payload_sorts_by_time() {
+        let req = ArtistSongsRequest::new(6452, 50, 100).with_order(ArtistSongOrder::Time);
+        let payload = req.payload();
+        assert_eq!(payload["order"].as_str(), Some("time"));
+        assert_eq!(payload["offset"].as_u64(), Some(100));
+    }
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::api::common::models::TrackDto;
+use crate::api::request::ApiRequest;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ArtistTopSongResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub songs: Vec<TrackDto>,
+    #[serde(default)]
+    pub more: bool,
+}
+
+// The artist's 50 most played songs.
+pub struct ArtistTopSongRequest {
+    id: i64,
+}
+
+impl ArtistTopSongRequest {
+    pub fn new(id: i64) -> Self {
+        Self { id }
+    }
+}
+
+impl ApiRequest for ArtistTopSongRequest {
+    type Response = ArtistTopSongResponse;
+
+    fn endpoint(&self) -> &'static str {
+        "/api/artist/top/song"
+    }
+
+    fn payload(&self) -> Value {
+        json!({ "id": self.id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::ArtistTopSongRequest;
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn artist_top_song_payload_contains_id() {
+        let req = ArtistTopSongRequest::new(6452);
+        assert_eq!(req.endpoint(), "/api/artist/top/song");
+        assert_eq!(req.payload(), json!({ "id": 6452 }));
+    }
+
+    #[tokio::test]
+    async fn live_artist_top_song_request() {
+        let client = crate::NeteaseClient::new();
+        let response = client
+            .weapi_request(ArtistTopSongRequest::new(6452))
+            .await
+            .expect("artist top song request failed");
+
+        assert_eq!(response.code, 200);
+        assert!(!response.songs.is_empty());
+    }
+}
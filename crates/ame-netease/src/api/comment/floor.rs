@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::api::comment::thread::CommentResource;
+use crate::api::common::models::CommentDto;
+use crate::api::request::ApiRequest;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct CommentFloorDto {
+    #[serde(default)]
+    pub comments: Vec<CommentDto>,
+    #[serde(default, rename = "ownerComment")]
+    pub owner_comment: Option<CommentDto>,
+    #[serde(default, rename = "totalCount")]
+    pub total_count: u64,
+    #[serde(default, rename = "hasMore")]
+    pub has_more: bool,
+    // Cursor for the next page: the time of the last reply.
+    #[serde(default)]
+    pub time: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct CommentFloorResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub data: CommentFloorDto,
+}
+
+// Replies under one comment.
+pub struct CommentFloorRequest {
+    resource: CommentResource,
+    parent_comment_id: i64,
+    limit: u32,
+    time: Option<i64>,
+}
+
+impl CommentFloorRequest {
+    pub fn new(resource: CommentResource, parent_comment_id: i64) -> Self {
+        Self {
+            resource,
+            parent_comment_id,
+            limit: 20,
+            time: None,
+        }
+    }
+
+    pub fn next_page(&self, floor: &CommentFloorDto) -> Option<Self> {
+        floor.has_more.then_some(Self {
+            resource: self.resource,
+            parent_comment_id: self.parent_comment_id,
+            limit: self.limit,
+            time: floor.time,
+        })
+    }
+}
+
+impl ApiRequest for CommentFloorRequest {
+    type Response = CommentFloorResponse;
+
+    fn endpoint(&self) -> &'static str {
+        "/api/resource/comment/floor/get"
+    }
+
+    fn payload(&self) -> Value {
+        json!({
+            "parentCommentId": self.parent_comment_id,
+            "threadId": self.resource.thread_id(),
+            "time": self.time.unwrap_or(-1),
+            "limit": self.limit,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{CommentFloorDto, CommentFloorRequest};
+    use crate::api::comment::thread::CommentResource;
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn floor_payload_starts_without_cursor() {
+        let req = CommentFloorRequest::new(CommentResource::Mv(5436712), 42);
+        assert_eq!(req.endpoint(), "/api/resource/comment/floor/get");
+        assert_eq!(
+            req.payload(),
+            json!({
+                "parentCommentId": 42,
+                "threadId": "R_MV_5_5436712",
+                "time": -1,
+                "limit": 20,
+            })
+        );
+    }
+
+    #[test]
+    fn floor_next_page_uses_reply_time() {
+        let req = CommentFloorRequest::new(CommentResource::Song(326904), 42);
+        let floor = CommentFloorDto {
+            has_more: true,
+            time: Some(1_700_000_000_000),
+            ..Default::default()
+        };
+        let next = req.next_page(&floor).unwrap();
+        assert_eq!(next.payload()["time"].as_i64(), Some(1_700_000_000_000));
+    }
+}
@@ -0,0 +1,73 @@
+use serde_json::{Value, json};
+
+use crate::api::comment::thread::CommentResource;
+use crate::api::common::models::WriteResponse;
+use crate::api::request::ApiRequest;
+
+pub struct CommentLikeRequest {
+    resource: CommentResource,
+    comment_id: i64,
+    like: bool,
+    csrf_token: String,
+}
+
+impl CommentLikeRequest {
+    pub fn new(
+        resource: CommentResource,
+        comment_id: i64,
+        like: bool,
+        csrf_token: impl Into<String>,
+    ) -> Self {
+        Self {
+            resource,
+            comment_id,
+            like,
+            csrf_token: csrf_token.into(),
+        }
+    }
+}
+
+impl ApiRequest for CommentLikeRequest {
+    type Response = WriteResponse;
+
+    fn endpoint(&self) -> &'static str {
+        if self.like {
+            "/api/v1/comment/like"
+        } else {
+            "/api/v1/comment/unlike"
+        }
+    }
+
+    fn payload(&self) -> Value {
+        json!({
+            "threadId": self.resource.thread_id(),
+            "commentId": self.comment_id,
+            "csrf_token": self.csrf_token,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::CommentLikeRequest;
+    use crate::api::comment::thread::CommentResource;
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn like_and_unlike_pick_endpoint() {
+        let like = CommentLikeRequest::new(CommentResource::Song(326904), 42, true, "csrf");
+        assert_eq!(like.endpoint(), "/api/v1/comment/like");
+        assert_eq!(
+            like.payload(),
+            json!({
+                "threadId": "R_SO_4_326904",
+                "commentId": 42,
+                "csrf_token": "csrf",
+            })
+        );
+        let unlike = CommentLikeRequest::new(CommentResource::Song(326904), 42, false, "csrf");
+        assert_eq!(unlike.endpoint(), "/api/v1/comment/unlike");
+    }
+}
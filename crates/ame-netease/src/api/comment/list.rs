@@ -0,0 +1,236 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::api::comment::thread::CommentResource;
+use crate::api::common::models::CommentDto;
+use crate::api::request::ApiRequest;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CommentSort {
+    #[default]
+    Recommended,
+    Hot,
+    Latest,
+}
+
+impl CommentSort {
+    pub const fn code(self) -> u8 {
+        match self {
+            Self::Recommended => 99,
+            Self::Hot => 2,
+            Self::Latest => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct CommentPageDto {
+    #[serde(default)]
+    pub comments: Vec<CommentDto>,
+    #[serde(default, rename = "totalCount")]
+    pub total_count: u64,
+    #[serde(default, rename = "hasMore")]
+    pub has_more: bool,
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct CommentListResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub data: CommentPageDto,
+}
+
+pub struct CommentListRequest {
+    resource: CommentResource,
+    sort: CommentSort,
+    page_no: u32,
+    page_size: u32,
+    // Only used by `Latest`, which pages by the time of the last comment.
+    cursor: Option<String>,
+}
+
+impl CommentListRequest {
+    pub fn new(resource: CommentResource, sort: CommentSort) -> Self {
+        Self {
+            resource,
+            sort,
+            page_no: 1,
+            page_size: 20,
+            cursor: None,
+        }
+    }
+
+    pub fn with_page_size(mut self, page_size: u32) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    // The request for the page after `page`, or `None` once it was the last.
+    pub fn next_page(&self, page: &CommentPageDto) -> Option<Self> {
+        page.has_more.then(|| Self {
+            resource: self.resource,
+            sort: self.sort,
+            page_no: self.page_no + 1,
+            page_size: self.page_size,
+            cursor: page.cursor.clone(),
+        })
+    }
+
+    fn cursor(&self) -> String {
+        let skipped = self.page_no.saturating_sub(1) * self.page_size;
+        match self.sort {
+            CommentSort::Recommended => skipped.to_string(),
+            CommentSort::Hot => format!("normalHot#{skipped}"),
+            CommentSort::Latest => self.cursor.clone().unwrap_or_else(|| "0".to_string()),
+        }
+    }
+}
+
+impl ApiRequest for CommentListRequest {
+    type Response = CommentListResponse;
+
+    fn endpoint(&self) -> &'static str {
+        "/api/v2/resource/comments"
+    }
+
+    fn payload(&self) -> Value {
+        json!({
+            "threadId": self.resource.thread_id(),
+            "pageNo": self.page_no,
+            "pageSize": self.page_size,
+            "showInner": true,
+            "cursor": self.cursor(),
+            "sortType": self.sort.code(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{CommentListRequest, CommentListResponse, CommentSort};
+    use crate::api::comment::thread::CommentResource;
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn hot_comments_payload() {
+        let req = CommentListRequest::new(CommentResource::Song(326904), CommentSort::Hot);
+        assert_eq!(req.endpoint(), "/api/v2/resource/comments");
+        assert_eq!(
+            req.payload(),
+            json!({
+                "threadId": "R_SO_4_326904",
+                "pageNo": 1,
+                "pageSize": 20,
+                "showInner": true,
+                "cursor": "normalHot#0",
+                "sortType": 2,
+            })
+        );
+    }
+
+    #[test]
+    fn latest_comments_follow_the_response_cursor() {
+        let req = CommentListRequest::new(CommentResource::Album(32311), CommentSort::Latest)
+            .with_page_size(30);
+        assert_eq!(req.payload()["cursor"].as_str(), Some("0"));
+
+        let response: CommentListResponse = serde_json::from_value(json!({
+            "code": 200,
+            "data": {
+                "comments": [{
+                    "commentId": 1,
+                    "user": { "userId": 9, "nickname": "listener", "avatarUrl": "https://p1.music.126.net/u.jpg" },
+                    "content": "nice",
+                    "time": 1_700_000_000_000_u64,
+                    "timeStr": "2023-11-14",
+                    "likedCount": 5,
+                    "liked": false,
+                    "beReplied": [{ "beRepliedCommentId": 0, "content": "hi" }],
+                    "showFloorComment": { "replyCount": 2 }
+                }],
+                "totalCount": 31,
+                "hasMore": true,
+                "cursor": "1699999999999"
+            }
+        }))
+        .unwrap();
+        let comment = &response.data.comments[0];
+        assert_eq!(comment.user.nickname.as_deref(), Some("listener"));
+        assert_eq!(comment.liked_count, 5);
+        assert_eq!(
+            comment
+                .replied
+                .as_deref()
+                .map(|replied| replied[0].content.as_deref()),
+            Some(Some("hi"))
+        );
+        assert_eq!(
+            comment.floor.as_ref().map(|floor| floor.reply_count),
+            Some(2)
+        );
+
+        let next = req.next_page(&response.data).unwrap();
+        let payload = next.payload();
+        assert_eq!(payload["pageNo"].as_u64(), Some(2));
+        assert_eq!(payload["pageSize"].as_u64(), Some(30));
+        assert_eq!(payload["cursor"].as_str(), Some("1699999999999"));
+    }
+
+    #[test]
+    fn top_level_comment_without_replies_parses() {
+        let response: CommentListResponse = serde_json::from_value(json!({
+            "code": 200,
+            "data": {
+                "comments": [{
+                    "commentId": 2,
+                    "user": { "userId": 9, "nickname": "listener" },
+                    "content": "first",
+                    "beReplied": null,
+                    "showFloorComment": null
+                }],
+                "totalCount": 1,
+                "hasMore": false
+            }
+        }))
+        .unwrap();
+        let comment = &response.data.comments[0];
+        assert_eq!(comment.content.as_deref(), Some("first"));
+        assert!(comment.replied.is_none());
+        assert!(comment.floor.is_none());
+    }
+
+    #[test]
+    fn recommended_pages_by_offset_and_stop_at_the_end() {
+        let req =
+            CommentListRequest::new(CommentResource::Playlist(7001), CommentSort::Recommended);
+        let mut page = super::CommentPageDto {
+            has_more: true,
+            ..Default::default()
+        };
+        let next = req.next_page(&page).unwrap();
+        assert_eq!(next.payload()["cursor"].as_str(), Some("20"));
+        assert_eq!(next.payload()["sortType"].as_u64(), Some(99));
+
+        page.has_more = false;
+        assert!(next.next_page(&page).is_none());
+    }
+
+    #[tokio::test]
+    async fn live_song_hot_comments_request() {
+        let client = crate::NeteaseClient::new();
+        let response = client
+            .eapi_request(CommentListRequest::new(
+                CommentResource::Song(326904),
+                CommentSort::Hot,
+            ))
+            .await
+            .expect("comment list request failed");
+
+        assert_eq!(response.code, 200);
+        assert!(!response.data.comments.is_empty());
+    }
+}
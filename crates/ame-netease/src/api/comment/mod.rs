@@ -0,0 +1,5 @@
+pub mod floor;
+pub mod like;
+pub mod list;
+pub mod thread;
+pub mod write;
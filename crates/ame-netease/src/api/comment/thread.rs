@@ -0,0 +1,33 @@
+// What a comment thread hangs off. The thread id is the resource id behind a
+// per-type prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommentResource {
+    Song(i64),
+    Playlist(i64),
+    Album(i64),
+    Mv(i64),
+}
+
+impl CommentResource {
+    pub fn thread_id(self) -> String {
+        match self {
+            Self::Song(id) => format!("R_SO_4_{id}"),
+            Self::Playlist(id) => format!("A_PL_0_{id}"),
+            Self::Album(id) => format!("R_AL_3_{id}"),
+            Self::Mv(id) => format!("R_MV_5_{id}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CommentResource;
+
+    #[test]
+    fn thread_ids_use_resource_prefixes() {
+        assert_eq!(CommentResource::Song(326904).thread_id(), "R_SO_4_326904");
+        assert_eq!(CommentResource::Playlist(7001).thread_id(), "A_PL_0_7001");
+        assert_eq!(CommentResource::Album(32311).thread_id(), "R_AL_3_32311");
+        assert_eq!(CommentResource::Mv(5436712).thread_id(), "R_MV_5_5436712");
+    }
+}
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::api::comment::thread::CommentResource;
+use crate::api::common::models::{CommentDto, WriteStatus};
+use crate::api::request::ApiRequest;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommentAction {
+    Post { content: String },
+    Reply { comment_id: i64, content: String },
+    Delete { comment_id: i64 },
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct CommentWriteResponse {
+    pub code: i64,
+    // The new comment, for posts and replies.
+    #[serde(default)]
+    pub comment: Option<CommentDto>,
+    #[serde(default, alias = "msg")]
+    pub message: Option<String>,
+}
+
+impl CommentWriteResponse {
+    pub const fn status(&self) -> WriteStatus {
+        WriteStatus::from_code(self.code)
+    }
+}
+
+pub struct CommentWriteRequest {
+    resource: CommentResource,
+    action: CommentAction,
+    csrf_token: String,
+}
+
+impl CommentWriteRequest {
+    pub fn new(
+        resource: CommentResource,
+        action: CommentAction,
+        csrf_token: impl Into<String>,
+    ) -> Self {
+        Self {
+            resource,
+            action,
+            csrf_token: csrf_token.into(),
+        }
+    }
+
+    pub fn post(
+        resource: CommentResource,
+        content: impl Into<String>,
+        csrf_token: impl Into<String>,
+    ) -> Self {
+        let content = content.into();
+        Self::new(resource, CommentAction::Post { content }, csrf_token)
+    }
+
+    pub fn reply(
+        resource: CommentResource,
+        comment_id: i64,
+        content: impl Into<String>,
+        csrf_token: impl Into<String>,
+    ) -> Self {
+        let content = content.into();
+        Self::new(
+            resource,
+            CommentAction::Reply {
+                comment_id,
+                content,
+            },
+            csrf_token,
+        )
+    }
+
+    pub fn delete(
+        resource: CommentResource,
+        comment_id: i64,
+        csrf_token: impl Into<String>,
+    ) -> Self {
+        Self::new(resource, CommentAction::Delete { comment_id }, csrf_token)
+    }
+}
+
+impl ApiRequest for CommentWriteRequest {
+    type Response = CommentWriteResponse;
+
+    fn endpoint(&self) -> &'static str {
+        match self.action {
+            CommentAction::Post { .. } => "/api/resource/comments/add",
+            CommentAction::Reply { .. } => "/api/resource/comments/reply",
+            CommentAction::Delete { .. } => "/api/resource/comments/delete",
+        }
+    }
+
+    fn payload(&self) -> Value {
+        let mut payload = json!({
+            "threadId": self.resource.thread_id(),
+            "csrf_token": self.csrf_token,
+        });
+        match &self.action {
+            CommentAction::Post { content } => payload["content"] = json!(content),
+            CommentAction::Reply {
+                comment_id,
+                content,
+            } => {
+                payload["commentId"] = json!(comment_id);
+                payload["content"] = json!(content);
+            }
+            CommentAction::Delete { comment_id } => payload["commentId"] = json!(comment_id),
+        }
+        payload
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{CommentWriteRequest, CommentWriteResponse};
+    use crate::api::comment::thread::CommentResource;
+    use crate::api::common::models::WriteStatus;
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn post_payload() {
+        let req = CommentWriteRequest::post(CommentResource::Song(326904), "great", "csrf");
+        assert_eq!(req.endpoint(), "/api/resource/comments/add");
+        assert_eq!(
+            req.payload(),
+            json!({
+                "threadId": "R_SO_4_326904",
+                "content": "great",
+                "csrf_token": "csrf",
+            })
+        );
+    }
+
+    #[test]
+    fn reply_and_delete_carry_comment_id() {
+        let reply =
+            CommentWriteRequest::reply(CommentResource::Playlist(7001), 42, "agreed", "csrf");
+        assert_eq!(reply.endpoint(), "/api/resource/comments/reply");
+        assert_eq!(reply.payload()["commentId"].as_i64(), Some(42));
+        assert_eq!(reply.payload()["content"].as_str(), Some("agreed"));
+
+        let delete = CommentWriteRequest::delete(CommentResource::Playlist(7001), 42, "csrf");
+        assert_eq!(delete.endpoint(), "/api/resource/comments/delete");
+        assert_eq!(delete.payload()["commentId"].as_i64(), Some(42));
+        assert!(delete.payload().get("content").is_none());
+    }
+
+    #[test]
+    fn write_response_returns_new_comment() {
+        let response: CommentWriteResponse = serde_json::from_value(json!({
+            "code": 200,
+            "comment": { "commentId": 43, "content": "great", "time": 1_700_000_000_000_u64 }
+        }))
+        .unwrap();
+        assert_eq!(response.status(), WriteStatus::Ok);
+        assert_eq!(response.comment.map(|comment| comment.comment_id), Some(43));
+    }
+}
@@ -1 +1,12 @@
 pub mod models;
+
+// Formats ids the way list parameters are sent: `[1,2,3]`.
+pub(crate) fn id_list(ids: &[i64]) -> String {
+    format!(
+        "[{}]",
+        ids.iter()
+            .map(std::string::ToString::to_string)
+            .collect::<Vec<String>>()
+            .join(",")
+    )
+}
@@ -51,6 +51,16 @@ pub struct AlbumDto {
     pub artist: Option<ArtistDto>,
     #[serde(default)]
     pub artists: Vec<ArtistDto>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default, rename = "publishTime")]
+    pub publish_time: Option<u64>,
+    #[serde(default)]
+    pub company: Option<String>,
+    #[serde(default)]
+    pub size: Option<u64>,
+    #[serde(default, rename = "subType")]
+    pub sub_type: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -62,6 +72,31 @@ pub struct TrackAlbumDto {
     pub pic_url: Option<String>,
 }
 
+// Playback and download rights the account has for a track. `st` below zero
+// means the track is unavailable; `pl` is the highest bitrate it may play.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TrackPrivilegeDto {
+    pub id: i64,
+    #[serde(default)]
+    pub fee: Option<i64>,
+    #[serde(default)]
+    pub payed: Option<i64>,
+    #[serde(default)]
+    pub st: Option<i64>,
+    #[serde(default)]
+    pub pl: Option<u64>,
+    #[serde(default)]
+    pub dl: Option<u64>,
+    #[serde(default)]
+    pub maxbr: Option<u64>,
+}
+
+impl TrackPrivilegeDto {
+    pub fn is_playable(&self) -> bool {
+        self.st.is_none_or(|st| st >= 0) && self.pl != Some(0)
+    }
+}
+
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct TrackDto {
     pub id: i64,
@@ -81,6 +116,10 @@ pub struct TrackDto {
     pub duration_ms: Option<u64>,
     #[serde(default, rename = "picUrl")]
     pub pic_url: Option<String>,
+    #[serde(default)]
+    pub fee: Option<i64>,
+    #[serde(default)]
+    pub privilege: Option<TrackPrivilegeDto>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -100,3 +139,118 @@ pub struct UserProfileDto {
     #[serde(default, rename = "avatarUrl")]
     pub avatar_url: Option<String>,
 }
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct MvDto {
+    pub id: i64,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default, rename = "imgurl16v9", alias = "imgurl")]
+    pub cover_url: Option<String>,
+    #[serde(default, rename = "artistName")]
+    pub artist_name: Option<String>,
+    #[serde(default, rename = "duration")]
+    pub duration_ms: Option<u64>,
+    #[serde(default, rename = "playCount")]
+    pub play_count: Option<u64>,
+    #[serde(default, rename = "publishTime")]
+    pub publish_time: Option<String>,
+}
+
+// Outcome of a write endpoint, read from the response `code`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteStatus {
+    Ok,
+    BadRequest,
+    NeedLogin,
+    Forbidden,
+    NotFound,
+    TooFrequent,
+    // The track is already in the playlist.
+    Duplicate,
+    Other(i64),
+}
+
+impl WriteStatus {
+    pub const fn from_code(code: i64) -> Self {
+        match code {
+            200 => Self::Ok,
+            400 => Self::BadRequest,
+            301 => Self::NeedLogin,
+            401 | 403 => Self::Forbidden,
+            404 => Self::NotFound,
+            405 => Self::TooFrequent,
+            502 => Self::Duplicate,
+            other => Self::Other(other),
+        }
+    }
+
+    pub const fn is_ok(self) -> bool {
+        matches!(self, Self::Ok)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct WriteResponse {
+    pub code: i64,
+    #[serde(default, alias = "msg")]
+    pub message: Option<String>,
+}
+
+impl WriteResponse {
+    pub const fn status(&self) -> WriteStatus {
+        WriteStatus::from_code(self.code)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct CommentUserDto {
+    #[serde(default, rename = "userId")]
+    pub user_id: Option<i64>,
+    #[serde(default)]
+    pub nickname: Option<String>,
+    #[serde(default, rename = "avatarUrl")]
+    pub avatar_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RepliedCommentDto {
+    #[serde(default, rename = "beRepliedCommentId")]
+    pub comment_id: Option<i64>,
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default)]
+    pub user: Option<CommentUserDto>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct CommentFloorSummaryDto {
+    #[serde(default, rename = "replyCount")]
+    pub reply_count: u64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct CommentDto {
+    #[serde(rename = "commentId")]
+    pub comment_id: i64,
+    #[serde(default)]
+    pub user: CommentUserDto,
+    #[serde(default)]
+    pub content: Option<String>,
+    // Milliseconds since the epoch.
+    #[serde(default)]
+    pub time: u64,
+    #[serde(default, rename = "timeStr")]
+    pub time_str: Option<String>,
+    #[serde(default, rename = "likedCount")]
+    pub liked_count: u64,
+    #[serde(default)]
+    pub liked: bool,
+    #[serde(default, rename = "parentCommentId")]
+    pub parent_comment_id: Option<i64>,
+    // Null on top-level comments.
+    #[serde(default, rename = "beReplied")]
+    pub replied: Option<Vec<RepliedCommentDto>>,
+    #[serde(default, rename = "showFloorComment")]
+    pub floor: Option<CommentFloorSummaryDto>,
+}
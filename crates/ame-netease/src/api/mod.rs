@@ -2,6 +2,7 @@ pub mod request;
 
 pub mod album;
 pub mod artist;
+pub mod comment;
 pub mod common;
 pub mod mv;
 pub mod playlist;
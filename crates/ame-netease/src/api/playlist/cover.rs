@@ -0,0 +1,59 @@
+use serde_json::{Value, json};
+
+use crate::api::common::models::WriteResponse;
+use crate::api::request::ApiRequest;
+
+// Points the playlist at an already uploaded image.
+pub struct PlaylistCoverRequest {
+    id: i64,
+    cover_img_id: String,
+    csrf_token: String,
+}
+
+impl PlaylistCoverRequest {
+    pub fn new(id: i64, cover_img_id: impl Into<String>, csrf_token: impl Into<String>) -> Self {
+        Self {
+            id,
+            cover_img_id: cover_img_id.into(),
+            csrf_token: csrf_token.into(),
+        }
+    }
+}
+
+impl ApiRequest for PlaylistCoverRequest {
+    type Response = WriteResponse;
+
+    fn endpoint(&self) -> &'static str {
+        "/api/playlist/cover/update"
+    }
+
+    fn payload(&self) -> Value {
+        json!({
+            "id": self.id,
+            "coverImgId": self.cover_img_id,
+            "csrf_token": self.csrf_token,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::PlaylistCoverRequest;
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn cover_payload_uses_image_id() {
+        let req = PlaylistCoverRequest::new(7001, "109951163", "csrf");
+        assert_eq!(req.endpoint(), "/api/playlist/cover/update");
+        assert_eq!(
+            req.payload(),
+            json!({
+                "id": 7001,
+                "coverImgId": "109951163",
+                "csrf_token": "csrf",
+            })
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::api::common::models::{PlaylistDto, WriteStatus};
+use crate::api::request::ApiRequest;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlaylistPrivacy {
+    #[default]
+    Public,
+    Private,
+}
+
+impl PlaylistPrivacy {
+    pub const fn code(self) -> u8 {
+        match self {
+            Self::Public => 0,
+            Self::Private => 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PlaylistCreateResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub id: Option<i64>,
+    #[serde(default)]
+    pub playlist: Option<PlaylistDto>,
+    #[serde(default, alias = "msg")]
+    pub message: Option<String>,
+}
+
+impl PlaylistCreateResponse {
+    pub const fn status(&self) -> WriteStatus {
+        WriteStatus::from_code(self.code)
+    }
+}
+
+pub struct PlaylistCreateRequest {
+    name: String,
+    privacy: PlaylistPrivacy,
+    csrf_token: String,
+}
+
+impl PlaylistCreateRequest {
+    pub fn new(name: impl Into<String>, csrf_token: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            privacy: PlaylistPrivacy::default(),
+            csrf_token: csrf_token.into(),
+        }
+    }
+
+    pub fn with_privacy(mut self, privacy: PlaylistPrivacy) -> Self {
+        self.privacy = privacy;
+        self
+    }
+}
+
+impl ApiRequest for PlaylistCreateRequest {
+    type Response = PlaylistCreateResponse;
+
+    fn endpoint(&self) -> &'static str {
+        "/api/playlist/create"
+    }
+
+    fn payload(&self) -> Value {
+        json!({
+            "name": self.name,
+            "privacy": self.privacy.code(),
+            "type": "NORMAL",
+            "csrf_token": self.csrf_token,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{PlaylistCreateRequest, PlaylistCreateResponse, PlaylistPrivacy};
+    use crate::api::common::models::WriteStatus;
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn create_payload_defaults_to_public() {
+        let req = PlaylistCreateRequest::new("Road trip", "csrf");
+        assert_eq!(req.endpoint(), "/api/playlist/create");
+        assert_eq!(
+            req.payload(),
+            json!({
+                "name": "Road trip",
+                "privacy": 0,
+                "type": "NORMAL",
+                "csrf_token": "csrf",
+            })
+        );
+    }
+
+    #[test]
+    fn create_payload_marks_private_playlists() {
+        let req =
+            PlaylistCreateRequest::new("Secret", "csrf").with_privacy(PlaylistPrivacy::Private);
+        assert_eq!(req.payload()["privacy"].as_u64(), Some(10));
+    }
+
+    #[test]
+    fn create_response_reports_new_id() {
+        let response: PlaylistCreateResponse = serde_json::from_value(json!({
+            "code": 200,
+            "id": 7001,
+            "playlist": { "id": 7001, "name": "Road trip" }
+        }))
+        .unwrap();
+        assert_eq!(response.status(), WriteStatus::Ok);
+        assert_eq!(response.id, Some(7001));
+    }
+}
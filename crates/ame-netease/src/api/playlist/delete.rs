@@ -0,0 +1,66 @@
+use serde_json::{Value, json};
+
+use crate::api::common::id_list;
+use crate::api::common::models::WriteResponse;
+use crate::api::request::ApiRequest;
+
+pub struct PlaylistDeleteRequest {
+    ids: Vec<i64>,
+    csrf_token: String,
+}
+
+impl PlaylistDeleteRequest {
+    pub fn new(ids: Vec<i64>, csrf_token: impl Into<String>) -> Self {
+        Self {
+            ids,
+            csrf_token: csrf_token.into(),
+        }
+    }
+}
+
+impl ApiRequest for PlaylistDeleteRequest {
+    type Response = WriteResponse;
+
+    fn endpoint(&self) -> &'static str {
+        "/api/playlist/remove"
+    }
+
+    fn payload(&self) -> Value {
+        json!({
+            "ids": id_list(&self.ids),
+            "csrf_token": self.csrf_token,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::PlaylistDeleteRequest;
+    use crate::api::common::models::{WriteResponse, WriteStatus};
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn delete_payload_joins_ids() {
+        let req = PlaylistDeleteRequest::new(vec![7001, 7002], "csrf");
+        assert_eq!(req.endpoint(), "/api/playlist/remove");
+        assert_eq!(
+            req.payload(),
+            json!({
+                "ids": "[7001,7002]",
+                "csrf_token": "csrf",
+            })
+        );
+    }
+
+    #[test]
+    fn write_response_maps_error_codes() {
+        let response: WriteResponse =
+            serde_json::from_value(json!({ "code": 404, "msg": "歌单不存在" })).unwrap();
+        assert_eq!(response.status(), WriteStatus::NotFound);
+        assert_eq!(response.message.as_deref(), Some("歌单不存在"));
+        assert!(!response.status().is_ok());
+        assert_eq!(WriteStatus::from_code(250), WriteStatus::Other(250));
+    }
+}
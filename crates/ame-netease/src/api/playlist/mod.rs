@@ -1,6 +1,13 @@
+pub mod cover;
+pub mod create;
+pub mod delete;
 pub mod detail;
 pub mod list;
+pub mod order;
 pub mod personalized;
 pub mod recommend_resource;
 pub mod recommend_songs;
+pub mod subscribe;
 pub mod toplist;
+pub mod tracks;
+pub mod update;
@@ -0,0 +1,57 @@
+use serde_json::{Value, json};
+
+use crate::api::common::id_list;
+use crate::api::common::models::WriteResponse;
+use crate::api::request::ApiRequest;
+
+// Sets the order of the user's own playlists, which is also the order of the
+// covers on their profile. `ids` lists every created playlist.
+pub struct PlaylistOrderRequest {
+    ids: Vec<i64>,
+    csrf_token: String,
+}
+
+impl PlaylistOrderRequest {
+    pub fn new(ids: Vec<i64>, csrf_token: impl Into<String>) -> Self {
+        Self {
+            ids,
+            csrf_token: csrf_token.into(),
+        }
+    }
+}
+
+impl ApiRequest for PlaylistOrderRequest {
+    type Response = WriteResponse;
+
+    fn endpoint(&self) -> &'static str {
+        "/api/playlist/order/update"
+    }
+
+    fn payload(&self) -> Value {
+        json!({
+            "ids": id_list(&self.ids),
+            "csrf_token": self.csrf_token,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::PlaylistOrderRequest;
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn order_payload_joins_ids() {
+        let req = PlaylistOrderRequest::new(vec![7002, 7001], "csrf");
+        assert_eq!(req.endpoint(), "/api/playlist/order/update");
+        assert_eq!(
+            req.payload(),
+            json!({
+                "ids": "[7002,7001]",
+                "csrf_token": "csrf",
+            })
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use serde_json::{Value, json};
+
+use crate::api::common::models::WriteResponse;
+use crate::api::request::ApiRequest;
+
+pub struct PlaylistSubscribeRequest {
+    id: i64,
+    subscribe: bool,
+    csrf_token: String,
+}
+
+impl PlaylistSubscribeRequest {
+    pub fn new(id: i64, subscribe: bool, csrf_token: impl Into<String>) -> Self {
+        Self {
+            id,
+            subscribe,
+            csrf_token: csrf_token.into(),
+        }
+    }
+}
+
+impl ApiRequest for PlaylistSubscribeRequest {
+    type Response = WriteResponse;
+
+    fn endpoint(&self) -> &'static str {
+        if self.subscribe {
+            "/api/playlist/subscribe"
+        } else {
+            "/api/playlist/unsubscribe"
+        }
+    }
+
+    fn payload(&self) -> Value {
+        json!({
+            "id": self.id,
+            "csrf_token": self.csrf_token,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::PlaylistSubscribeRequest;
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn subscribe_and_unsubscribe_pick_endpoint() {
+        let sub = PlaylistSubscribeRequest::new(7001, true, "csrf");
+        assert_eq!(sub.endpoint(), "/api/playlist/subscribe");
+        assert_eq!(
+            sub.payload(),
+            json!({
+                "id": 7001,
+                "csrf_token": "csrf",
+            })
+        );
+        assert_eq!(
+            PlaylistSubscribeRequest::new(7001, false, "csrf").endpoint(),
+            "/api/playlist/unsubscribe"
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::api::common::id_list;
+use crate::api::common::models::WriteStatus;
+use crate::api::request::ApiRequest;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaylistTrackOp {
+    Add,
+    Remove,
+    // `track_ids` is the complete new order of the playlist.
+    Reorder,
+}
+
+impl PlaylistTrackOp {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Add => "add",
+            Self::Remove => "del",
+            Self::Reorder => "update",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PlaylistTracksResponse {
+    pub code: i64,
+    #[serde(default)]
+    pub count: Option<u64>,
+    #[serde(default, rename = "trackIds")]
+    pub track_ids: Option<String>,
+    #[serde(default, alias = "msg")]
+    pub message: Option<String>,
+}
+
+impl PlaylistTracksResponse {
+    pub const fn status(&self) -> WriteStatus {
+        WriteStatus::from_code(self.code)
+    }
+}
+
+pub struct PlaylistTracksRequest {
+    playlist_id: i64,
+    op: PlaylistTrackOp,
+    track_ids: Vec<i64>,
+    csrf_token: String,
+}
+
+impl PlaylistTracksRequest {
+    pub fn new(
+        playlist_id: i64,
+        op: PlaylistTrackOp,
+        track_ids: Vec<i64>,
+        csrf_token: impl Into<String>,
+    ) -> Self {
+        Self {
+            playlist_id,
+            op,
+            track_ids,
+            csrf_token: csrf_token.into(),
+        }
+    }
+
+    pub fn add(playlist_id: i64, track_ids: Vec<i64>, csrf_token: impl Into<String>) -> Self {
+        Self::new(playlist_id, PlaylistTrackOp::Add, track_ids, csrf_token)
+    }
+
+    pub fn remove(playlist_id: i64, track_ids: Vec<i64>, csrf_token: impl Into<String>) -> Self {
+        Self::new(playlist_id, PlaylistTrackOp::Remove, track_ids, csrf_token)
+    }
+
+    pub fn reorder(playlist_id: i64, track_ids: Vec<i64>, csrf_token: impl Into<String>) -> Self {
+        Self::new(playlist_id, PlaylistTrackOp::Reorder, track_ids, csrf_token)
+    }
+}
+
+impl ApiRequest for PlaylistTracksRequest {
+    type Response = PlaylistTracksResponse;
+
+    fn endpoint(&self) -> &'static str {
+        "/api/playlist/manipulate/tracks"
+    }
+
+    fn payload(&self) -> Value {
+        json!({
+            "op": self.op.as_str(),
+            "pid": self.playlist_id,
+            "trackIds": id_list(&self.track_ids),
+            "imme": "true",
+            "csrf_token": self.csrf_token,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{PlaylistTracksRequest, PlaylistTracksResponse};
+    use crate::api::common::models::WriteStatus;
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn add_tracks_payload() {
+        let req = PlaylistTracksRequest::add(7001, vec![326904, 409926], "csrf");
+        assert_eq!(req.endpoint(), "/api/playlist/manipulate/tracks");
+        assert_eq!(
+            req.payload(),
+            json!({
+                "op": "add",
+                "pid": 7001,
+                "trackIds": "[326904,409926]",
+                "imme": "true",
+                "csrf_token": "csrf",
+            })
+        );
+    }
+
+    #[test]
+    fn remove_and_reorder_use_their_ops() {
+        let remove = PlaylistTracksRequest::remove(7001, vec![326904], "csrf");
+        assert_eq!(remove.payload()["op"].as_str(), Some("del"));
+        let reorder = PlaylistTracksRequest::reorder(7001, vec![409926, 326904], "csrf");
+        assert_eq!(reorder.payload()["op"].as_str(), Some("update"));
+        assert_eq!(
+            reorder.payload()["trackIds"].as_str(),
+            Some("[409926,326904]")
+        );
+    }
+
+    #[test]
+    fn duplicate_track_is_reported() {
+        let response: PlaylistTracksResponse =
+            serde_json::from_value(json!({ "code": 502, "message": "歌单内歌曲重复" })).unwrap();
+        assert_eq!(response.status(), WriteStatus::Duplicate);
+    }
+}
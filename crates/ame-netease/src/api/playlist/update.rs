@@ -0,0 +1,102 @@
+use serde_json::{Value, json};
+
+use crate::api::common::models::WriteResponse;
+use crate::api::request::ApiRequest;
+
+// One editable field of a playlist; each goes to its own endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlaylistUpdate {
+    Name(String),
+    Description(String),
+    Tags(Vec<String>),
+}
+
+pub struct PlaylistUpdateRequest {
+    id: i64,
+    update: PlaylistUpdate,
+    csrf_token: String,
+}
+
+impl PlaylistUpdateRequest {
+    pub fn new(id: i64, update: PlaylistUpdate, csrf_token: impl Into<String>) -> Self {
+        Self {
+            id,
+            update,
+            csrf_token: csrf_token.into(),
+        }
+    }
+
+    pub fn rename(id: i64, name: impl Into<String>, csrf_token: impl Into<String>) -> Self {
+        Self::new(id, PlaylistUpdate::Name(name.into()), csrf_token)
+    }
+
+    pub fn describe(id: i64, desc: impl Into<String>, csrf_token: impl Into<String>) -> Self {
+        Self::new(id, PlaylistUpdate::Description(desc.into()), csrf_token)
+    }
+
+    pub fn tag(id: i64, tags: Vec<String>, csrf_token: impl Into<String>) -> Self {
+        Self::new(id, PlaylistUpdate::Tags(tags), csrf_token)
+    }
+}
+
+impl ApiRequest for PlaylistUpdateRequest {
+    type Response = WriteResponse;
+
+    fn endpoint(&self) -> &'static str {
+        match self.update {
+            PlaylistUpdate::Name(_) => "/api/playlist/update/name",
+            PlaylistUpdate::Description(_) => "/api/playlist/desc/update",
+            PlaylistUpdate::Tags(_) => "/api/playlist/tags/update",
+        }
+    }
+
+    fn payload(&self) -> Value {
+        let mut payload = json!({
+            "id": self.id,
+            "csrf_token": self.csrf_token,
+        });
+        match &self.update {
+            PlaylistUpdate::Name(name) => payload["name"] = json!(name),
+            PlaylistUpdate::Description(desc) => payload["desc"] = json!(desc),
+            PlaylistUpdate::Tags(tags) => payload["tags"] = json!(tags.join(";")),
+        }
+        payload
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::PlaylistUpdateRequest;
+    use crate::api::request::ApiRequest;
+
+    #[test]
+    fn rename_payload() {
+        let req = PlaylistUpdateRequest::rename(7001, "New name", "csrf");
+        assert_eq!(req.endpoint(), "/api/playlist/update/name");
+        assert_eq!(
+            req.payload(),
+            json!({
+                "id": 7001,
+                "name": "New name",
+                "csrf_token": "csrf",
+            })
+        );
+    }
+
+    #[test]
+    fn description_payload() {
+        let req = PlaylistUpdateRequest::describe(7001, "For long drives", "csrf");
+        assert_eq!(req.endpoint(), "/api/playlist/desc/update");
+        assert_eq!(req.payload()["desc"].as_str(), Some("For long drives"));
+    }
+
+    #[test]
+    fn tags_are_joined_with_semicolons() {
+        let req =
+            PlaylistUpdateRequest::tag(7001, vec!["华语".to_string(), "流行".to_string()], "csrf");
+        assert_eq!(req.endpoint(), "/api/playlist/tags/update");
+        assert_eq!(req.payload()["tags"].as_str(), Some("华语;流行"));
+    }
+}
@@ -6,13 +6,26 @@ pub trait ApiRequest {
 
     fn endpoint(&self) -> &'static str;
     fn payload(&self) -> Value;
+
+    // Trailing path segment for routes that carry an id in the path, such as
+    // `/api/v1/album/{id}`.
+    fn path_param(&self) -> Option<String> {
+        None
+    }
+}
+
+pub(crate) fn request_path<R: ApiRequest>(req: &R) -> String {
+    match req.path_param() {
+        Some(param) => format!("{}/{param}", req.endpoint()),
+        None => req.endpoint().to_string(),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use serde_json::{Value, json};
 
-    use super::ApiRequest;
+    use super::{ApiRequest, request_path};
 
     struct DummyRequest;
 
@@ -37,4 +50,28 @@ mod tests {
         let endpoint = generic_accept(DummyRequest);
         assert_eq!(endpoint, "/dummy");
     }
+
+    #[test]
+    fn path_param_is_appended_to_the_endpoint() {
+        struct ById;
+
+        impl ApiRequest for ById {
+            type Response = Value;
+
+            fn endpoint(&self) -> &'static str {
+                "/api/v1/album"
+            }
+
+            fn payload(&self) -> Value {
+                json!({})
+            }
+
+            fn path_param(&self) -> Option<String> {
+                Some("32311".into())
+            }
+        }
+
+        assert_eq!(request_path(&DummyRequest), "/dummy");
+        assert_eq!(request_path(&ById), "/api/v1/album/32311");
+    }
 }
@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 
 use crate::api::request::ApiRequest;
+use crate::lyric::{Lyrics, parse_lrc, parse_yrc};
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct LyricDto {
@@ -21,6 +22,16 @@ pub struct TrackLyricResponse {
     #[serde(default)]
     pub klyric: LyricDto,
     #[serde(default)]
+    pub romalrc: LyricDto,
+    // Word-timed lyrics with their own translation and romanization, only
+    // returned by `/song/lyric/v1`.
+    #[serde(default)]
+    pub yrc: LyricDto,
+    #[serde(default)]
+    pub ytlrc: LyricDto,
+    #[serde(default)]
+    pub yromalrc: LyricDto,
+    #[serde(default)]
     pub qfy: Option<bool>,
     #[serde(default)]
     pub sfy: Option<bool>,
@@ -28,15 +39,39 @@ pub struct TrackLyricResponse {
     pub sgc: Option<bool>,
 }
 
-impl TrackLyricResponse {
-    pub fn main_lyric(&self) -> Option<&str> {
-        self.lrc
-            .lyric
+impl LyricDto {
+    pub fn text(&self) -> Option<&str> {
+        self.lyric
             .as_deref()
             .filter(|value| !value.trim().is_empty())
     }
 }
 
+impl TrackLyricResponse {
+    pub fn main_lyric(&self) -> Option<&str> {
+        self.lrc.text()
+    }
+
+    // The word-timed lyrics when there are any, otherwise the LRC ones, with
+    // translation and romanization merged in.
+    pub fn lyrics(&self) -> Lyrics {
+        let (main, translation, romanization) = match self.yrc.text() {
+            Some(yrc) => (
+                parse_yrc(yrc),
+                self.ytlrc.text().or(self.tlyric.text()),
+                self.yromalrc.text().or(self.romalrc.text()),
+            ),
+            None => (
+                parse_lrc(self.main_lyric().unwrap_or_default()),
+                self.tlyric.text(),
+                self.romalrc.text(),
+            ),
+        };
+        main.with_translation(parse_lrc(translation.unwrap_or_default()))
+            .with_romanization(parse_lrc(romanization.unwrap_or_default()))
+    }
+}
+
 pub struct TrackLyricRequest {
     pub id: i64,
 }
@@ -59,11 +94,43 @@ impl ApiRequest for TrackLyricRequest {
     }
 }
 
+pub struct TrackLyricV1Request {
+    pub id: i64,
+}
+
+impl TrackLyricV1Request {
+    pub fn new(id: i64) -> Self {
+        Self { id }
+    }
+}
+
+impl ApiRequest for TrackLyricV1Request {
+    type Response = TrackLyricResponse;
+
+    fn endpoint(&self) -> &'static str {
+        "/api/song/lyric/v1"
+    }
+
+    fn payload(&self) -> Value {
+        json!({
+            "id": self.id,
+            "cp": false,
+            "tv": 0,
+            "lv": 0,
+            "rv": 0,
+            "kv": 0,
+            "yv": 0,
+            "ytv": 0,
+            "yrv": 0,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::json;
 
-    use super::TrackLyricRequest;
+    use super::{TrackLyricRequest, TrackLyricResponse, TrackLyricV1Request};
     use crate::api::request::ApiRequest;
 
     #[test]
@@ -76,6 +143,61 @@ mod tests {
         );
     }
 
+    #[test]
+    fn lyric_v1_payload_asks_for_every_version() {
+        let req = TrackLyricV1Request::new(409926);
+        assert_eq!(req.endpoint(), "/api/song/lyric/v1");
+        assert_eq!(
+            req.payload(),
+            json!({
+                "id": 409926,
+                "cp": false,
+                "tv": 0,
+                "lv": 0,
+                "rv": 0,
+                "kv": 0,
+                "yv": 0,
+                "ytv": 0,
+                "yrv": 0,
+            })
+        );
+    }
+
+    #[test]
+    fn lyrics_prefer_yrc_and_its_translation() {
+        let response: TrackLyricResponse = serde_json::from_value(json!({
+            "code": 200,
+            "lrc": { "lyric": "[00:01.00]line" },
+            "tlyric": { "lyric": "[00:01.00]lrc translation" },
+            "yrc": { "lyric": "[1000,900](1000,400,0)li(1400,500,0)ne" },
+            "ytlrc": { "lyric": "[00:01.00]yrc translation" },
+            "romalrc": { "lyric": "[00:01.00]roma" }
+        }))
+        .unwrap();
+        let lyrics = response.lyrics();
+        assert_eq!(lyrics.lines.len(), 1);
+        assert_eq!(lyrics.lines[0].words.len(), 2);
+        assert_eq!(
+            lyrics.lines[0].translation.as_deref(),
+            Some("yrc translation")
+        );
+        assert_eq!(lyrics.lines[0].romanization.as_deref(), Some("roma"));
+    }
+
+    #[test]
+    fn lyrics_fall_back_to_lrc() {
+        let response: TrackLyricResponse = serde_json::from_value(json!({
+            "code": 200,
+            "lrc": { "lyric": "[00:01.00]line" },
+            "tlyric": { "lyric": "[00:01.00]翻译" },
+            "yrc": { "lyric": "  " }
+        }))
+        .unwrap();
+        let lyrics = response.lyrics();
+        assert!(!lyrics.has_word_timing());
+        assert_eq!(lyrics.lines[0].translation.as_deref(), Some("翻译"));
+    }
+
     #[tokio::test]
     async fn live_track_lyric_request() {
         let client = crate::NeteaseClient::new();
@@ -31,6 +31,14 @@ pub struct TrackFreeTimeTrialPrivilegeDto {
     pub user_consumable: Option<bool>,
 }
 
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TrackFreeTrialInfoDto {
+    #[serde(default)]
+    pub start: Option<u64>,
+    #[serde(default)]
+    pub end: Option<u64>,
+}
+
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct TrackUrlDto {
     pub id: i64,
@@ -54,6 +62,8 @@ pub struct TrackUrlDto {
     pub size: Option<u64>,
     #[serde(default)]
     pub time: Option<u64>,
+    #[serde(default, rename = "freeTrialInfo")]
+    pub free_trial_info: Option<TrackFreeTrialInfoDto>,
     #[serde(default, rename = "freeTrialPrivilege")]
     pub free_trial_privilege: Option<TrackFreeTrialPrivilegeDto>,
     #[serde(default, rename = "freeTimeTrialPrivilege")]
@@ -67,20 +77,136 @@ pub struct TrackUrlResponse {
     pub data: Vec<TrackUrlDto>,
 }
 
+// Quality levels accepted by `/song/enhance/player/url/v1`, from the lowest
+// bitrate up. `Jyeffect` and `Sky` are surround mixes rather than higher
+// fidelity, so fallback skips over them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioQuality {
+    Standard,
+    Higher,
+    #[default]
+    Exhigh,
+    Lossless,
+    Hires,
+    Jyeffect,
+    Sky,
+    Jymaster,
+}
+
+impl AudioQuality {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Higher => "higher",
+            Self::Exhigh => "exhigh",
+            Self::Lossless => "lossless",
+            Self::Hires => "hires",
+            Self::Jyeffect => "jyeffect",
+            Self::Sky => "sky",
+            Self::Jymaster => "jymaster",
+        }
+    }
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Standard => "标准",
+            Self::Higher => "较高",
+            Self::Exhigh => "极高",
+            Self::Lossless => "无损",
+            Self::Hires => "Hi-Res",
+            Self::Jyeffect => "高清环绕声",
+            Self::Sky => "沉浸环绕声",
+            Self::Jymaster => "超清母带",
+        }
+    }
+
+    pub const fn variants() -> [Self; 8] {
+        [
+            Self::Standard,
+            Self::Higher,
+            Self::Exhigh,
+            Self::Lossless,
+            Self::Hires,
+            Self::Jyeffect,
+            Self::Sky,
+            Self::Jymaster,
+        ]
+    }
+
+    pub fn from_level(level: &str) -> Option<Self> {
+        Self::variants()
+            .into_iter()
+            .find(|quality| quality.as_str().eq_ignore_ascii_case(level.trim()))
+    }
+
+    // Closest level for a bitrate in bits per second, for responses that omit
+    // `level`. Anything above 320k is at least lossless.
+    pub const fn from_bitrate(br: u64) -> Self {
+        match br {
+            0..=128_000 => Self::Standard,
+            128_001..=192_000 => Self::Higher,
+            192_001..=320_000 => Self::Exhigh,
+            _ => Self::Lossless,
+        }
+    }
+
+    // The level to ask for when this one is not granted.
+    pub const fn fallback(self) -> Option<Self> {
+        match self {
+            Self::Jymaster | Self::Sky | Self::Jyeffect => Some(Self::Hires),
+            Self::Hires => Some(Self::Lossless),
+            Self::Lossless => Some(Self::Exhigh),
+            Self::Exhigh => Some(Self::Higher),
+            Self::Higher => Some(Self::Standard),
+            Self::Standard => None,
+        }
+    }
+}
+
+impl TrackUrlDto {
+    pub fn playable_url(&self) -> Option<&str> {
+        self.url
+            .as_deref()
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+    }
+
+    // Level the server actually handed out, which may be below the requested
+    // one when the account is not entitled to it.
+    pub fn granted_quality(&self) -> Option<AudioQuality> {
+        self.level
+            .as_deref()
+            .and_then(AudioQuality::from_level)
+            .or_else(|| self.br.filter(|br| *br > 0).map(AudioQuality::from_bitrate))
+    }
+
+    // Only a preview clip is returned.
+    pub fn is_trial(&self) -> bool {
+        self.free_trial_info.is_some()
+    }
+
+    // Songs that need a VIP subscription (1) or an album purchase (4). When
+    // their URL is missing, lower levels are not granted either.
+    pub fn requires_purchase(&self) -> bool {
+        matches!(self.fee, Some(1 | 4))
+    }
+}
+
 pub struct TrackUrlRequest {
     pub ids: Vec<i64>,
-    pub level: String, // TODO: enum化
+    pub level: AudioQuality,
 }
 
 impl TrackUrlRequest {
     pub fn new(ids: Vec<i64>) -> Self {
         Self {
             ids,
-            level: "exhigh".to_string(),
+            level: AudioQuality::default(),
         }
     }
 
-    pub fn with_level(ids: Vec<i64>, level: String) -> Self {
+    pub fn with_level(ids: Vec<i64>, level: AudioQuality) -> Self {
         Self { ids, level }
     }
 }
@@ -102,11 +228,11 @@ impl ApiRequest for TrackUrlRequest {
                     .collect::<Vec<String>>()
                     .join(",")
             ),
-            "level": self.level,
+            "level": self.level.as_str(),
             "encodeType": "flac"
         });
 
-        if self.level == "sky" {
+        if self.level == AudioQuality::Sky {
             payload["immerseType"] = json!("c51");
         }
 
@@ -116,7 +242,9 @@ impl ApiRequest for TrackUrlRequest {
 
 #[cfg(test)]
 mod tests {
-    use super::TrackUrlRequest;
+    use serde_json::json;
+
+    use super::{AudioQuality, TrackUrlDto, TrackUrlRequest};
     use crate::api::request::ApiRequest;
 
     #[test]
@@ -131,11 +259,63 @@ mod tests {
 
     #[test]
     fn sky_level_sets_immerse_type() {
-        let req = TrackUrlRequest::with_level(vec![409926], "sky".to_string());
+        let req = TrackUrlRequest::with_level(vec![409926], AudioQuality::Sky);
         let payload = req.payload();
         assert_eq!(payload["immerseType"].as_str(), Some("c51"));
     }
 
+    #[test]
+    fn quality_levels_round_trip_and_fall_back_past_surround() {
+        for quality in AudioQuality::variants() {
+            assert_eq!(AudioQuality::from_level(quality.as_str()), Some(quality));
+        }
+        assert_eq!(AudioQuality::from_level("none"), None);
+        assert_eq!(AudioQuality::Sky.fallback(), Some(AudioQuality::Hires));
+
+        let mut chain = vec![AudioQuality::Jymaster];
+        while let Some(next) = chain.last().and_then(|quality| quality.fallback()) {
+            chain.push(next);
+        }
+        assert_eq!(
+            chain,
+            [
+                AudioQuality::Jymaster,
+                AudioQuality::Hires,
+                AudioQuality::Lossless,
+                AudioQuality::Exhigh,
+                AudioQuality::Higher,
+                AudioQuality::Standard,
+            ]
+        );
+    }
+
+    #[test]
+    fn granted_quality_prefers_level_over_bitrate() {
+        let item: TrackUrlDto = serde_json::from_value(json!({
+            "id": 409926,
+            "url": "https://m701.music.126.net/a.mp3",
+            "level": "exhigh",
+            "br": 320000,
+            "fee": 8
+        }))
+        .unwrap();
+        assert_eq!(item.granted_quality(), Some(AudioQuality::Exhigh));
+        assert!(!item.is_trial());
+
+        let item: TrackUrlDto = serde_json::from_value(json!({
+            "id": 409926,
+            "url": "",
+            "br": 128000,
+            "fee": 1,
+            "freeTrialInfo": { "start": 0, "end": 30 }
+        }))
+        .unwrap();
+        assert_eq!(item.playable_url(), None);
+        assert_eq!(item.granted_quality(), Some(AudioQuality::Standard));
+        assert!(item.is_trial());
+        assert!(item.requires_purchase());
+    }
+
     #[tokio::test]
     async fn live_eapi_song_url_v1_request() {
         let client = crate::NeteaseClient::new();
@@ -1,4 +1,4 @@
-use crate::api::request::ApiRequest;
+use crate::api::request::{ApiRequest, request_path};
 use crate::crypto::{eapi, weapi};
 use reqwest::{Client, header::SET_COOKIE};
 use serde_json::Value;
@@ -50,15 +50,15 @@ impl NeteaseClient {
     }
 
     pub async fn eapi_request<R: ApiRequest>(&self, req: R) -> Result<R::Response, Error> {
-        let endpoint = req.endpoint();
-        let route = strip_api_prefix(endpoint);
+        let endpoint = request_path(&req);
+        let route = strip_api_prefix(&endpoint);
         let mut params = req.payload();
         let url = format!("{}{}", EAPI_BASE, route);
         let cookie_pairs = normalize_cookie_pairs(&self.cookie);
         let eapi_header = build_eapi_header(&cookie_pairs);
         attach_eapi_header(&mut params, eapi_header.clone());
         let cookie_header = cookie_string_from_pairs(&eapi_header);
-        let encrypted = eapi::encrypt(&eapi_encrypt_path(endpoint), &params.to_string());
+        let encrypted = eapi::encrypt(&eapi_encrypt_path(&endpoint), &params.to_string());
 
         let resp: reqwest::Response = self
             .client
@@ -92,8 +92,8 @@ impl NeteaseClient {
     }
 
     pub async fn weapi_request<R: ApiRequest>(&self, req: R) -> Result<R::Response, Error> {
-        let endpoint = req.endpoint();
-        let route = strip_api_prefix(endpoint);
+        let endpoint = request_path(&req);
+        let route = strip_api_prefix(&endpoint);
         let params = req.payload();
         let url = format!("{}{}", WEAPI_BASE, route);
         let payload = weapi::encrypt(&params.to_string());
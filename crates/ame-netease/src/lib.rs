@@ -1,6 +1,8 @@
 pub mod api;
 pub mod client;
 pub mod crypto;
+pub mod lyric;
 
 pub use client::{Error as ClientError, NeteaseClient};
 pub use crypto::{Error as CryptoError, WeapiPayload, eapi_decrypt, eapi_encrypt, weapi_encrypt};
+pub use lyric::{LyricLine, LyricWord, Lyrics};
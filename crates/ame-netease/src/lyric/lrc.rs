@@ -0,0 +1,145 @@
+use super::{LyricLine, Lyrics, finish, parse_json_line};
+
+// Parses LRC text. Lines may carry several timestamps, which repeat their text
+// at each time; `[mm:ss]`, `[mm:ss.x]` to `[mm:ss.xxx]` and `[mm:ss:xx]` are
+// accepted. Anything that does not parse is skipped.
+pub fn parse_lrc(raw: &str) -> Lyrics {
+    let mut lyrics = Lyrics::default();
+    let mut offset_ms = 0_i64;
+    for line in raw.lines() {
+        let line = line.trim();
+        if line.starts_with('{') {
+            lyrics.lines.extend(parse_json_line(line));
+            continue;
+        }
+
+        let mut rest = line;
+        let mut stamps = Vec::new();
+        while let Some(tag) = rest.strip_prefix('[') {
+            let Some((tag, after)) = tag.split_once(']') else {
+                break;
+            };
+            rest = after;
+            if let Some(ms) = parse_timestamp(tag) {
+                stamps.push(ms);
+            } else if let Some((key, value)) = tag.split_once(':') {
+                let key = key.trim().to_ascii_lowercase();
+                if key == "offset" {
+                    offset_ms = value.trim().parse().unwrap_or(0);
+                } else {
+                    lyrics.tags.insert(key, value.trim().to_string());
+                }
+            }
+        }
+        let text = rest.trim();
+        lyrics
+            .lines
+            .extend(stamps.into_iter().map(|start_ms| LyricLine {
+                start_ms,
+                text: text.to_string(),
+                ..LyricLine::default()
+            }));
+    }
+
+    // A positive offset shows the lyrics earlier.
+    if offset_ms != 0 {
+        for line in &mut lyrics.lines {
+            line.start_ms = line.start_ms.saturating_add_signed(-offset_ms);
+        }
+    }
+    finish(lyrics)
+}
+
+fn parse_timestamp(tag: &str) -> Option<u64> {
+    let (minutes, rest) = tag.trim().split_once(':')?;
+    let (seconds, fraction) = rest.split_once(['.', ':']).unwrap_or((rest, ""));
+    let minutes = digits(minutes)?;
+    let seconds = digits(seconds)?;
+    let fraction_ms = if fraction.is_empty() {
+        0
+    } else {
+        digits(fraction)?;
+        let fraction = &fraction[..fraction.len().min(3)];
+        fraction.parse::<u64>().ok()? * 10_u64.pow(3 - fraction.len() as u32)
+    };
+    Some(minutes * 60_000 + seconds * 1_000 + fraction_ms)
+}
+
+fn digits(value: &str) -> Option<u64> {
+    if value.is_empty() || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    value.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_lrc, parse_timestamp};
+
+    #[test]
+    fn timestamps_accept_common_precisions() {
+        assert_eq!(parse_timestamp("01:02"), Some(62_000));
+        assert_eq!(parse_timestamp("01:02.5"), Some(62_500));
+        assert_eq!(parse_timestamp("01:02.34"), Some(62_340));
+        assert_eq!(parse_timestamp("01:02.345"), Some(62_345));
+        assert_eq!(parse_timestamp("01:02:34"), Some(62_340));
+        assert_eq!(parse_timestamp("120:00.00"), Some(7_200_000));
+        assert_eq!(parse_timestamp("ti:Title"), None);
+        assert_eq!(parse_timestamp("01:xx.00"), None);
+    }
+
+    #[test]
+    fn repeated_timestamps_expand_and_sort() {
+        let lyrics = parse_lrc("[00:30.00][00:05.00]chorus\n[00:10.00]verse");
+        let lines: Vec<_> = lyrics
+            .lines
+            .iter()
+            .map(|line| (line.start_ms, line.text.as_str()))
+            .collect();
+        assert_eq!(
+            lines,
+            [(5_000, "chorus"), (10_000, "verse"), (30_000, "chorus")]
+        );
+    }
+
+    #[test]
+    fn tags_are_collected_and_offset_shifts_lines() {
+        let lyrics = parse_lrc(
+            "[ti:Title]\n[AR: Artist ]\n[offset:+500]\n[00:00.20]first\n[00:10.00]second\n[by:]",
+        );
+        assert_eq!(lyrics.tags.get("ti").map(String::as_str), Some("Title"));
+        assert_eq!(lyrics.tags.get("ar").map(String::as_str), Some("Artist"));
+        assert_eq!(lyrics.tags.get("by").map(String::as_str), Some(""));
+        assert!(!lyrics.tags.contains_key("offset"));
+        assert_eq!(lyrics.lines[0].start_ms, 0);
+        assert_eq!(lyrics.lines[1].start_ms, 9_500);
+    }
+
+    #[test]
+    fn negative_offset_delays_lines() {
+        let lyrics = parse_lrc("[offset:-250]\n[00:01.00]late");
+        assert_eq!(lyrics.lines[0].start_ms, 1_250);
+    }
+
+    #[test]
+    fn credits_json_and_junk_lines() {
+        let lyrics = parse_lrc(
+            "{\"t\":0,\"c\":[{\"tx\":\"作词: \"},{\"tx\":\"某人\"}]}\nno timestamp here\n[00:0a.00]bad\n[00:01.00] spaced \n[00:02.00",
+        );
+        let lines: Vec<_> = lyrics
+            .lines
+            .iter()
+            .map(|line| (line.start_ms, line.text.as_str()))
+            .collect();
+        assert_eq!(lines, [(0, "作词: 某人"), (1_000, "spaced")]);
+    }
+
+    #[test]
+    fn empty_lines_are_kept_as_breaks() {
+        let lyrics = parse_lrc("[00:01.00]sing\n[00:04.00]\n");
+        assert_eq!(lyrics.lines.len(), 2);
+        assert!(lyrics.lines[1].text.is_empty());
+        assert!(!lyrics.is_empty());
+        assert!(parse_lrc("").is_empty());
+    }
+}
@@ -0,0 +1,177 @@
+mod lrc;
+mod yrc;
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+pub use lrc::parse_lrc;
+pub use yrc::parse_yrc;
+
+// How far a translation or romanization may be timed from the line it belongs
+// to. NetEase rounds them to 10ms and sometimes to the LRC rather than the YRC
+// line start.
+const MERGE_TOLERANCE_MS: u64 = 300;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LyricWord {
+    pub start_ms: u64,
+    pub duration_ms: u64,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LyricLine {
+    pub start_ms: u64,
+    // Only YRC lines carry their length.
+    pub duration_ms: Option<u64>,
+    pub text: String,
+    // Empty unless the line came from YRC.
+    pub words: Vec<LyricWord>,
+    pub translation: Option<String>,
+    pub romanization: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Lyrics {
+    // ID tags such as `ti`, `ar`, `al` and `by`, keyed in lowercase. An
+    // `offset` tag is applied to the line times rather than kept here.
+    pub tags: BTreeMap<String, String>,
+    // Sorted by start time. Lines with empty text mark instrumental breaks.
+    pub lines: Vec<LyricLine>,
+}
+
+impl Lyrics {
+    pub fn is_empty(&self) -> bool {
+        self.lines.iter().all(|line| line.text.is_empty())
+    }
+
+    pub fn has_word_timing(&self) -> bool {
+        self.lines.iter().any(|line| !line.words.is_empty())
+    }
+
+    pub fn with_translation(mut self, translation: Lyrics) -> Self {
+        merge(&mut self.lines, translation, |line, text| {
+            line.translation = Some(text);
+        });
+        self
+    }
+
+    pub fn with_romanization(mut self, romanization: Lyrics) -> Self {
+        merge(&mut self.lines, romanization, |line, text| {
+            line.romanization = Some(text);
+        });
+        self
+    }
+
+    // Index of the line playing at `position_ms`.
+    pub fn line_at(&self, position_ms: u64) -> Option<usize> {
+        self.lines
+            .partition_point(|line| line.start_ms <= position_ms)
+            .checked_sub(1)
+    }
+}
+
+// Attaches each line of `other` to the main line closest in time, skipping
+// breaks and the `//` NetEase uses for "no translation".
+fn merge(lines: &mut [LyricLine], other: Lyrics, assign: impl Fn(&mut LyricLine, String)) {
+    let targets: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !line.text.is_empty())
+        .map(|(index, _)| index)
+        .collect();
+    for extra in other.lines {
+        let text = extra.text.trim();
+        if text.is_empty() || text == "//" {
+            continue;
+        }
+        let after = targets.partition_point(|&index| lines[index].start_ms < extra.start_ms);
+        let nearest = [after.checked_sub(1), Some(after)]
+            .into_iter()
+            .flatten()
+            .filter_map(|slot| targets.get(slot).copied())
+            .min_by_key(|&index| lines[index].start_ms.abs_diff(extra.start_ms));
+        if let Some(index) = nearest
+            && lines[index].start_ms.abs_diff(extra.start_ms) <= MERGE_TOLERANCE_MS
+        {
+            assign(&mut lines[index], text.to_string());
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonLine {
+    t: u64,
+    #[serde(default)]
+    c: Vec<JsonChunk>,
+}
+
+#[derive(Deserialize)]
+struct JsonChunk {
+    #[serde(default)]
+    tx: String,
+}
+
+// Credit lines such as `{"t":0,"c":[{"tx":"作词: "},{"tx":"某人"}]}`, which
+// both LRC and YRC mix in with the timed lines.
+fn parse_json_line(line: &str) -> Option<LyricLine> {
+    let parsed: JsonLine = serde_json::from_str(line).ok()?;
+    let text: String = parsed.c.iter().map(|chunk| chunk.tx.as_str()).collect();
+    Some(LyricLine {
+        start_ms: parsed.t,
+        text: text.trim().to_string(),
+        ..LyricLine::default()
+    })
+}
+
+fn finish(mut lyrics: Lyrics) -> Lyrics {
+    lyrics.lines.sort_by_key(|line| line.start_ms);
+    lyrics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_lrc, parse_yrc};
+
+    #[test]
+    fn translation_and_romanization_merge_by_nearest_time() {
+        let main = parse_yrc(
+            "[1000,2000](1000,1000,0)君の(2000,1000,0)声\n[3500,1500](3500,1500,0)夜\n[6000,1000](6000,1000,0)朝",
+        );
+        let translation = parse_lrc("[00:01.02]你的声音\n[00:03.40]夜晚\n[00:06.00]//");
+        let romanization = parse_lrc("[00:01.00]kimi no koe\n[00:09.00]too far away");
+
+        let lyrics = main
+            .with_translation(translation)
+            .with_romanization(romanization);
+        assert_eq!(lyrics.lines[0].translation.as_deref(), Some("你的声音"));
+        assert_eq!(lyrics.lines[0].romanization.as_deref(), Some("kimi no koe"));
+        assert_eq!(lyrics.lines[1].translation.as_deref(), Some("夜晚"));
+        assert_eq!(lyrics.lines[2].translation, None);
+        assert!(
+            lyrics
+                .lines
+                .iter()
+                .skip(1)
+                .all(|line| line.romanization.is_none())
+        );
+    }
+
+    #[test]
+    fn merge_skips_instrumental_breaks() {
+        let main = parse_lrc("[00:10.00]line\n[00:12.00]\n[00:12.20]next");
+        let lyrics = main.with_translation(parse_lrc("[00:12.10]下一句"));
+        assert_eq!(lyrics.lines[1].translation, None);
+        assert_eq!(lyrics.lines[2].translation.as_deref(), Some("下一句"));
+    }
+
+    #[test]
+    fn line_at_finds_the_current_line() {
+        let lyrics = parse_lrc("[00:01.00]a\n[00:02.00]b\n[00:03.00]c");
+        assert_eq!(lyrics.line_at(500), None);
+        assert_eq!(lyrics.line_at(1_000), Some(0));
+        assert_eq!(lyrics.line_at(2_999), Some(1));
+        assert_eq!(lyrics.line_at(60_000), Some(2));
+    }
+}
@@ -0,0 +1,167 @@
+use super::{LyricLine, LyricWord, Lyrics, finish, parse_json_line};
+
+// Parses NetEase word-timed lyrics: `[start,duration]` per line followed by
+// `(start,duration,0)word` per word, all in milliseconds. Parentheses that do
+// not hold a timing stay part of the word text.
+pub fn parse_yrc(raw: &str) -> Lyrics {
+    let mut lyrics = Lyrics::default();
+    for line in raw.lines() {
+        let line = line.trim();
+        if line.starts_with('{') {
+            lyrics.lines.extend(parse_json_line(line));
+            continue;
+        }
+        let Some((head, rest)) = line.strip_prefix('[').and_then(|line| line.split_once(']'))
+        else {
+            continue;
+        };
+        let Some((start_ms, duration_ms)) = parse_timing(head) else {
+            if let Some((key, value)) = head.split_once(':') {
+                lyrics
+                    .tags
+                    .insert(key.trim().to_ascii_lowercase(), value.trim().to_string());
+            }
+            continue;
+        };
+
+        let mut words = parse_words(rest);
+        // Older klyric-style lines time words from the line start.
+        if words.first().is_some_and(|word| word.start_ms < start_ms) {
+            for word in &mut words {
+                word.start_ms += start_ms;
+            }
+        }
+        let text = if words.is_empty() {
+            rest.trim().to_string()
+        } else {
+            words
+                .iter()
+                .map(|word| word.text.as_str())
+                .collect::<String>()
+                .trim()
+                .to_string()
+        };
+        lyrics.lines.push(LyricLine {
+            start_ms,
+            duration_ms: Some(duration_ms),
+            text,
+            words,
+            ..LyricLine::default()
+        });
+    }
+    finish(lyrics)
+}
+
+// `start,duration` with any further fields ignored.
+fn parse_timing(value: &str) -> Option<(u64, u64)> {
+    let mut fields = value.split(',');
+    let start = fields.next()?.trim().parse().ok()?;
+    let duration = fields.next()?.trim().parse().ok()?;
+    if fields.any(|field| field.trim().parse::<i64>().is_err()) {
+        return None;
+    }
+    Some((start, duration))
+}
+
+// The first `(start,duration,..)` group in `value`: where it opens, where it
+// ends, and its timing.
+fn next_timing(value: &str) -> Option<(usize, usize, u64, u64)> {
+    let mut from = 0;
+    while let Some(open) = value[from..].find('(').map(|index| from + index) {
+        let close = open + value[open..].find(')')?;
+        if let Some((start, duration)) = parse_timing(&value[open + 1..close]) {
+            return Some((open, close + 1, start, duration));
+        }
+        from = open + 1;
+    }
+    None
+}
+
+fn parse_words(mut rest: &str) -> Vec<LyricWord> {
+    let mut words = Vec::new();
+    let Some((_, mut end, mut start_ms, mut duration_ms)) = next_timing(rest) else {
+        return words;
+    };
+    loop {
+        let tail = &rest[end..];
+        let next = next_timing(tail);
+        let text = next.map_or(tail, |(open, ..)| &tail[..open]);
+        words.push(LyricWord {
+            start_ms,
+            duration_ms,
+            text: text.to_string(),
+        });
+        let Some((_, next_end, next_start, next_duration)) = next else {
+            return words;
+        };
+        rest = tail;
+        (end, start_ms, duration_ms) = (next_end, next_start, next_duration);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LyricWord, parse_yrc};
+
+    fn word(start_ms: u64, duration_ms: u64, text: &str) -> LyricWord {
+        LyricWord {
+            start_ms,
+            duration_ms,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn words_carry_their_own_timing() {
+        let lyrics = parse_yrc("[1200,1500](1200,500,0)Hello (1700,1000,0)world");
+        let line = &lyrics.lines[0];
+        assert_eq!(line.start_ms, 1_200);
+        assert_eq!(line.duration_ms, Some(1_500));
+        assert_eq!(line.text, "Hello world");
+        assert_eq!(
+            line.words,
+            [word(1_200, 500, "Hello "), word(1_700, 1_000, "world")]
+        );
+        assert!(lyrics.has_word_timing());
+    }
+
+    #[test]
+    fn literal_parentheses_stay_in_the_text() {
+        let lyrics = parse_yrc("[0,900](0,400,0)Stay (300,500,0)(oh) (yeah)");
+        assert_eq!(
+            lyrics.lines[0].words,
+            [word(0, 400, "Stay "), word(300, 500, "(oh) (yeah)")]
+        );
+        assert_eq!(lyrics.lines[0].text, "Stay (oh) (yeah)");
+    }
+
+    #[test]
+    fn relative_word_times_are_moved_to_the_line() {
+        let lyrics = parse_yrc("[5000,800](0,300)one(300,500)two");
+        assert_eq!(
+            lyrics.lines[0].words,
+            [word(5_000, 300, "one"), word(5_300, 500, "two")]
+        );
+    }
+
+    #[test]
+    fn credits_tags_and_broken_lines() {
+        let lyrics = parse_yrc(
+            "{\"t\":0,\"c\":[{\"tx\":\"作曲: \"},{\"tx\":\"某人\"}]}\n[ti:Title]\n[abc,10](0,1,0)x\n[3000,500]plain text\n[4000,500](4000,500,0",
+        );
+        assert_eq!(lyrics.tags.get("ti").map(String::as_str), Some("Title"));
+        let lines: Vec<_> = lyrics
+            .lines
+            .iter()
+            .map(|line| (line.start_ms, line.text.as_str(), line.words.len()))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                (0, "作曲: 某人", 0),
+                (3_000, "plain text", 0),
+                (4_000, "(4000,500,0", 0)
+            ]
+        );
+    }
+}